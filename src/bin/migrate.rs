@@ -0,0 +1,32 @@
+// converts a legacy column workload into the structured toml format:
+// migrate tasks.txt [tasks.toml]
+
+extern crate rust_cfs as cfs;
+
+use cfs::workload::{legacy, spec};
+
+use std::fs;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("usage: migrate <legacy workload> [output.toml]");
+        process::exit(2);
+    }
+
+    let src = fs::read_to_string(&args[0]).unwrap_or_else(|err| {
+        eprintln!("Could not read {}: {}", args[0], err);
+        process::exit(1);
+    });
+    let tasks = legacy::parse(&src).unwrap_or_else(|err| {
+        eprintln!("{}: {}", args[0], err);
+        process::exit(1);
+    });
+
+    let out = spec::to_toml(&tasks);
+    match args.get(1) {
+        Some(path) => fs::write(path, out).expect("Unable to write the migrated workload"),
+        None => print!("{}", out)
+    };
+}
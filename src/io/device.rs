@@ -0,0 +1,221 @@
+// a shared i/o device, tasks queue requests on it and block until
+// their request has been dispatched and served
+
+use crate::proc::dist::{Distribution, Rng};
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IoKind {
+    Read,
+    Write,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum IoPolicy {
+    // requests are served in the order they were submitted
+    #[default]
+    Fifo,
+    // the request whose deadline is closest is served first, reads
+    // expire sooner than writes so that they are not stuck behind them
+    Deadline,
+}
+
+impl FromStr for IoPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fifo" | "none" => Ok(IoPolicy::Fifo),
+            "deadline" | "mq-deadline" => Ok(IoPolicy::Deadline),
+            _ => Err(format!("Unknown i/o scheduling policy {:?}", s))
+        }
+    }
+}
+
+impl fmt::Display for IoPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IoPolicy::Fifo => write!(f, "fifo"),
+            IoPolicy::Deadline => write!(f, "deadline")
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IoRequest {
+    pub id: u16,
+    pub kind: IoKind,
+    pub submit: u128,
+    pub deadline: u128,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DeviceStats {
+    pub nr_requests: u64,
+    pub nr_reads: u64,
+    pub nr_writes: u64,
+    // ticks with at least one request in service
+    pub busy_ticks: u64,
+    pub wait_sum: u128,
+    pub wait_max: u128,
+    pub service_sum: u128,
+    pub max_queue: usize,
+    pub nr_expired: u64,
+}
+
+impl DeviceStats {
+    // mean ticks a request spent queued before it was dispatched
+    pub fn mean_wait(&self) -> f64 {
+        if self.nr_requests == 0 {
+            return 0.0;
+        }
+        self.wait_sum as f64 / self.nr_requests as f64
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Device {
+    name: String,
+    service: Distribution,
+    policy: IoPolicy,
+    read_expire: u64,
+    write_expire: u64,
+    depth: usize,
+    queue: Vec<IoRequest>,
+    // requests being served, with the time they are done
+    in_service: Vec<(u128, IoRequest)>,
+    rng: Rng,
+    stats: DeviceStats,
+}
+
+impl Device {
+    pub fn new(name: &str, service: Distribution, policy: IoPolicy) -> Self {
+        if let Err(err) = service.validate() {
+            panic!("Invalid service time for device {:?}: {}", name, err);
+        }
+
+        Self {
+            name: name.to_string(),
+            service,
+            policy,
+            read_expire: 50,
+            write_expire: 500,
+            depth: 1,
+            queue: Vec::new(),
+            in_service: Vec::new(),
+            rng: Rng::new(0),
+            stats: DeviceStats::default()
+        }
+    }
+
+    // how many requests the device serves at once
+    pub fn set_depth(&mut self, depth: usize) {
+        if depth == 0 {
+            panic!("Device {:?} needs a depth of at least one", self.name);
+        }
+        self.depth = depth;
+    }
+
+    pub fn set_read_expire(&mut self, ticks: u64) {
+        self.read_expire = ticks;
+    }
+
+    pub fn set_write_expire(&mut self, ticks: u64) {
+        self.write_expire = ticks;
+    }
+
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    #[inline]
+    pub fn get_name(&self) -> &str { &self.name }
+
+    #[inline]
+    pub fn get_service(&self) -> &Distribution { &self.service }
+
+    #[inline]
+    pub fn get_policy(&self) -> IoPolicy { self.policy }
+
+    #[inline]
+    pub fn get_read_expire(&self) -> u64 { self.read_expire }
+
+    #[inline]
+    pub fn get_write_expire(&self) -> u64 { self.write_expire }
+
+    #[inline]
+    pub fn get_depth(&self) -> usize { self.depth }
+
+    #[inline]
+    pub fn get_stats(&self) -> DeviceStats { self.stats }
+
+    // requests waiting to be dispatched
+    pub fn get_queue(&self) -> &[IoRequest] {
+        &self.queue
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.queue.is_empty() && self.in_service.is_empty()
+    }
+
+    // drops a request that has not been dispatched yet
+    pub fn cancel(&mut self, id: u16) {
+        self.queue.retain(|request| request.id != id);
+    }
+
+    pub fn submit(&mut self, id: u16, kind: IoKind, now: u128) {
+        let expire = match kind {
+            IoKind::Read => self.read_expire,
+            IoKind::Write => self.write_expire
+        };
+        self.queue.push(IoRequest { id, kind, submit: now, deadline: now + expire as u128 });
+
+        self.stats.nr_requests += 1;
+        match kind {
+            IoKind::Read => self.stats.nr_reads += 1,
+            IoKind::Write => self.stats.nr_writes += 1
+        };
+        self.stats.max_queue = self.stats.max_queue.max(self.queue.len());
+    }
+
+    fn dispatch_idx(&self) -> usize {
+        match self.policy {
+            IoPolicy::Fifo => 0,
+            IoPolicy::Deadline => {
+                (0..self.queue.len())
+                    .min_by_key(|idx| (self.queue[*idx].deadline, *idx))
+                    .unwrap()
+            }
+        }
+    }
+
+    // finishes the requests that are done and dispatches queued ones into
+    // the free slots, returning the ids of the tasks whose request is done
+    pub fn tick(&mut self, now: u128) -> Vec<u16> {
+        let (done, in_service): (Vec<_>, Vec<_>) = self.in_service
+            .drain(..)
+            .partition(|(finish, _)| *finish <= now);
+        self.in_service = in_service;
+
+        while self.in_service.len() < self.depth && !self.queue.is_empty() {
+            let request = self.queue.remove(self.dispatch_idx());
+            let service = self.service.sample(&mut self.rng).max(1);
+
+            let wait = now - request.submit;
+            self.stats.wait_sum += wait;
+            self.stats.wait_max = self.stats.wait_max.max(wait);
+            self.stats.service_sum += service as u128;
+            if now > request.deadline {
+                self.stats.nr_expired += 1;
+            }
+            self.in_service.push((now + service as u128, request));
+        }
+        if !self.in_service.is_empty() {
+            self.stats.busy_ticks += 1;
+        }
+
+        done.into_iter().map(|(_, request)| request.id).collect()
+    }
+}
@@ -1,2 +1,7 @@
 pub mod proc;
 pub mod sched;
+pub mod workload;
+pub mod io;
+pub mod sync;
+pub mod stats;
+pub mod trace;
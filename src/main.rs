@@ -1,42 +1,236 @@
 extern crate rust_cfs as cfs;
 
+use cfs::proc::signal::Signal;
 use cfs::sched::scheduler::Scheduler;
-use cfs::proc::task::TaskChar;
+use cfs::sched::dynamic::DynamicWeight;
+use cfs::sched::features::SchedFeatures;
+use cfs::stats::series::{Table, TimeSeries};
+use cfs::trace::gantt::{Gantt, GanttRows};
+use cfs::trace::{chrome, ftrace};
+use cfs::workload;
 
-use std::io::Read;
-use std::fs::File;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::str::FromStr;
 
-fn main() {
-    let mut file = File::open("tasks.txt").expect("Could not find tasks file");
-    let mut file_str = String::new();
-    file.read_to_string(&mut file_str).expect("Unable to write contents of tasks file to string");
-
-    let task_lines_split = file_str.as_str().split("\n");
-    let task_lines: Vec<&str> = task_lines_split.collect::<Vec<&str>>();
-    
-    let mut task_props = vec![];
-    for line in task_lines {
-        let props_split = line.split_whitespace().take(4);
-        task_props.push(props_split.collect::<Vec<&str>>());
-    }
-
-    let mut tasks: Vec<TaskChar> = Vec::new();
-    
-    let mut idx: u16 = 1;
-    for task in task_props {
-        if let [cpu_time, cpu_burst_length, io_burst_length, weight] = task[..] {
-            tasks.push(TaskChar::new(
-                idx,
-                cpu_time.parse::<u64>().unwrap(),
-                cpu_burst_length.parse::<u64>().unwrap(),
-                io_burst_length.parse::<u64>().unwrap(),
-                weight.parse::<u32>().unwrap()
-            ));
-        }
-        idx += 1;
+fn arg(prefix: &str) -> Option<String> {
+    args(prefix).next()
+}
+
+// every value of a flag that may be given more than once
+fn args(prefix: &str) -> impl Iterator<Item = String> + '_ {
+    std::env::args().filter_map(move |arg| arg.strip_prefix(prefix).map(String::from))
+}
+
+// reported like a bad workload, with the flag in place of the path
+fn bad_flag(prefix: &str, value: &str, message: &str) -> ! {
+    eprintln!("{}{}: {}", prefix, value, message);
+    process::exit(1);
+}
+
+fn parse_flag<T: FromStr>(prefix: &str, value: &str) -> T where T::Err: fmt::Display {
+    parse_part(prefix, value, value)
+}
+
+// parses one piece of a flag's value, reporting the whole value if it is wrong
+fn parse_part<T: FromStr>(prefix: &str, value: &str, part: &str) -> T where T::Err: fmt::Display {
+    part.parse::<T>().unwrap_or_else(|err| bad_flag(prefix, value, &err.to_string()))
+}
+
+// as parquet if the path ends in .parquet, csv otherwise
+fn write_table(path: &str, table: &Table) -> Result<(), String> {
+    if !path.ends_with(".parquet") {
+        return fs::write(path, table.to_csv()).map_err(|err| err.to_string());
+    }
+    #[cfg(feature = "parquet")]
+    {
+        let file = fs::File::create(path).map_err(|err| err.to_string())?;
+        table.write_parquet(file).map_err(|err| err.to_string())
     }
+    #[cfg(not(feature = "parquet"))]
+    Err(String::from("built without the parquet feature"))
+}
+
+fn main() {
+    // tasks.txt in the legacy column format unless a workload file is given
+    let path = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .unwrap_or_else(|| String::from("tasks.txt"));
+
+    let workload = match workload::load(Path::new(&path)) {
+        Ok(workload) => workload,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }
+    };
 
     let mut scheduler = Scheduler::new();
+    scheduler.set_devices(workload.devices);
+    scheduler.set_sync_objects(workload.sync_objects);
+    if std::env::args().any(|arg| arg == "--dynamic-weight") {
+        scheduler.set_dynamic_weight(Some(DynamicWeight::new(1, 32, 100)));
+    }
+    if std::env::args().any(|arg| arg == "--autogroup") {
+        scheduler.set_autogroup(true);
+    }
+    if std::env::args().any(|arg| arg == "--child-runs-first") {
+        scheduler.set_child_runs_first(true);
+    }
+    if std::env::args().any(|arg| arg == "--no-inherit-vruntime") {
+        scheduler.set_inherit_vruntime(false);
+    }
+    if std::env::args().any(|arg| arg == "--priority-inheritance") {
+        scheduler.set_priority_inheritance(true);
+    }
+    if let Some(bound) = arg("--inversion-bound=") {
+        scheduler.set_inversion_bound(parse_flag("--inversion-bound=", &bound));
+    }
+    if let Some(window) = arg("--fairness-window=") {
+        let ticks = parse_flag("--fairness-window=", &window);
+        if ticks == 0 {
+            bad_flag("--fairness-window=", &window, "windows need to be at least one tick long");
+        }
+        scheduler.set_fairness_window(ticks);
+    }
+    // --signal=<time>:<task id>:<signal>, e.g. --signal=100:3:SIGSTOP
+    for signal in args("--signal=") {
+        let parts: Vec<&str> = signal.split(':').collect();
+        if parts.len() != 3 {
+            bad_flag("--signal=", &signal, "expected <time>:<task id>:<signal>");
+        }
+        scheduler.send_signal(
+            parse_part("--signal=", &signal, parts[0]),
+            parse_part("--signal=", &signal, parts[1]),
+            parse_part::<Signal>("--signal=", &signal, parts[2])
+        );
+    }
+    // --sched-debug=<time>, as often as wanted
+    for at in args("--sched-debug=") {
+        scheduler.show_sched_debug(parse_flag("--sched-debug=", &at));
+    }
+    if let Some(flags) = arg("--features=") {
+        scheduler.set_features(parse_flag::<SchedFeatures>("--features=", &flags));
+    }
+    if let Some(seed) = arg("--seed=") {
+        scheduler.set_seed(parse_flag("--seed=", &seed));
+    }
+
+    let chrome_trace = arg("--chrome-trace=");
+    let ftrace = arg("--ftrace=");
+    // --gantt or --gantt=cpus draws the schedule in the terminal
+    let gantt = std::env::args().find_map(|arg| match arg.as_str() {
+        "--gantt" | "--gantt=tasks" => Some(GanttRows::Tasks),
+        "--gantt=cpus" => Some(GanttRows::Cpus),
+        _ => None
+    });
+    let gantt_svg = arg("--gantt-svg=");
+    let gantt_zoom = arg("--gantt-zoom=").map(|zoom| {
+        let ticks = parse_flag("--gantt-zoom=", &zoom);
+        if ticks == 0 {
+            bad_flag("--gantt-zoom=", &zoom, "a column has to be at least a tick wide");
+        }
+        ticks
+    });
+    // --gantt-window=<from>:<to>, the end left out to draw up to the end
+    let gantt_window = arg("--gantt-window=").map(|window| {
+        let (from, to) = window.split_once(':').unwrap_or((&window, ""));
+        let from = parse_part("--gantt-window=", &window, from);
+        let to = if to.is_empty() { None } else { Some(parse_part("--gantt-window=", &window, to)) };
+        if to.is_some_and(|to| to <= from) {
+            bad_flag("--gantt-window=", &window, "the window has to end after it starts");
+        }
+        (from, to)
+    });
+    scheduler.set_tracing(chrome_trace.is_some() || ftrace.is_some() || gantt.is_some() || gantt_svg.is_some());
 
-    scheduler.run(tasks);
+    // --runqueue-series=rq.csv and --task-series=tasks.parquet, sampled every
+    // --sample-interval=N ticks, with only the --runqueue-columns=a,b,... and
+    // --task-columns=a,b,... given
+    let series = [
+        (arg("--runqueue-series="), arg("--runqueue-columns=")),
+        (arg("--task-series="), arg("--task-columns="))
+    ];
+    if series.iter().any(|(path, _)| path.is_some()) {
+        let interval = arg("--sample-interval=").unwrap_or_else(|| String::from("1"));
+        if let Err(err) = scheduler.set_sampling(Some(parse_flag("--sample-interval=", &interval))) {
+            bad_flag("--sample-interval=", &interval, &err);
+        }
+        // the columns of a series with no samples yet are those of the real one
+        let empty = TimeSeries::new(1).unwrap();
+        let checks = [("--runqueue-columns=", empty.runqueue_table()), ("--task-columns=", empty.task_table())];
+        for ((_, columns), (prefix, table)) in series.iter().zip(checks.iter()) {
+            if let Some(columns) = columns {
+                if let Err(err) = table.select(&columns.split(',').collect::<Vec<&str>>()) {
+                    bad_flag(prefix, columns, &err);
+                }
+            }
+        }
+    }
+
+    let report = scheduler.run(workload.tasks);
+    for (_, snapshot) in report.sched_debug.iter() {
+        print!("{}", snapshot);
+    }
+    print!("{}", report);
+    println!("Scheduler job complete");
+    // the latency histograms as json, which Latencies::from_json reads back to merge runs
+    if let Some(path) = arg("--histograms=") {
+        if let Err(err) = fs::write(&path, report.latencies.to_json()) {
+            eprintln!("Could not write {}: {}", path, err);
+            process::exit(1);
+        }
+    }
+    if let Some(sampled) = report.series.as_ref() {
+        let tables = [sampled.runqueue_table(), sampled.task_table()];
+        for ((path, columns), table) in series.iter().zip(tables.iter()) {
+            if let Some(path) = path {
+                let table = match columns {
+                    Some(columns) => table.select(&columns.split(',').collect::<Vec<&str>>()),
+                    None => Ok(table.clone())
+                };
+                if let Err(err) = table.and_then(|table| write_table(path, &table)) {
+                    eprintln!("Could not write {}: {}", path, err);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+    if let Some(trace) = report.trace.as_ref() {
+        let outputs = [(chrome_trace, chrome::to_json as fn(&_) -> String), (ftrace, ftrace::to_text)];
+        for (path, export) in outputs.iter() {
+            if let Some(path) = path {
+                if let Err(err) = fs::write(path, export(trace)) {
+                    eprintln!("Could not write {}: {}", path, err);
+                    process::exit(1);
+                }
+            }
+        }
+
+        let mut chart = Gantt::new(trace);
+        chart.set_rows(gantt.unwrap_or(GanttRows::Tasks));
+        if let Some(ticks) = gantt_zoom {
+            chart.set_zoom(ticks);
+        }
+        if let Some((from, to)) = gantt_window {
+            chart.set_window(from, to);
+        }
+        if gantt.is_some() {
+            print!("{}", chart.to_ascii());
+        }
+        if let Some(path) = gantt_svg {
+            if let Err(err) = fs::write(&path, chart.to_svg()) {
+                eprintln!("Could not write {}: {}", path, err);
+                process::exit(1);
+            }
+        }
+    }
+
+    // tasks left stopped or deadlocked mean the workload never finished
+    if !report.is_complete() {
+        process::exit(2);
+    }
 }
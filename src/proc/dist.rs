@@ -0,0 +1,157 @@
+// burst length distributions, sampled with a small seeded generator so
+// that the same workload and seed always produce the same bursts
+
+use std::fmt;
+
+// splitmix64, good enough for workloads and needs no dependencies
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in (0, 1]
+    pub fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    // uniform in [min, max]
+    pub fn range(&mut self, min: u64, max: u64) -> u64 {
+        let span = max - min;
+        if span == u64::MAX {
+            return self.next_u64();
+        }
+        min + self.next_u64() % (span + 1)
+    }
+
+    // standard normal, box-muller
+    pub fn normal(&mut self) -> f64 {
+        let u1 = self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Distribution {
+    Constant(u64),
+    Uniform { min: u64, max: u64 },
+    Exponential { mean: f64 },
+    LogNormal { mu: f64, sigma: f64 },
+    Pareto { scale: f64, shape: f64 },
+    // (burst length, relative frequency) pairs
+    Empirical(Vec<(u64, u64)>),
+}
+
+impl Distribution {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Distribution::Constant(_) => Ok(()),
+            Distribution::Uniform { min, max } => {
+                if min > max {
+                    return Err(format!("uniform min {} is larger than max {}", min, max));
+                }
+                Ok(())
+            },
+            Distribution::Exponential { mean } => {
+                if !(*mean > 0.0 && mean.is_finite()) {
+                    return Err(String::from("exponential mean must be positive"));
+                }
+                Ok(())
+            },
+            Distribution::LogNormal { mu, sigma } => {
+                if !(mu.is_finite() && *sigma >= 0.0 && sigma.is_finite()) {
+                    return Err(String::from("lognormal needs a finite mu and a non-negative sigma"));
+                }
+                Ok(())
+            },
+            Distribution::Pareto { scale, shape } => {
+                if !(*scale > 0.0 && scale.is_finite() && *shape > 0.0 && shape.is_finite()) {
+                    return Err(String::from("pareto scale and shape must be positive"));
+                }
+                Ok(())
+            },
+            Distribution::Empirical(bins) => {
+                if bins.iter().map(|(_, count)| *count).sum::<u64>() == 0 {
+                    return Err(String::from("empirical distribution needs at least one bin with a positive count"));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn sample(&self, rng: &mut Rng) -> u64 {
+        let value = match self {
+            Distribution::Constant(value) => return *value,
+            Distribution::Uniform { min, max } => return rng.range(*min, *max),
+            Distribution::Exponential { mean } => -mean * rng.next_f64().ln(),
+            Distribution::LogNormal { mu, sigma } => (mu + sigma * rng.normal()).exp(),
+            Distribution::Pareto { scale, shape } => scale / rng.next_f64().powf(1.0 / shape),
+            Distribution::Empirical(bins) => {
+                let total: u64 = bins.iter().map(|(_, count)| *count).sum();
+                let mut pick = rng.range(0, total - 1);
+                for (value, count) in bins.iter() {
+                    if pick < *count {
+                        return *value;
+                    }
+                    pick -= count;
+                }
+                unreachable!()
+            }
+        };
+
+        // saturating float to int conversion keeps heavy tails from wrapping
+        value.round() as u64
+    }
+
+    pub fn mean(&self) -> f64 {
+        match self {
+            Distribution::Constant(value) => *value as f64,
+            Distribution::Uniform { min, max } => (*min as f64 + *max as f64) / 2.0,
+            Distribution::Exponential { mean } => *mean,
+            Distribution::LogNormal { mu, sigma } => (mu + sigma * sigma / 2.0).exp(),
+            Distribution::Pareto { scale, shape } => {
+                if *shape <= 1.0 {
+                    f64::INFINITY
+                } else {
+                    shape * scale / (shape - 1.0)
+                }
+            },
+            Distribution::Empirical(bins) => {
+                let total: u64 = bins.iter().map(|(_, count)| *count).sum();
+                let sum: f64 = bins.iter().map(|(value, count)| *value as f64 * *count as f64).sum();
+                sum / total as f64
+            }
+        }
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Distribution::Constant(value) => write!(f, "{}", value),
+            Distribution::Uniform { min, max } => write!(f, "uniform({}, {})", min, max),
+            Distribution::Exponential { mean } => write!(f, "exponential({})", mean),
+            Distribution::LogNormal { mu, sigma } => write!(f, "lognormal({}, {})", mu, sigma),
+            Distribution::Pareto { scale, shape } => write!(f, "pareto({}, {})", scale, shape),
+            Distribution::Empirical(bins) => {
+                let bins: Vec<String> = bins.iter()
+                    .map(|(value, count)| format!("{}:{}", value, count))
+                    .collect();
+                write!(f, "empirical({})", bins.join(", "))
+            }
+        }
+    }
+}
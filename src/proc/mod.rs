@@ -1,2 +1,6 @@
 pub mod task;
 pub mod queue;
+pub mod dist;
+pub mod phase;
+pub mod periodic;
+pub mod signal;
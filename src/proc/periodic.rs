@@ -0,0 +1,59 @@
+// periodic tasks release a job every period, each job being one run of
+// the task's bursts or phases with at most wcet ticks of cpu time
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Periodic {
+    pub period: u64,
+    // delay of the first release after the task's arrival
+    pub offset: u64,
+    pub wcet: u64,
+    // every release is delayed by up to this many ticks
+    pub jitter: u64,
+    pub jobs: u64,
+}
+
+impl Periodic {
+    pub fn new(period: u64, wcet: u64, jobs: u64) -> Self {
+        Self { period, offset: 0, wcet, jitter: 0, jobs }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.period == 0 {
+            return Err(String::from("period must be positive"));
+        }
+        if self.wcet == 0 {
+            return Err(String::from("wcet must be positive"));
+        }
+        if self.jobs == 0 {
+            return Err(String::from("a periodic task needs at least one job"));
+        }
+        Ok(())
+    }
+
+    // when the given job is due, before jitter
+    pub fn nominal_release(&self, arrival: u128, job: u64) -> u128 {
+        arrival + self.offset as u128 + job as u128 * self.period as u128
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct JobRecord {
+    pub job: u64,
+    pub nominal: u128,
+    pub release: u128,
+    pub start: u128,
+    pub finish: u128,
+    // finished after the next job was due
+    pub overrun: bool,
+}
+
+impl JobRecord {
+    // from the time the job was due, so release jitter counts against it
+    pub fn response(&self) -> u128 {
+        self.finish - self.nominal
+    }
+
+    pub fn start_delay(&self) -> u128 {
+        self.start - self.nominal
+    }
+}
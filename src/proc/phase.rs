@@ -0,0 +1,301 @@
+// task behaviour as a list of phases, e.g. compute 500, then sleep 20 and
+// compute 30 ten times over, then drop to nice 10 and compute 5000
+
+use super::dist::{Distribution, Rng};
+use super::task::SchedPolicy;
+use crate::io::device::IoKind;
+use crate::sched::weight::{nice_to_weight, MAX_NICE, MIN_NICE};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Phase {
+    Compute(Distribution),
+    // waits on the io queue
+    Io(Distribution),
+    // blocks for a number of ticks without going through the io queue
+    Sleep(Distribution),
+    // blocks on a request to the named device
+    Request(String, IoKind),
+    // takes a mutex or a semaphore, blocking while it is unavailable
+    Lock(String),
+    Unlock(String),
+    // blocks on a condition variable, dropping the given mutex while
+    // asleep and taking it back before going on, or on a wait queue
+    WaitOn(String, Option<String>),
+    // wakes one or every task waiting on a condition variable or wait queue
+    Signal(String),
+    Broadcast(String),
+    SetNice(i32),
+    SetWeight(u32),
+    SetPolicy(SchedPolicy),
+    // runs the inner phases the given number of times, or forever with None
+    Repeat(Option<u64>, Vec<Phase>),
+    // starts a child that runs the given phases
+    Fork(Vec<Phase>),
+    // replaces the rest of the script, and the task's name
+    Exec(String, Vec<Phase>),
+    // blocks until every child has exited and reaps them
+    Wait,
+    Exit,
+}
+
+impl Phase {
+    pub fn validate(phases: &[Phase]) -> Result<(), String> {
+        for phase in phases {
+            match phase {
+                Phase::Compute(dist) | Phase::Io(dist) | Phase::Sleep(dist) => dist.validate()?,
+                Phase::SetNice(nice) => {
+                    if !(MIN_NICE..=MAX_NICE).contains(nice) {
+                        return Err(format!("nice must be within [{}, {}]", MIN_NICE, MAX_NICE));
+                    }
+                },
+                Phase::SetWeight(weight) => {
+                    if *weight == 0 {
+                        return Err(String::from("weight must be positive"));
+                    }
+                },
+                Phase::SetPolicy(_) | Phase::Wait | Phase::Exit => {},
+                Phase::Request(device, _) => {
+                    if device.is_empty() {
+                        return Err(String::from("a request needs a device name"));
+                    }
+                },
+                Phase::Lock(name) | Phase::Unlock(name) | Phase::Signal(name) | Phase::Broadcast(name) => {
+                    if name.is_empty() {
+                        return Err(String::from("a synchronisation phase needs the name of its object"));
+                    }
+                },
+                Phase::WaitOn(name, mutex) => {
+                    if name.is_empty() || mutex.as_ref().is_some_and(|mutex| mutex.is_empty()) {
+                        return Err(String::from("a synchronisation phase needs the name of its object"));
+                    }
+                },
+                Phase::Fork(body) => {
+                    // the child has no cpu time to run out of
+                    if Phase::is_endless(body) {
+                        return Err(String::from("a forked child can't repeat forever"));
+                    }
+                    Phase::validate(body)?;
+                },
+                Phase::Exec(_, body) => Phase::validate(body)?,
+                Phase::Repeat(times, body) => {
+                    if body.is_empty() {
+                        return Err(String::from("repeat needs at least one phase"));
+                    }
+                    // a loop that never blocks nor computes would never let the clock move
+                    if times.is_none() && !Phase::takes_time(body) {
+                        return Err(String::from("a repeat without a count needs a compute, io or sleep phase"));
+                    }
+                    Phase::validate(body)?;
+                }
+            };
+        }
+
+        Ok(())
+    }
+
+    fn takes_time(phases: &[Phase]) -> bool {
+        phases.iter().any(|phase| match phase {
+            Phase::Compute(_) | Phase::Io(_) | Phase::Sleep(_) | Phase::Request(..) => true,
+            Phase::Repeat(times, body) => *times != Some(0) && Phase::takes_time(body),
+            _ => false
+        })
+    }
+
+    // whether the phases loop forever
+    pub fn is_endless(phases: &[Phase]) -> bool {
+        phases.iter().any(|phase| match phase {
+            Phase::Repeat(None, _) => true,
+            Phase::Repeat(Some(0), _) => false,
+            Phase::Repeat(Some(_), body) | Phase::Exec(_, body) => Phase::is_endless(body),
+            _ => false
+        })
+    }
+}
+
+// a phase with its length drawn, as handed to the task
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Step {
+    Compute(u64),
+    Io(u64),
+    Sleep(u64),
+    // index of the device to hand to Script::name
+    Request(usize, IoKind),
+    // the operation, the object it is on and for condition variables the mutex
+    Sync(SyncOp, usize, Option<usize>),
+    SetWeight(u32),
+    SetPolicy(SchedPolicy),
+    // index of the script to hand to Script::child
+    Fork(usize),
+    Exec(usize),
+    Wait,
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Phase(Phase),
+    // jumps back to start until the loop has run the given number of times
+    EndRepeat(usize, Option<u64>),
+    Fork(usize),
+    Exec(usize),
+    Request(usize, IoKind),
+    Sync(SyncOp, usize, Option<usize>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SyncOp {
+    Lock,
+    Unlock,
+    Wait,
+    Signal,
+    Broadcast,
+}
+
+// phases flattened into a list of ops with backwards jumps for the loops
+#[derive(Clone, Debug)]
+pub struct Script {
+    ops: Vec<Op>,
+    pc: usize,
+    // iterations done so far by the loop ending at each op
+    loops: Vec<u64>,
+    // scripts of forked children and exec'd images, with the name to take on
+    children: Vec<(Option<String>, Script)>,
+    // devices and synchronisation objects the script refers to
+    names: Vec<String>,
+}
+
+impl Script {
+    pub fn new(phases: &[Phase]) -> Self {
+        if let Err(err) = Phase::validate(phases) {
+            panic!("Invalid phases: {}", err);
+        }
+
+        let mut script = Self {
+            ops: Vec::new(),
+            pc: 0,
+            loops: Vec::new(),
+            children: Vec::new(),
+            names: Vec::new()
+        };
+        script.compile(phases);
+        script.loops = vec![0; script.ops.len()];
+
+        script
+    }
+
+    // alternates between cpu and io bursts until the task's cpu time runs out
+    pub fn classic(cpu_burst: Distribution, io_burst: Distribution) -> Self {
+        Script::new(&[Phase::Repeat(None, vec![Phase::Compute(cpu_burst), Phase::Io(io_burst)])])
+    }
+
+    fn compile(&mut self, phases: &[Phase]) {
+        for phase in phases {
+            match phase {
+                Phase::Repeat(Some(0), _) => {},
+                Phase::Repeat(times, body) => {
+                    let start = self.ops.len();
+                    self.compile(body);
+                    self.ops.push(Op::EndRepeat(start, *times));
+                },
+                Phase::Fork(body) => {
+                    self.ops.push(Op::Fork(self.children.len()));
+                    self.children.push((None, Script::new(body)));
+                },
+                Phase::Exec(name, body) => {
+                    self.ops.push(Op::Exec(self.children.len()));
+                    self.children.push((Some(name.clone()), Script::new(body)));
+                },
+                Phase::Request(device, kind) => {
+                    let idx = self.intern(device);
+                    self.ops.push(Op::Request(idx, *kind));
+                },
+                Phase::Lock(name) => {
+                    let idx = self.intern(name);
+                    self.ops.push(Op::Sync(SyncOp::Lock, idx, None));
+                },
+                Phase::Unlock(name) => {
+                    let idx = self.intern(name);
+                    self.ops.push(Op::Sync(SyncOp::Unlock, idx, None));
+                },
+                // the mutex is dropped as a step of its own so that waiting is only ever blocking
+                Phase::WaitOn(name, mutex) => {
+                    let mutex = mutex.as_ref().map(|mutex| self.intern(mutex));
+                    if let Some(mutex) = mutex {
+                        self.ops.push(Op::Sync(SyncOp::Unlock, mutex, None));
+                    }
+                    let idx = self.intern(name);
+                    self.ops.push(Op::Sync(SyncOp::Wait, idx, mutex));
+                },
+                Phase::Signal(name) => {
+                    let idx = self.intern(name);
+                    self.ops.push(Op::Sync(SyncOp::Signal, idx, None));
+                },
+                Phase::Broadcast(name) => {
+                    let idx = self.intern(name);
+                    self.ops.push(Op::Sync(SyncOp::Broadcast, idx, None));
+                },
+                Phase::SetNice(nice) => self.ops.push(Op::Phase(Phase::SetWeight(nice_to_weight(*nice)))),
+                _ => self.ops.push(Op::Phase(phase.clone()))
+            };
+        }
+    }
+
+    fn intern(&mut self, name: &str) -> usize {
+        match self.names.iter().position(|other| other == name) {
+            Some(idx) => idx,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() - 1
+            }
+        }
+    }
+
+    pub fn name(&self, idx: usize) -> &str {
+        &self.names[idx]
+    }
+
+    // a fresh copy of a forked or exec'd script
+    pub fn child(&self, idx: usize) -> (Option<String>, Script) {
+        self.children[idx].clone()
+    }
+
+    pub fn reset(&mut self) {
+        self.pc = 0;
+        self.loops.iter_mut().for_each(|count| *count = 0);
+    }
+
+    // the next phase to carry out, None once the script is done
+    pub fn next(&mut self, rng: &mut Rng) -> Option<Step> {
+        while self.pc < self.ops.len() {
+            let pc = self.pc;
+            self.pc += 1;
+
+            match &self.ops[pc] {
+                Op::Phase(Phase::Compute(dist)) => return Some(Step::Compute(dist.sample(rng).max(1))),
+                Op::Phase(Phase::Io(dist)) => return Some(Step::Io(dist.sample(rng))),
+                Op::Phase(Phase::Sleep(dist)) => return Some(Step::Sleep(dist.sample(rng))),
+                Op::Phase(Phase::SetWeight(weight)) => return Some(Step::SetWeight(*weight)),
+                Op::Phase(Phase::SetPolicy(policy)) => return Some(Step::SetPolicy(*policy)),
+                Op::Phase(Phase::Wait) => return Some(Step::Wait),
+                Op::Phase(Phase::Exit) => {
+                    self.pc = self.ops.len();
+                    return None;
+                },
+                Op::Phase(_) => unreachable!(),
+                Op::Fork(idx) => return Some(Step::Fork(*idx)),
+                Op::Exec(idx) => return Some(Step::Exec(*idx)),
+                Op::Request(idx, kind) => return Some(Step::Request(*idx, *kind)),
+                Op::Sync(op, idx, mutex) => return Some(Step::Sync(*op, *idx, *mutex)),
+                Op::EndRepeat(start, times) => {
+                    self.loops[pc] += 1;
+                    if times.is_none_or(|times| self.loops[pc] < times) {
+                        self.pc = *start;
+                    } else {
+                        self.loops[pc] = 0;
+                    }
+                }
+            };
+        }
+
+        None
+    }
+}
@@ -8,12 +8,20 @@ pub struct TaskQueue {
 }
 
 impl TaskQueue {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Self { tasks: HashMap::new() }
     }
 
+    #[allow(clippy::map_entry)]
     pub fn add(&mut self, task: Task) {
-        if task.get_status() == TaskStatus::Idle || task.get_status() == TaskStatus::Terminated {
+        // a task whose script starts out blocked is idle before it ever ran
+        let born = match task.get_status() {
+            TaskStatus::Idle => task.get_runtime() == 0,
+            TaskStatus::Terminated | TaskStatus::Zombie | TaskStatus::Dead | TaskStatus::Stopped => false,
+            _ => true
+        };
+        if !born {
             return;
         }
         let start_time = task.get_start_time();
@@ -27,22 +35,32 @@ impl TaskQueue {
 
     pub fn append(&mut self, tasks: &[Task]) {
         for task in tasks {
-            self.add(*task);
+            self.add(task.clone());
         }
     }
 
     pub fn pop(&mut self) -> Vec<Task> {
-        if self.is_empty() {
-            return Vec::new();
+        match self.next_time() {
+            Some(time) => self.tasks.remove(&time).unwrap(),
+            None => Vec::new()
         }
-        let mut smallest_key = 0;
-        for key in self.tasks.keys() {
-            if smallest_key > *key || smallest_key == 0 {
-                smallest_key = *key;
+    }
+
+    // all tasks born at or before the given time, in order of birth
+    pub fn release(&mut self, now: u128) -> Vec<Task> {
+        let mut released = Vec::new();
+        while let Some(time) = self.next_time() {
+            if time > now {
+                break;
             }
+            released.append(&mut self.tasks.remove(&time).unwrap());
         }
-        
-        self.tasks.remove(&smallest_key).unwrap()
+
+        released
+    }
+
+    pub fn next_time(&self) -> Option<u128> {
+        self.tasks.keys().min().copied()
     }
 
     pub fn remove(&mut self, time: u128) -> Vec<Task> {
@@ -0,0 +1,35 @@
+// signals the scheduler delivers to tasks at a given system time
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Signal {
+    // keeps the task off the cpu until it is continued
+    Stop,
+    Cont,
+    Kill,
+}
+
+impl FromStr for Signal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().trim_start_matches("SIG") {
+            "STOP" => Ok(Signal::Stop),
+            "CONT" => Ok(Signal::Cont),
+            "KILL" => Ok(Signal::Kill),
+            _ => Err(format!("Unknown signal {:?}", s))
+        }
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Signal::Stop => write!(f, "SIGSTOP"),
+            Signal::Cont => write!(f, "SIGCONT"),
+            Signal::Kill => write!(f, "SIGKILL")
+        }
+    }
+}
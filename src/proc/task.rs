@@ -5,15 +5,99 @@ pub enum TaskStatus {
     Terminated,
     Waiting,
     New,
+    // exited, but not yet reaped by its parent
+    Zombie,
+    Dead,
+    // off the cpu until it gets a SIGCONT
+    Stopped,
 }
 
-#[derive(Clone, Copy)]
+use super::dist::{Distribution, Rng};
+use super::periodic::{JobRecord, Periodic};
+use super::phase::{Phase, Script, Step, SyncOp};
+use crate::io::device::IoKind;
+use crate::sched::weight::WEIGHT_IDLEPRIO;
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SchedPolicy {
+    #[default]
+    Normal,
+    Batch,
+    Idle,
+}
+
+impl SchedPolicy {
+    pub const ALL: [SchedPolicy; 3] = [SchedPolicy::Normal, SchedPolicy::Batch, SchedPolicy::Idle];
+}
+
+impl FromStr for SchedPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "normal" | "other" | "sched_normal" | "sched_other" => Ok(SchedPolicy::Normal),
+            "batch" | "sched_batch" => Ok(SchedPolicy::Batch),
+            "idle" | "sched_idle" => Ok(SchedPolicy::Idle),
+            _ => Err(format!("Unknown scheduling policy {:?}", s))
+        }
+    }
+}
+
+impl fmt::Display for SchedPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchedPolicy::Normal => write!(f, "SCHED_NORMAL"),
+            SchedPolicy::Batch => write!(f, "SCHED_BATCH"),
+            SchedPolicy::Idle => write!(f, "SCHED_IDLE")
+        }
+    }
+}
+
+// voluntary ways for a task to give up the cpu, fired once the
+// task has consumed a given amount of cpu time
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    Yield,
+    CondResched,
+    Sleep(u64),
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TaskStats {
+    pub nr_yields: u64,
+    pub nr_voluntary_switches: u64,
+    pub nr_involuntary_switches: u64,
+    pub nr_stops: u64,
+    pub killed: bool,
+    // the first tick the task ever ran, unlike first_run which starts over every job
+    pub first_run: Option<u128>,
+    pub completion: Option<u128>,
+    // ticks spent runnable in the tree
+    pub wait_time: u128,
+    // ticks spent on io bursts and device requests, queueing included
+    pub io_time: u128,
+    pub cpu_time: u64,
+}
+
+#[derive(Clone)]
 pub struct TaskChar {
     id: u16,
     cpu_time: u64,
-    cpu_burst_length: u64,
-    io_burst_length: u64,
+    cpu_burst: Distribution,
+    io_burst: Distribution,
     weight: u32,
+    session: u32,
+    policy: SchedPolicy,
+    actions: Vec<(u64, Action)>,
+    arrival: u128,
+    name: String,
+    affinity: u64,
+    phases: Option<Vec<Phase>>,
+    periodic: Option<Periodic>,
+    io_device: Option<String>,
 }
 
 impl TaskChar {
@@ -28,11 +112,88 @@ impl TaskChar {
         Self {
             id,
             cpu_time,
-            cpu_burst_length,
-            io_burst_length,
-            weight
+            cpu_burst: Distribution::Constant(cpu_burst_length),
+            io_burst: Distribution::Constant(io_burst_length),
+            weight,
+            session: 0,
+            policy: SchedPolicy::Normal,
+            actions: Vec::new(),
+            arrival: 0,
+            name: format!("task-{}", id),
+            affinity: u64::MAX,
+            phases: None,
+            periodic: None,
+            io_device: None
+        }
+
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    // draws every cpu burst from the given distribution instead of using a fixed length
+    pub fn set_cpu_burst(&mut self, dist: Distribution) {
+        if let Err(err) = dist.validate() {
+            panic!("Invalid cpu burst distribution for task {:?}: {}", self.id, err);
         }
+        self.cpu_burst = dist;
+    }
+
+    pub fn set_io_burst(&mut self, dist: Distribution) {
+        if let Err(err) = dist.validate() {
+            panic!("Invalid io burst distribution for task {:?}: {}", self.id, err);
+        }
+        self.io_burst = dist;
+    }
+
+    // replaces the cpu and io bursts with a script of phases
+    pub fn set_phases(&mut self, phases: Vec<Phase>) {
+        if let Err(err) = Phase::validate(&phases) {
+            panic!("Invalid phases for task {:?}: {}", self.id, err);
+        }
+        self.phases = Some(phases);
+    }
+
+    // releases a job every period instead of running once, each job gets wcet
+    // ticks of cpu time in place of the task's cpu time
+    pub fn set_periodic(&mut self, periodic: Periodic) {
+        if let Err(err) = periodic.validate() {
+            panic!("Invalid period for task {:?}: {}", self.id, err);
+        }
+        self.periodic = Some(periodic);
+    }
 
+    // sends the io bursts to a shared device instead of counting them down,
+    // the device's service time takes the place of the io burst length
+    pub fn set_io_device(&mut self, device: &str) {
+        self.io_device = Some(device.to_string());
+    }
+
+    // bitmask of the cpus the task may run on
+    pub fn set_affinity(&mut self, affinity: u64) {
+        self.affinity = affinity;
+    }
+
+    pub fn set_weight(&mut self, weight: u32) {
+        self.weight = weight;
+    }
+
+    // the system time at which the task is born
+    pub fn set_arrival(&mut self, arrival: u128) {
+        self.arrival = arrival;
+    }
+
+    pub fn add_action(&mut self, offset: u64, action: Action) {
+        self.actions.push((offset, action));
+    }
+
+    pub fn set_session(&mut self, session: u32) {
+        self.session = session;
+    }
+
+    pub fn set_policy(&mut self, policy: SchedPolicy) {
+        self.policy = policy;
     }
 
     #[inline]
@@ -41,28 +202,116 @@ impl TaskChar {
     #[inline]
     pub fn get_cpu_time(&self) -> u64 { self.cpu_time }
 
+    // the mean burst lengths, exact for constant bursts
+    #[inline]
+    pub fn get_cpu_burst_length(&self) -> u64 { self.cpu_burst.mean().round() as u64 }
+
+    #[inline]
+    pub fn get_io_burst_length(&self) -> u64 { self.io_burst.mean().round() as u64 }
+
     #[inline]
-    pub fn get_cpu_burst_length(&self) -> u64 { self.cpu_burst_length }
+    pub fn get_cpu_burst(&self) -> &Distribution { &self.cpu_burst }
 
     #[inline]
-    pub fn get_io_burst_length(&self) -> u64 { self.io_burst_length }
+    pub fn get_io_burst(&self) -> &Distribution { &self.io_burst }
 
     #[inline]
     pub fn get_weight(&self) -> u32 { self.weight }
+
+    #[inline]
+    pub fn get_session(&self) -> u32 { self.session }
+
+    #[inline]
+    pub fn get_policy(&self) -> SchedPolicy { self.policy }
+
+    #[inline]
+    pub fn get_actions(&self) -> &[(u64, Action)] { &self.actions }
+
+    #[inline]
+    pub fn get_arrival(&self) -> u128 { self.arrival }
+
+    #[inline]
+    pub fn get_name(&self) -> &str { &self.name }
+
+    #[inline]
+    pub fn get_affinity(&self) -> u64 { self.affinity }
+
+    #[inline]
+    pub fn get_phases(&self) -> Option<&[Phase]> { self.phases.as_deref() }
+
+    #[inline]
+    pub fn get_periodic(&self) -> Option<&Periodic> { self.periodic.as_ref() }
+
+    #[inline]
+    pub fn get_io_device(&self) -> Option<&str> { self.io_device.as_deref() }
+
+    pub fn spawn(&self) -> Task {
+        let mut task = Task::new(
+            self.id,
+            self.cpu_time,
+            1,
+            0,
+            self.arrival,
+            self.weight
+        );
+        task.set_script(match (&self.phases, &self.io_device) {
+            (Some(phases), _) => Script::new(phases),
+            (None, Some(device)) => Script::new(&[Phase::Repeat(None, vec![
+                Phase::Compute(self.cpu_burst.clone()),
+                Phase::Request(device.clone(), IoKind::Read)
+            ])]),
+            (None, None) => Script::classic(self.cpu_burst.clone(), self.io_burst.clone())
+        });
+        task.set_name(&self.name);
+        task.set_session(self.session);
+        task.set_policy(self.policy);
+        for (offset, action) in self.actions.iter() {
+            task.add_action(*offset, *action);
+        }
+        if let Some(periodic) = &self.periodic {
+            task.set_periodic(periodic.clone());
+        }
+
+        task
+    }
 }
 
 #[derive(Debug)]
 pub struct Task {
     id: u16,
     cpu_time: u64,
-    cpu_burst_length: u64,
+    script: Script,
+    // the phase the task is in, a compute phase counts down the ticks left
+    step: Step,
     io_burst_length: u64,
+    rng: Rng,
     state: TaskStatus,
     runtime: u64,
     vruntime: u64,
     idle_time: u64,
     start_time: u128,
     weight: u32,
+    // inherited from the waiters on a lock the task holds
+    boost: Option<u32>,
+    session: u32,
+    policy: SchedPolicy,
+    recent_runtime: u64,
+    recent_idle_time: u64,
+    window_start: u128,
+    actions: Vec<(u64, Action)>,
+    next_action: usize,
+    pending: Option<Action>,
+    stats: TaskStats,
+    wake_time: Option<u128>,
+    name: String,
+    // scripts of children forked since the run queue last looked
+    forks: Vec<Script>,
+    parent: Option<u16>,
+    first_run: Option<u128>,
+    periodic: Option<Periodic>,
+    arrival: u128,
+    job: u64,
+    jobs: Vec<JobRecord>,
 }
 
 impl Task {
@@ -75,18 +324,44 @@ impl Task {
         weight: u32
     ) -> Self {
 
-        Self {
+        let mut task = Self {
             id, cpu_time,
-            cpu_burst_length,
+            script: Script::classic(
+                Distribution::Constant(cpu_burst_length),
+                Distribution::Constant(io_burst_length)
+            ),
+            step: Step::Compute(0),
             io_burst_length,
+            rng: Rng::new(id as u64),
             state: TaskStatus::New,
             runtime: 0,
             vruntime: 0,
             idle_time: 0,
             start_time,
-            weight
-        }
-
+            weight,
+            boost: None,
+            session: 0,
+            policy: SchedPolicy::Normal,
+            recent_runtime: 0,
+            recent_idle_time: 0,
+            window_start: start_time,
+            actions: Vec::new(),
+            next_action: 0,
+            pending: None,
+            stats: TaskStats::default(),
+            wake_time: None,
+            name: format!("task-{}", id),
+            forks: Vec::new(),
+            parent: None,
+            first_run: None,
+            periodic: None,
+            arrival: start_time,
+            job: 0,
+            jobs: Vec::new()
+        };
+        task.advance();
+
+        task
     }
 
     pub fn get_id(&self) -> u16 {
@@ -97,6 +372,207 @@ impl Task {
         self.cpu_time
     }
 
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    // starts the task over on the given script
+    pub fn set_script(&mut self, script: Script) {
+        self.script = script;
+        self.script.reset();
+        self.advance();
+    }
+
+    // reseeds the burst generator and starts the script over, tasks get
+    // their own stream so that adding a task leaves the others alone
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed.wrapping_add((self.id as u64) << 32));
+        self.script.reset();
+        self.advance();
+        if self.job == 0 {
+            self.start_time = self.next_release(0);
+        }
+    }
+
+    pub fn set_periodic(&mut self, periodic: Periodic) {
+        self.cpu_time = periodic.wcet;
+        self.periodic = Some(periodic);
+        self.job = 0;
+        self.start_time = self.next_release(0);
+        self.window_start = self.start_time;
+    }
+
+    pub fn get_periodic(&self) -> Option<&Periodic> {
+        self.periodic.as_ref()
+    }
+
+    // jobs finished so far
+    pub fn get_jobs(&self) -> &[JobRecord] {
+        &self.jobs
+    }
+
+    // the time the task first got the cpu since it was born or released
+    pub fn get_first_run(&self) -> Option<u128> {
+        self.first_run
+    }
+
+    pub fn set_first_run(&mut self, time: u128) {
+        if self.first_run.is_none() {
+            self.first_run = Some(time);
+        }
+        if self.stats.first_run.is_none() {
+            self.stats.first_run = Some(time);
+        }
+    }
+
+    pub fn get_arrival(&self) -> u128 {
+        self.arrival
+    }
+
+    pub fn set_completion(&mut self, now: u128) {
+        self.stats.completion = Some(now);
+    }
+
+    fn next_release(&mut self, job: u64) -> u128 {
+        match &self.periodic {
+            Some(periodic) => {
+                let jitter = periodic.jitter;
+                periodic.nominal_release(self.arrival, job) + self.rng.range(0, jitter) as u128
+            },
+            None => self.start_time
+        }
+    }
+
+    // records the job that just finished and returns when the next one is
+    // released. a job that overran its period is followed right away
+    pub fn finish_job(&mut self, now: u128) -> Option<u128> {
+        let periodic = self.periodic.clone()?;
+        let nominal = periodic.nominal_release(self.arrival, self.job);
+        self.jobs.push(JobRecord {
+            job: self.job,
+            nominal,
+            release: self.start_time,
+            start: self.first_run.unwrap_or(now),
+            finish: now,
+            overrun: now > nominal + periodic.period as u128
+        });
+
+        self.job += 1;
+        if self.job >= periodic.jobs {
+            return None;
+        }
+        Some(self.next_release(self.job).max(now))
+    }
+
+    pub fn get_step(&self) -> Step {
+        self.step
+    }
+
+    // the synchronisation step the task is at, with the object and the mutex of a condition wait
+    pub fn get_sync(&self) -> Option<(SyncOp, &str, Option<&str>)> {
+        match self.step {
+            Step::Sync(op, idx, mutex) if self.state != TaskStatus::Terminated => Some((op, self.script.name(idx), mutex.map(|mutex| self.script.name(mutex)))),
+            _ => None
+        }
+    }
+
+    // the device the task is blocked on, if any
+    pub fn get_request(&self) -> Option<(&str, IoKind)> {
+        match self.step {
+            Step::Request(idx, kind) if self.state == TaskStatus::Idle => Some((self.script.name(idx), kind)),
+            _ => None
+        }
+    }
+
+    // moves on to the next phase, applying weight and policy changes on the way
+    fn advance(&mut self) {
+        loop {
+            match self.script.next(&mut self.rng) {
+                None => {
+                    self.terminate();
+                    return;
+                },
+                Some(Step::SetWeight(weight)) => self.weight = weight,
+                Some(Step::SetPolicy(policy)) => self.policy = policy,
+                Some(Step::Fork(idx)) => {
+                    let (_, script) = self.script.child(idx);
+                    self.forks.push(script);
+                },
+                Some(Step::Exec(idx)) => {
+                    let (name, script) = self.script.child(idx);
+                    if let Some(name) = name {
+                        self.name = name;
+                    }
+                    self.script = script;
+                },
+                Some(step) => {
+                    self.step = step;
+                    break;
+                }
+            };
+        }
+
+        match self.step {
+            Step::Compute(_) => {
+                if self.state == TaskStatus::Idle {
+                    self.schedule();
+                }
+            },
+            Step::Io(ticks) => {
+                self.idle_time = 0;
+                self.io_burst_length = ticks;
+                self.to_idle();
+            },
+            Step::Sleep(ticks) => {
+                self.pending = Some(Action::Sleep(ticks));
+                self.to_idle();
+            },
+            // the run queue decides whether there are children to wait for
+            // and hands requests to their device
+            Step::Wait | Step::Request(..) => self.to_idle(),
+            // left to the run queue, which knows whether the object is available
+            Step::Sync(..) => {},
+            _ => unreachable!()
+        };
+    }
+
+    // called once a sleep or a wait is over, or a synchronisation step is done
+    pub fn wake(&mut self) {
+        match self.step {
+            Step::Sleep(_) | Step::Wait | Step::Request(..) | Step::Sync(..) => self.advance(),
+            // slept on an action right as its compute phase ended
+            Step::Io(_) => {},
+            _ => self.schedule()
+        };
+    }
+
+    // the task that forked this one, if any
+    pub fn get_parent(&self) -> Option<u16> {
+        self.parent
+    }
+
+    pub fn take_forks(&mut self) -> Vec<Script> {
+        std::mem::take(&mut self.forks)
+    }
+
+    // a child running the given script, with the parent's weight, policy and session
+    pub fn fork(&mut self, script: Script, pid: u16, now: u128, vruntime: u64) -> Task {
+        let mut child = Task::new(pid, u64::MAX, 1, 0, now, self.weight);
+        child.vruntime = vruntime;
+        child.name = self.name.clone();
+        child.session = self.session;
+        child.policy = self.policy;
+        child.parent = Some(self.id);
+        child.rng = Rng::new(self.rng.next_u64());
+        child.set_script(script);
+
+        child
+    }
+
     pub fn get_start_time(&self) -> u128 {
         self.start_time
     }
@@ -113,18 +589,148 @@ impl Task {
         self.state = TaskStatus::Terminated
     }
 
+    // the weight the task runs at, raised to that of its heaviest waiter
+    // while priority inheritance boosts it
     pub fn weight(&self) -> u32 {
-        self.weight
+        let base = self.base_weight();
+        match self.boost {
+            Some(boost) => boost.max(base),
+            None => base
+        }
+    }
+
+    // sched_idle tasks always run at the minimal weight
+    pub fn base_weight(&self) -> u32 {
+        match self.policy {
+            SchedPolicy::Idle => WEIGHT_IDLEPRIO,
+            _ => self.weight
+        }
+    }
+
+    pub fn set_boost(&mut self, boost: Option<u32>) {
+        self.boost = boost;
+    }
+
+    pub fn get_boost(&self) -> Option<u32> {
+        self.boost
+    }
+
+    pub fn set_weight(&mut self, weight: u32) {
+        self.weight = weight;
+    }
+
+    pub fn get_session(&self) -> u32 {
+        self.session
+    }
+
+    pub fn set_session(&mut self, session: u32) {
+        self.session = session;
+    }
+
+    pub fn get_policy(&self) -> SchedPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: SchedPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn get_vruntime(&self) -> u64 {
+        self.vruntime
+    }
+
+    pub fn add_action(&mut self, offset: u64, action: Action) {
+        let idx = self.actions
+            .iter()
+            .position(|(at, _)| *at > offset)
+            .unwrap_or(self.actions.len());
+        self.actions.insert(idx, (offset, action));
+    }
+
+    // the action fired by the last cpu cycle, if any
+    pub fn take_action(&mut self) -> Option<Action> {
+        self.pending.take()
+    }
+
+    pub fn get_stats(&self) -> TaskStats {
+        self.stats
+    }
+
+    // time at which the task last became runnable, cleared once it gets the cpu
+    pub fn set_wake_time(&mut self, time: u128) {
+        self.wake_time = Some(time);
+    }
+
+    pub fn take_wake_time(&mut self) -> Option<u128> {
+        self.wake_time.take()
+    }
+
+    pub fn count_yield(&mut self) {
+        self.stats.nr_yields += 1;
+    }
+
+    pub fn count_switch(&mut self, voluntary: bool) {
+        if voluntary {
+            self.stats.nr_voluntary_switches += 1;
+        } else {
+            self.stats.nr_involuntary_switches += 1;
+        }
+    }
+
+    pub fn get_recent_runtime(&self) -> u64 {
+        self.recent_runtime
+    }
+
+    pub fn get_recent_idle_time(&self) -> u64 {
+        self.recent_idle_time
+    }
+
+    pub fn get_window_start(&self) -> u128 {
+        self.window_start
+    }
+
+    pub fn reset_window(&mut self, now: u128) {
+        self.recent_runtime = 0;
+        self.recent_idle_time = 0;
+        self.window_start = now;
     }
 
     pub fn vruntime(&mut self, now: u128) -> u64 {
-        let dt: u64 = now.overflowing_sub(self.start_time).0 as u64;
-        let delta_exec_weighted: u64 = dt / (self.weight as u64);
-        self.vruntime += delta_exec_weighted;
+        self.vruntime = self.peek_vruntime(now);
 
         self.vruntime
     }
 
+    // the vruntime a call to vruntime(now) would produce, without updating it
+    pub fn peek_vruntime(&self, now: u128) -> u64 {
+        let dt: u64 = now.overflowing_sub(self.start_time).0 as u64;
+        let delta_exec_weighted: u64 = dt / (self.weight() as u64);
+
+        self.vruntime + delta_exec_weighted
+    }
+
+    pub fn to_zombie(&mut self) {
+        self.state = TaskStatus::Zombie
+    }
+
+    pub fn to_dead(&mut self) {
+        self.state = TaskStatus::Dead
+    }
+
+    pub fn to_stopped(&mut self) {
+        self.state = TaskStatus::Stopped
+    }
+
+    pub fn count_stop(&mut self) {
+        self.stats.nr_stops += 1;
+    }
+
+    // terminates the task wherever it is in its script
+    pub fn kill(&mut self) {
+        self.stats.killed = true;
+        self.state = TaskStatus::Terminated;
+    }
+
     pub fn to_idle(&mut self) {
         match self.state {
             TaskStatus::Terminated => panic!("Cannot yield a terminated task ({:?})!", self.id),
@@ -148,32 +754,70 @@ impl Task {
         self.idle_time = 0;
         self.state = TaskStatus::New;
         self.start_time = time;
+        self.next_action = 0;
+        self.pending = None;
+        self.first_run = None;
+        self.reset_window(time);
+        self.script.reset();
+        self.advance();
+    }
+
+    // a tick spent runnable in the tree while another task ran
+    pub fn wait_cycle(&mut self) {
+        self.stats.wait_time += 1;
+    }
+
+    // a tick spent blocked on io, queueing for the device included
+    pub fn blocked_cycle(&mut self) {
+        self.stats.io_time += 1;
     }
 
-    pub fn cpu_cycle(&mut self) {
+    // false if the task was not running, which leaves it as it was
+    pub fn cpu_cycle(&mut self) -> bool {
         match self.state {
             TaskStatus::Running => {
                 self.runtime += 1;
+                self.stats.cpu_time += 1;
+                self.recent_runtime += 1;
                 if self.runtime >= self.cpu_time {
                     self.terminate();
-                } else if self.runtime % self.cpu_burst_length == 0 {
+                    return true;
+                }
+
+                while self.next_action < self.actions.len() && self.actions[self.next_action].0 <= self.runtime {
+                    if self.pending.is_none() {
+                        self.pending = Some(self.actions[self.next_action].1);
+                    }
+                    self.next_action += 1;
+                }
+                if let Step::Compute(left) = &mut self.step {
+                    *left = left.saturating_sub(1);
+                    if *left == 0 {
+                        self.advance();
+                    }
+                }
+                if self.state == TaskStatus::Running && matches!(self.pending, Some(Action::Sleep(_))) {
                     self.to_idle();
                 }
+                true
             },
-            _ => println!("Task {:?} is not running", self.id)
+            _ => false
         }
     }
 
-    pub fn io_cycle(&mut self) {
+    // false if the task was not idle, which leaves it as it was
+    pub fn io_cycle(&mut self) -> bool {
         match self.state {
             TaskStatus::Idle => {
                 self.idle_time += 1;
+                self.recent_idle_time += 1;
                 if self.idle_time >= self.io_burst_length {
                     self.idle_time = 0;
-                    self.schedule();
+                    self.advance();
                 }
+                true
             },
-            _ => println!("Task {:?} is currently not idle", self.id)
+            _ => false
         }
     }
 }
@@ -189,19 +833,38 @@ impl Clone for Task {
         Self {
             id:                 self.id,
             cpu_time:           self.cpu_time,
-            cpu_burst_length:   self.cpu_burst_length,
+            script:             self.script.clone(),
+            step:               self.step,
             io_burst_length:    self.io_burst_length,
+            rng:                self.rng.clone(),
             state:              self.state,
             runtime:            self.runtime,
             vruntime:           self.vruntime,
             idle_time:          self.idle_time,
             start_time:         self.start_time,
-            weight:             self.weight
+            weight:             self.weight,
+            boost:              self.boost,
+            session:            self.session,
+            policy:             self.policy,
+            recent_runtime:     self.recent_runtime,
+            recent_idle_time:   self.recent_idle_time,
+            window_start:       self.window_start,
+            actions:            self.actions.clone(),
+            next_action:        self.next_action,
+            pending:            self.pending,
+            stats:              self.stats,
+            wake_time:          self.wake_time,
+            name:               self.name.clone(),
+            forks:              self.forks.clone(),
+            parent:             self.parent,
+            first_run:          self.first_run,
+            periodic:           self.periodic.clone(),
+            arrival:            self.arrival,
+            job:                self.job,
+            jobs:               self.jobs.clone()
         }
     }
 }
 
-impl Copy for Task {}
-
 unsafe impl Send for Task {}
 unsafe impl Sync for Task {}
@@ -0,0 +1,68 @@
+// per-session task group, cpu time is divided fairly between
+// groups before it is divided between the tasks of a group
+
+use super::weight::{nice_to_weight, tick_delta};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Autogroup {
+    session: u32,
+    nice: i32,
+    vruntime: u64,
+    nr_running: usize,
+}
+
+impl Autogroup {
+    pub fn new(session: u32, nice: i32) -> Self {
+        nice_to_weight(nice);  // validates the nice value
+
+        Self {
+            session,
+            nice,
+            vruntime: 0,
+            nr_running: 0
+        }
+    }
+
+    #[inline]
+    pub fn get_session(&self) -> u32 { self.session }
+
+    #[inline]
+    pub fn get_nice(&self) -> i32 { self.nice }
+
+    #[inline]
+    pub fn get_weight(&self) -> u32 { nice_to_weight(self.nice) }
+
+    #[inline]
+    pub fn get_vruntime(&self) -> u64 { self.vruntime }
+
+    #[inline]
+    pub fn get_nr_running(&self) -> usize { self.nr_running }
+
+    pub fn set_nice(&mut self, nice: i32) {
+        nice_to_weight(nice);
+        self.nice = nice;
+    }
+
+    // a group that becomes runnable again must not be able to
+    // make up for the time it spent with nothing to run
+    pub fn enqueue(&mut self, min_vruntime: Option<u64>) {
+        if self.nr_running == 0 {
+            if let Some(min) = min_vruntime {
+                self.vruntime = self.vruntime.max(min);
+            }
+        }
+        self.nr_running += 1;
+    }
+
+    pub fn dequeue(&mut self) {
+        self.nr_running = self.nr_running.saturating_sub(1);
+    }
+
+    pub fn charge(&mut self, ticks: u64) {
+        self.vruntime += ticks * tick_delta(self.get_weight());
+    }
+
+    pub fn reset_nr_running(&mut self) {
+        self.nr_running = 0;
+    }
+}
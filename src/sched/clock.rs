@@ -22,3 +22,9 @@ impl Clone for Clock {
         Self(self.0.clone())
     }
 }
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
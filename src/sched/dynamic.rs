@@ -0,0 +1,123 @@
+// dynamic weight policy that punishes tasks hogging the cpu and
+// rewards tasks that spend most of their time waiting on i/o
+
+use crate::proc::task::{SchedPolicy, Task};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeightAdjustment {
+    pub id: u16,
+    pub time: u128,
+    pub old_weight: u32,
+    pub new_weight: u32,
+    pub cpu_share: f64,
+    pub io_ratio: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct DynamicWeight {
+    min_weight: u32,
+    max_weight: u32,
+    window: u128,
+    hog_share: f64,
+    io_ratio: f64,
+    step: u32,
+    log: Vec<WeightAdjustment>,
+}
+
+impl DynamicWeight {
+    pub fn new(min_weight: u32, max_weight: u32, window: u128) -> Self {
+        if min_weight == 0 || min_weight > max_weight {
+            panic!("Invalid weight bounds [{:?}, {:?}]", min_weight, max_weight);
+        }
+        if window == 0 {
+            panic!("Dynamic weight window must be at least one tick");
+        }
+
+        Self {
+            min_weight,
+            max_weight,
+            window,
+            hog_share: 0.5,
+            io_ratio: 0.5,
+            step: 1,
+            log: Vec::new()
+        }
+    }
+
+    // share of the window's ticks spent on the cpu at or above which a task is punished
+    pub fn set_hog_share(&mut self, share: f64) {
+        self.hog_share = share;
+    }
+
+    // share of the task's active ticks spent idle at or above which a task is rewarded
+    pub fn set_io_ratio(&mut self, ratio: f64) {
+        self.io_ratio = ratio;
+    }
+
+    pub fn set_step(&mut self, step: u32) {
+        self.step = step;
+    }
+
+    #[inline]
+    pub fn get_min_weight(&self) -> u32 { self.min_weight }
+
+    #[inline]
+    pub fn get_max_weight(&self) -> u32 { self.max_weight }
+
+    #[inline]
+    pub fn get_window(&self) -> u128 { self.window }
+
+    pub fn get_log(&self) -> &[WeightAdjustment] {
+        &self.log
+    }
+
+    pub fn adjust(&mut self, task: &mut Task, now: u128) -> Option<WeightAdjustment> {
+        let elapsed = now.saturating_sub(task.get_window_start());
+        if elapsed < self.window {
+            return None;
+        }
+
+        let ran = task.get_recent_runtime();
+        let idle = task.get_recent_idle_time();
+        task.reset_window(now);
+        // sched_idle tasks run at a fixed weight, which is not their own
+        if task.get_policy() == SchedPolicy::Idle {
+            return None;
+        }
+
+        let cpu_share = ran as f64 / elapsed as f64;
+        let io_ratio = if ran + idle == 0 {
+            0.0
+        } else {
+            idle as f64 / (ran + idle) as f64
+        };
+
+        // an inherited boost is not the task's own weight to adjust; a weight
+        // already outside the bounds is only ever moved towards them
+        let old_weight = task.base_weight();
+        let new_weight = if cpu_share >= self.hog_share && io_ratio < self.io_ratio {
+            old_weight.min(old_weight.saturating_sub(self.step).max(self.min_weight))
+        } else if io_ratio >= self.io_ratio {
+            old_weight.max(old_weight.saturating_add(self.step).min(self.max_weight))
+        } else {
+            old_weight
+        };
+
+        if new_weight == old_weight {
+            return None;
+        }
+        task.set_weight(new_weight);
+
+        let adjustment = WeightAdjustment {
+            id: task.get_id(),
+            time: now,
+            old_weight,
+            new_weight,
+            cpu_share,
+            io_ratio
+        };
+        self.log.push(adjustment);
+
+        Some(adjustment)
+    }
+}
@@ -4,43 +4,1218 @@ extern crate raw_pointer as rptr;
 use rbtree::RBTree;
 use rptr::Pointer;
 use super::clock::Clock;
-use crate::proc::task::{Task, TaskStatus};
-use std::collections::VecDeque;
+use super::dynamic::DynamicWeight;
+use super::autogroup::Autogroup;
+use super::features::SchedFeatures;
+use super::observer::Observer;
+use super::weight::weight_to_nice;
+use crate::io::device::Device;
+use crate::proc::phase::{Step, SyncOp};
+use crate::proc::signal::Signal;
+use crate::proc::task::{Action, SchedPolicy, Task, TaskStatus};
+use crate::stats::fairness::FluidModel;
+use crate::stats::histogram::Latencies;
+use crate::stats::series::TimeSeries;
+use crate::trace::{EventKind, SwitchState, Trace};
+use crate::sync::{InversionWindow, SyncKind, SyncObject};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+// tasks are keyed by (vruntime, sequence number) so that every
+// key is unique and tasks with equal vruntime keep their fifo order
+type TaskKey = (u64, u64);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Buddy {
+    Next,
+    Last,
+    Skip,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RunqueueStats {
+    pub nr_switches: u64,
+    pub nr_wakeups: u64,
+    pub nr_forks: u64,
+    // ticks the cpu found nothing to run
+    pub nr_goidle: u64,
+    pub nr_next_buddy_picks: u64,
+    pub nr_last_buddy_picks: u64,
+    pub nr_skip_buddy_picks: u64,
+    pub wakeup_latency_count: u64,
+    pub wakeup_latency_sum: u128,
+    pub wakeup_latency_max: u128,
+}
+
+impl RunqueueStats {
+    pub fn mean_wakeup_latency(&self) -> f64 {
+        if self.wakeup_latency_count == 0 {
+            return 0.0;
+        }
+        self.wakeup_latency_sum as f64 / self.wakeup_latency_count as f64
+    }
+}
 
 pub struct FairAlgorithm {
-    tree: RBTree<u64, Task>,
+    tree: RBTree<TaskKey, Task>,
+    // the key of every task in the tree, and the keys again ordered with the
+    // sched_idle tasks last, overall and within each session, so that picks
+    // find the leftmost eligible task without going through the whole tree
+    keys: HashMap<u16, TaskKey>,
+    by_policy: BTreeSet<(bool, TaskKey)>,
+    by_session: BTreeSet<(u32, bool, TaskKey)>,
     idle: VecDeque<Task>,
     clock: Pointer<Clock>,
+    dynamic: Option<DynamicWeight>,
+    seq: u64,
+    autogroup: bool,
+    groups: HashMap<u32, Autogroup>,
+    curr: Option<Task>,
+    slice: u64,
+    slice_used: u64,
+    // when curr was picked, what it has run since is not in its vruntime yet
+    slice_start: u128,
+    // the smallest vruntime queued, never going back, as in the kernel
+    min_vruntime: u64,
+    wakeup_granularity: u64,
+    need_resched: bool,
+    policy_runtime: HashMap<SchedPolicy, u64>,
+    features: SchedFeatures,
+    next: Option<u16>,
+    last: Option<u16>,
+    skip: Option<u16>,
+    sleeping: Vec<(u128, Task)>,
+    exited: Vec<Task>,
+    prev_id: Option<u16>,
+    stats: RunqueueStats,
+    child_runs_first: bool,
+    inherit_vruntime: bool,
+    next_pid: u16,
+    // parents of children that are still alive, and how many each parent has
+    parents: HashMap<u16, u16>,
+    nr_children: HashMap<u16, usize>,
+    // exited children with the parent that has yet to reap them
+    zombies: Vec<(u16, Task)>,
+    // tasks blocked until their children have exited
+    waiting: Vec<Task>,
+    // periodic tasks between jobs, with the time of their next release
+    releases: Vec<(u128, Task)>,
+    devices: Vec<Device>,
+    // tasks blocked on a device request
+    blocked: Vec<Task>,
+    sync_objects: Vec<SyncObject>,
+    // tasks blocked on a lock, condition variable or wait queue
+    locked: Vec<Task>,
+    // tasks at a synchronisation step their object refuses, e.g. unlocking a
+    // mutex they don't hold, which are left blocked for good
+    stuck: HashSet<u16>,
+    priority_inheritance: bool,
+    // weights inherited by mutex holders from their waiters
+    boosts: HashMap<u16, u32>,
+    // own weight of every task as of its last insert
+    base_weights: HashMap<u16, u32>,
+    inversions: Vec<InversionWindow>,
+    inversion_bound: u64,
+    // tasks sent a SIGSTOP, which are parked once they would otherwise run
+    stopping: HashSet<u16>,
+    stopped: Vec<Task>,
+    // the service every task would have had under ideal gps
+    fluid: FluidModel,
+    // the schedule so far, if it is being traced
+    trace: Option<Trace>,
+    // set once the current tick has run, what happens from then on happens at its end
+    tick_done: bool,
+    // the runqueue and its tasks every few ticks, if they are being sampled
+    series: Option<TimeSeries>,
+    latencies: Latencies,
+    // when the tasks in the tree entered it
+    enqueued: HashMap<u16, u128>,
+    observers: Vec<Box<dyn Observer>>,
 }
 
 impl FairAlgorithm {
     pub fn new(clock: &mut Clock) -> Self {
         Self {
             tree: RBTree::new(),
+            keys: HashMap::new(),
+            by_policy: BTreeSet::new(),
+            by_session: BTreeSet::new(),
             idle: VecDeque::new(),
-            clock: Pointer::new(clock)
+            clock: Pointer::new(clock),
+            dynamic: None,
+            seq: 0,
+            autogroup: false,
+            groups: HashMap::new(),
+            curr: None,
+            slice: 1,
+            slice_used: 0,
+            slice_start: 0,
+            min_vruntime: 0,
+            wakeup_granularity: 0,
+            need_resched: false,
+            policy_runtime: HashMap::new(),
+            features: SchedFeatures::default(),
+            next: None,
+            last: None,
+            skip: None,
+            sleeping: Vec::new(),
+            exited: Vec::new(),
+            prev_id: None,
+            stats: RunqueueStats::default(),
+            child_runs_first: false,
+            inherit_vruntime: true,
+            next_pid: 1,
+            parents: HashMap::new(),
+            nr_children: HashMap::new(),
+            zombies: Vec::new(),
+            waiting: Vec::new(),
+            releases: Vec::new(),
+            devices: Vec::new(),
+            blocked: Vec::new(),
+            sync_objects: Vec::new(),
+            locked: Vec::new(),
+            stuck: HashSet::new(),
+            priority_inheritance: false,
+            boosts: HashMap::new(),
+            base_weights: HashMap::new(),
+            inversions: Vec::new(),
+            inversion_bound: 0,
+            fluid: FluidModel::default(),
+            trace: None,
+            tick_done: false,
+            series: None,
+            latencies: Latencies::new(),
+            enqueued: HashMap::new(),
+            observers: Vec::new(),
+            stopping: HashSet::new(),
+            stopped: Vec::new()
+        }
+    }
+
+    // whether a forked child preempts its parent right away
+    pub fn set_child_runs_first(&mut self, enabled: bool) {
+        self.child_runs_first = enabled;
+    }
+
+    // whether a forked child starts at its parent's vruntime or at zero like a new task
+    pub fn set_inherit_vruntime(&mut self, enabled: bool) {
+        self.inherit_vruntime = enabled;
+    }
+
+    // the pid handed to the next forked child, it has to be above every task's id
+    pub fn set_next_pid(&mut self, pid: u16) {
+        self.next_pid = pid;
+    }
+
+    #[inline]
+    pub fn get_child_runs_first(&self) -> bool { self.child_runs_first }
+
+    #[inline]
+    pub fn get_inherit_vruntime(&self) -> bool { self.inherit_vruntime }
+
+    // children of the task that have not exited yet
+    pub fn get_nr_children(&self, id: u16) -> usize {
+        *self.nr_children.get(&id).unwrap_or(&0)
+    }
+
+    pub fn get_zombies(&self) -> Vec<&Task> {
+        self.zombies.iter().map(|(_, task)| task).collect()
+    }
+
+    pub fn set_features(&mut self, features: SchedFeatures) {
+        self.features = features;
+    }
+
+    #[inline]
+    pub fn get_features(&self) -> SchedFeatures { self.features }
+
+    #[inline]
+    pub fn get_stats(&self) -> RunqueueStats { self.stats }
+
+    // number of ticks the current task may keep the cpu before it is preempted
+    pub fn set_slice(&mut self, slice: u64) {
+        if slice == 0 {
+            panic!("A time slice must be at least one tick");
+        }
+        self.slice = slice;
+    }
+
+    // how far a waking task's vruntime has to be behind the current task's to preempt it
+    pub fn set_wakeup_granularity(&mut self, granularity: u64) {
+        self.wakeup_granularity = granularity;
+    }
+
+    #[inline]
+    pub fn get_slice(&self) -> u64 { self.slice }
+
+    #[inline]
+    pub fn get_wakeup_granularity(&self) -> u64 { self.wakeup_granularity }
+
+    // the system time of the clock the runqueue runs by
+    pub fn get_time(&self) -> u128 {
+        self.clock.time()
+    }
+
+    pub fn get_curr(&self) -> Option<&Task> {
+        self.curr.as_ref()
+    }
+
+    // tasks that ran to completion and have been reaped, in that order
+    pub fn get_exited(&self) -> &[Task] {
+        &self.exited
+    }
+
+    // cpu ticks consumed by tasks of the given policy
+    pub fn get_policy_runtime(&self, policy: SchedPolicy) -> u64 {
+        *self.policy_runtime.get(&policy).unwrap_or(&0)
+    }
+
+    pub fn set_autogroup(&mut self, enabled: bool) {
+        self.autogroup = enabled;
+
+        for group in self.groups.values_mut() {
+            group.reset_nr_running();
+        }
+        if enabled {
+            let sessions: Vec<u32> = self.tree.values()
+                .map(|task| task.get_session())
+                .collect();
+            for session in sessions {
+                self.group_mut(session).enqueue(None);
+            }
+        }
+    }
+
+    #[inline]
+    pub fn is_autogroup(&self) -> bool {
+        self.autogroup
+    }
+
+    pub fn set_autogroup_nice(&mut self, session: u32, nice: i32) {
+        self.group_mut(session).set_nice(nice);
+    }
+
+    pub fn get_autogroup(&self, session: u32) -> Option<&Autogroup> {
+        self.groups.get(&session)
+    }
+
+    fn group_mut(&mut self, session: u32) -> &mut Autogroup {
+        self.groups
+            .entry(session)
+            .or_insert_with(|| Autogroup::new(session, 0))
+    }
+
+    fn min_group_vruntime(&self) -> Option<u64> {
+        self.groups.values()
+            .filter(|group| group.get_nr_running() > 0)
+            .map(|group| group.get_vruntime())
+            .min()
+    }
+
+    fn tree_insert(&mut self, key: TaskKey, task: Task) {
+        let idle = task.get_policy() == SchedPolicy::Idle;
+        self.keys.insert(task.get_id(), key);
+        self.by_policy.insert((idle, key));
+        self.by_session.insert((task.get_session(), idle, key));
+        self.tree.insert(key, task);
+    }
+
+    fn tree_remove(&mut self, key: &TaskKey) -> Task {
+        let task = self.tree.remove(key).unwrap();
+        let idle = task.get_policy() == SchedPolicy::Idle;
+        self.keys.remove(&task.get_id());
+        self.by_policy.remove(&(idle, *key));
+        self.by_session.remove(&(task.get_session(), idle, *key));
+        task
+    }
+
+    // keys of the session's tasks in tree order, either only the sched_idle ones or none of them
+    fn session_keys(&self, session: u32, idle: bool) -> impl Iterator<Item = TaskKey> + '_ {
+        self.by_session
+            .range((session, idle, (0, 0))..)
+            .take_while(move |(s, i, _)| *s == session && *i == idle)
+            .map(|(_, _, key)| *key)
+    }
+
+    // picks the leftmost task, sched_idle tasks are only eligible when nothing else is runnable
+    // and with autogroup enabled the task has to come from the group with the smallest vruntime.
+    // buddies win over the leftmost task as long as that is not unfair to it
+    fn pick_key(&self) -> (TaskKey, Option<Buddy>) {
+        // sched_idle tasks sort last, so the first one only comes first if nothing else is queued
+        let idle_only = self.by_policy.iter().next().unwrap().0;
+        let eligible = |task: &Task| idle_only || task.get_policy() != SchedPolicy::Idle;
+
+        let session = if self.autogroup {
+            self.groups.values()
+                .filter(|group| self.session_keys(group.get_session(), idle_only).next().is_some())
+                .min_by_key(|group| (group.get_vruntime(), group.get_session()))
+                .map(|group| group.get_session())
+        } else {
+            None
+        };
+
+        let candidates: Vec<TaskKey> = match session {
+            Some(session) => self.session_keys(session, idle_only).take(2).collect(),
+            None => self.by_policy.iter().take(2).filter(|(idle, _)| *idle == idle_only).map(|(_, key)| *key).collect()
+        };
+        let candidate = |task: &Task| eligible(task) && session.is_none_or(|s| task.get_session() == s);
+
+        let left = candidates[0];
+        let mut pick = (left, None);
+
+        // a task that yielded is passed over if anything else can run
+        if self.features.skip_buddy && self.skip == self.tree.get(&left).map(|task| task.get_id()) {
+            if let Some(second) = candidates.get(1) {
+                pick = (*second, Some(Buddy::Skip));
+            }
+        }
+
+        let buddy_key = |buddy: Option<u16>| {
+            let key = self.keys.get(&buddy?)?;
+            Some(*key)
+                .filter(|key| candidate(self.tree.get(key).unwrap()))
+                .filter(|key| key.0 <= left.0 + self.wakeup_granularity)
+        };
+        if self.features.last_buddy {
+            if let Some(key) = buddy_key(self.last) {
+                pick = (key, Some(Buddy::Last));
+            }
+        }
+        if self.features.next_buddy {
+            if let Some(key) = buddy_key(self.next) {
+                pick = (key, Some(Buddy::Next));
+            }
+        }
+
+        pick
+    }
+
+    // curr's vruntime with the part of its slice it has run so far charged
+    fn curr_vruntime(&self) -> Option<u64> {
+        let curr = self.curr.as_ref()?;
+        let ran = self.clock.time().saturating_sub(self.slice_start) as u64;
+        Some(curr.get_vruntime() + ran / curr.weight() as u64)
+    }
+
+    // whether a task other than the current one is waiting with a smaller vruntime
+    fn resched_pending(&self) -> bool {
+        let curr = match self.curr.as_ref() {
+            Some(curr) => curr,
+            None => return false
+        };
+        let vruntime = self.curr_vruntime().unwrap();
+
+        // only the leftmost task that could take the cpu from curr matters
+        let leftmost = if curr.get_policy() == SchedPolicy::Idle {
+            self.tree.get_first().map(|(key, _)| *key)
+        } else {
+            self.by_policy.iter().next().filter(|(idle, _)| !idle).map(|(_, key)| *key)
+        };
+        self.need_resched || leftmost.is_some_and(|key| key.0 < vruntime)
+    }
+
+    // counts an involuntary switch for a task that was put back
+    // into the tree if a different task is going to be picked
+    fn count_preemption(&mut self, id: u16) {
+        if self.tree.is_empty() {
+            return;
+        }
+
+        let (key, _) = self.pick_key();
+        if self.tree.get(&key).unwrap().get_id() == id {
+            return;
+        }
+        if let Some(key) = self.keys.get(&id).copied() {
+            self.tree.get_mut(&key).unwrap().count_switch(false);
+        }
+    }
+
+    // decides whether a task that just woke up should take the cpu from the current task
+    fn check_preempt_wakeup(&mut self, vruntime: u64, wakee: &Task, forked: bool) {
+        let curr = match self.curr.as_ref() {
+            Some(curr) => curr,
+            None => return
+        };
+        let curr_id = curr.get_id();
+
+        if self.features.next_buddy && !forked && wakee.get_policy() != SchedPolicy::Idle {
+            self.next = Some(wakee.get_id());
+        }
+        if self.need_resched {
+            return;
+        }
+
+        let preempt = if curr.get_policy() == SchedPolicy::Idle && wakee.get_policy() != SchedPolicy::Idle {
+            true
+        } else if wakee.get_policy() != SchedPolicy::Normal {
+            false
+        } else {
+            vruntime + self.wakeup_granularity < self.curr_vruntime().unwrap()
+        };
+
+        if preempt {
+            self.need_resched = true;
+            if self.features.last_buddy {
+                self.last = Some(curr_id);
+            }
+        }
+    }
+
+    fn clear_buddies(&mut self, id: u16) {
+        if self.next == Some(id) {
+            self.next = None;
+        }
+        if self.last == Some(id) {
+            self.last = None;
+        }
+        if self.skip == Some(id) {
+            self.skip = None;
+        }
+    }
+
+    pub fn add_device(&mut self, device: Device) {
+        if self.devices.iter().any(|other| other.get_name() == device.get_name()) {
+            panic!("Device {:?} was added twice", device.get_name());
+        }
+        self.devices.push(device);
+    }
+
+    pub fn get_devices(&self) -> &[Device] {
+        &self.devices
+    }
+
+    // hands the task's request to its device and blocks it until the request is served
+    fn submit(&mut self, task: Task) {
+        let (name, kind) = task.get_request().unwrap();
+        let now = self.clock.time();
+        match self.devices.iter_mut().find(|device| device.get_name() == name) {
+            Some(device) => device.submit(task.get_id(), kind, now),
+            None => panic!("Task id {} requested unknown device {:?}", task.get_id(), name)
+        };
+        self.blocked.push(task);
+    }
+
+    pub fn add_sync_object(&mut self, object: SyncObject) {
+        if self.sync_objects.iter().any(|other| other.get_name() == object.get_name()) {
+            panic!("Synchronisation object {:?} was added twice", object.get_name());
+        }
+        self.sync_objects.push(object);
+    }
+
+    pub fn get_sync_objects(&self) -> &[SyncObject] {
+        &self.sync_objects
+    }
+
+    // rt_mutex style, a mutex holder runs at the weight of its heaviest waiter
+    pub fn set_priority_inheritance(&mut self, enabled: bool) {
+        self.priority_inheritance = enabled;
+        self.update_boosts();
+    }
+
+    #[inline]
+    pub fn get_priority_inheritance(&self) -> bool { self.priority_inheritance }
+
+    // ticks lighter tasks may run while a heavier task waits on a lighter
+    // holder before the inversion counts as unbounded
+    pub fn set_inversion_bound(&mut self, ticks: u64) {
+        self.inversion_bound = ticks;
+    }
+
+    #[inline]
+    pub fn get_inversion_bound(&self) -> u64 { self.inversion_bound }
+
+    // ticks per window of the fairness index, starts the fluid model over
+    pub fn set_fairness_window(&mut self, ticks: u64) {
+        self.fluid = FluidModel::new(ticks);
+    }
+
+    pub fn get_fluid(&self) -> &FluidModel {
+        &self.fluid
+    }
+
+    // records the schedule from here on, or with false, stops recording it
+    pub fn set_tracing(&mut self, enabled: bool) {
+        self.trace = if enabled { Some(Trace::new()) } else { None };
+    }
+
+    pub fn get_trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    // samples the runqueue as every given number of ticks from here on runs,
+    // or with None, stops sampling it
+    pub fn set_sampling(&mut self, interval: Option<u64>) -> Result<(), String> {
+        self.series = interval.map(TimeSeries::new).transpose()?;
+        Ok(())
+    }
+
+    pub fn get_series(&self) -> Option<&TimeSeries> {
+        self.series.as_ref()
+    }
+
+    pub fn get_min_vruntime(&self) -> u64 {
+        self.min_vruntime
+    }
+
+    // moves min_vruntime up to the smallest vruntime curr and the tree hold
+    fn update_min_vruntime(&mut self) {
+        let now = self.clock.time();
+        let curr = self.curr.as_ref().map(|task| task.peek_vruntime(now));
+        let leftmost = self.tree.get_first().map(|(key, _)| key.0);
+        if let Some(min) = curr.into_iter().chain(leftmost).min() {
+            self.min_vruntime = self.min_vruntime.max(min);
+        }
+    }
+
+    fn sample(&mut self) {
+        let now = self.clock.time();
+        let min_vruntime = self.min_vruntime;
+        let series = match self.series.as_mut() {
+            Some(series) if series.is_due(now) => series,
+            _ => return
+        };
+        let curr = self.curr.as_ref().map(|task| (task, task.peek_vruntime(now)));
+        let runnable: Vec<(&Task, u64)> = self.tree.iter().map(|(key, task)| (task, key.0)).collect();
+        let others = self.idle.iter()
+            .chain(self.blocked.iter())
+            .chain(self.locked.iter())
+            .chain(self.waiting.iter())
+            .chain(self.stopped.iter())
+            .chain(self.sleeping.iter().map(|(_, task)| task))
+            .chain(self.releases.iter().map(|(_, task)| task))
+            .chain(self.zombies.iter().map(|(_, task)| task));
+        series.sample(now, min_vruntime, curr, &runnable, others);
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn Observer>) {
+        self.observers.push(observer);
+    }
+
+    // hands the observers back, in the order they were added
+    pub fn take_observers(&mut self) -> Vec<Box<dyn Observer>> {
+        std::mem::take(&mut self.observers)
+    }
+
+    fn notify(&mut self, event: impl Fn(&mut dyn Observer, u128)) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let now = self.trace_time();
+        for observer in self.observers.iter_mut() {
+            event(observer.as_mut(), now);
+        }
+    }
+
+    pub fn get_latencies(&self) -> &Latencies {
+        &self.latencies
+    }
+
+    // the task leaves the cpu after the ticks it ran since it was picked
+    fn end_slice(&mut self, id: u16, policy: SchedPolicy) {
+        self.latencies.slice.record(id, policy, self.slice_used);
+    }
+
+    fn trace_time(&self) -> u128 {
+        self.clock.time() + self.tick_done as u128
+    }
+
+    fn trace_event(&mut self, id: u16, kind: EventKind) {
+        let now = self.trace_time();
+        if let Some(trace) = self.trace.as_mut() {
+            trace.event(0, id, now, kind);
+        }
+    }
+
+    // closes the task's slice with wherever it went, unless it is still on the cpu
+    fn trace_switch_out(&mut self, id: u16) {
+        if self.trace.is_none() || self.curr.as_ref().is_some_and(|curr| curr.get_id() == id) {
+            return;
+        }
+        let state = self.get_switch_state(id).unwrap_or(SwitchState::Dead);
+        self.trace.as_mut().unwrap().switch_out(id, state);
+    }
+
+    // what a task off the cpu is doing, as the state it would have left the cpu
+    // in, Dead for a zombie, None for the task on the cpu or one not held here
+    pub fn get_switch_state(&self, id: u16) -> Option<SwitchState> {
+        let is_task = |task: &Task| task.get_id() == id;
+        if self.tree.values().any(is_task) {
+            Some(SwitchState::Preempted)
+        } else if self.stopped.iter().any(is_task) {
+            Some(SwitchState::Stopped)
+        } else if self.idle.iter().chain(self.blocked.iter()).chain(self.locked.iter()).any(is_task) {
+            Some(SwitchState::Blocked)
+        } else if self.sleeping.iter().chain(self.releases.iter()).map(|(_, task)| task).chain(self.waiting.iter()).any(is_task) {
+            Some(SwitchState::Sleeping)
+        } else if self.zombies.iter().any(|(_, task)| is_task(task)) {
+            Some(SwitchState::Dead)
+        } else {
+            None
+        }
+    }
+
+    // every task the runqueue holds, not those that exited, in order of id
+    pub fn get_tasks(&self) -> Vec<&Task> {
+        let mut tasks: Vec<&Task> = self.curr.iter()
+            .chain(self.tree.values())
+            .chain(self.idle.iter())
+            .chain(self.blocked.iter())
+            .chain(self.locked.iter())
+            .chain(self.waiting.iter())
+            .chain(self.stopped.iter())
+            .chain(self.sleeping.iter().map(|(_, task)| task))
+            .chain(self.releases.iter().map(|(_, task)| task))
+            .chain(self.zombies.iter().map(|(_, task)| task))
+            .collect();
+        tasks.sort_by_key(|task| task.get_id());
+        tasks
+    }
+
+    pub fn get_task(&self, id: u16) -> Option<&Task> {
+        self.get_tasks().into_iter().find(|task| task.get_id() == id)
+    }
+
+    // the tasks waiting in the tree with the vruntimes they are keyed by, leftmost first
+    pub fn get_runnable(&self) -> Vec<(u64, &Task)> {
+        self.tree.iter().map(|(key, task)| (key.0, task)).collect()
+    }
+
+    pub fn get_inversions(&self) -> &[InversionWindow] {
+        &self.inversions
+    }
+
+    pub fn get_unbounded_inversions(&self) -> Vec<&InversionWindow> {
+        self.inversions.iter().filter(|window| window.is_unbounded(self.inversion_bound)).collect()
+    }
+
+    // the weight a task inherits, if any
+    pub fn get_boost(&self, id: u16) -> Option<u32> {
+        self.boosts.get(&id).copied()
+    }
+
+    // works out every holder's boost from its waiters, following chains
+    // of tasks blocked on mutexes held by tasks that are blocked themselves
+    fn update_boosts(&mut self) {
+        let mut boosts: HashMap<u16, u32> = HashMap::new();
+
+        if self.priority_inheritance {
+            loop {
+                let mut changed = false;
+                for object in self.sync_objects.iter().filter(|object| object.get_kind() == SyncKind::Mutex) {
+                    let holder = match object.get_holders().first() {
+                        Some(holder) => *holder,
+                        None => continue
+                    };
+                    let heaviest = self.locked
+                        .iter()
+                        .filter(|task| object.get_waiters().contains(&task.get_id()))
+                        .map(|task| task.base_weight().max(*boosts.get(&task.get_id()).unwrap_or(&0)))
+                        .max();
+                    if let Some(weight) = heaviest {
+                        let own = *self.base_weights.get(&holder).unwrap_or(&0);
+                        if weight > own && weight > *boosts.get(&holder).unwrap_or(&0) {
+                            boosts.insert(holder, weight);
+                            changed = true;
+                        }
+                    }
+                }
+                if !changed {
+                    break;
+                }
+            }
+        }
+
+        self.boosts = boosts;
+        if let Some(curr) = self.curr.as_mut() {
+            curr.set_boost(self.boosts.get(&curr.get_id()).copied());
+        }
+    }
+
+    // counts the tick against every inversion window the running task is not helping along
+    fn track_inversions(&mut self, id: u16, weight: u32) {
+        for window in self.inversions.iter_mut().filter(|window| window.end.is_none()) {
+            let holder = self.sync_objects
+                .iter()
+                .find(|object| object.get_name() == window.lock)
+                .and_then(|object| object.get_holders().first().copied());
+            let waiter_weight = *self.base_weights.get(&window.waiter).unwrap_or(&0);
+            if holder != Some(id) && weight < waiter_weight {
+                window.interference += 1;
+            }
+        }
+    }
+
+    // stopped tasks, off the cpu until they get a SIGCONT
+    pub fn get_stopped(&self) -> &[Task] {
+        &self.stopped
+    }
+
+    pub fn is_stopped(&self, id: u16) -> bool {
+        self.stopping.contains(&id)
+    }
+
+    // delivers a signal, returning false if there is no such task to deliver it to
+    pub fn signal(&mut self, id: u16, signal: Signal) -> bool {
+        self.tick_done = false;
+        let delivered = match signal {
+            Signal::Stop => self.stop(id),
+            Signal::Cont => self.cont(id),
+            Signal::Kill => self.kill(id)
+        };
+        if delivered {
+            self.notify(|observer, now| observer.signal(now, id, signal));
+        }
+        self.trace_switch_out(id);
+        delivered
+    }
+
+    // takes a runnable task off the cpu or out of the tree
+    fn dequeue(&mut self, id: u16) -> Option<Task> {
+        if self.curr.as_ref().is_some_and(|curr| curr.get_id() == id) {
+            let mut task = self.curr.take().unwrap();
+            task.count_switch(true);
+            self.clear_buddies(id);
+            self.end_slice(id, task.get_policy());
+            self.notify(|observer, now| observer.dequeue(now, &task));
+            return Some(task);
+        }
+
+        let key = self.keys.get(&id).copied()?;
+        let task = self.tree_remove(&key);
+        self.clear_buddies(id);
+        self.enqueued.remove(&id);
+        if self.autogroup {
+            self.group_mut(task.get_session()).dequeue();
+        }
+        self.notify(|observer, now| observer.dequeue(now, &task));
+        Some(task)
+    }
+
+    // whether the task is anywhere but the tree or the cpu
+    fn is_blocked(&self, id: u16) -> bool {
+        self.idle.iter()
+            .chain(self.sleeping.iter().map(|(_, task)| task))
+            .chain(self.blocked.iter())
+            .chain(self.locked.iter())
+            .chain(self.waiting.iter())
+            .chain(self.releases.iter().map(|(_, task)| task))
+            .chain(self.stopped.iter())
+            .any(|task| task.get_id() == id)
+    }
+
+    // parks the task right away if it is runnable, otherwise once it wakes up
+    fn stop(&mut self, id: u16) -> bool {
+        match self.dequeue(id) {
+            Some(mut task) => {
+                task.count_stop();
+                self.stopping.insert(id);
+                task.to_stopped();
+                self.stopped.push(task);
+                true
+            },
+            None if self.is_blocked(id) => {
+                if self.stopping.insert(id) {
+                    if let Some(task) = self.find_blocked_mut(id) {
+                        task.count_stop();
+                    }
+                }
+                true
+            },
+            None => false
+        }
+    }
+
+    fn find_blocked_mut(&mut self, id: u16) -> Option<&mut Task> {
+        self.idle.iter_mut()
+            .chain(self.sleeping.iter_mut().map(|(_, task)| task))
+            .chain(self.blocked.iter_mut())
+            .chain(self.locked.iter_mut())
+            .chain(self.waiting.iter_mut())
+            .chain(self.releases.iter_mut().map(|(_, task)| task))
+            .find(|task| task.get_id() == id)
+    }
+
+    // a stopped task is woken up, one still blocked just runs again once it wakes
+    fn cont(&mut self, id: u16) -> bool {
+        let stopping = self.stopping.remove(&id);
+        match self.stopped.iter().position(|task| task.get_id() == id) {
+            Some(idx) => {
+                let mut task = self.stopped.remove(idx);
+                task.schedule();
+                self.insert(task);
+                true
+            },
+            None => stopping || self.is_runnable(id) || self.is_blocked(id)
+        }
+    }
+
+    fn is_runnable(&self, id: u16) -> bool {
+        self.curr.as_ref().is_some_and(|curr| curr.get_id() == id)
+            || self.keys.contains_key(&id)
+    }
+
+    // takes the task out of wherever it is, frees what it holds and exits it
+    fn kill(&mut self, id: u16) -> bool {
+        self.stopping.remove(&id);
+
+        let mut task = match self.dequeue(id) {
+            Some(task) => task,
+            None => {
+                let take = |tasks: &mut Vec<Task>| tasks.iter().position(|task| task.get_id() == id).map(|idx| tasks.remove(idx));
+                let found = take(&mut self.stopped)
+                    .or_else(|| take(&mut self.blocked))
+                    .or_else(|| take(&mut self.locked))
+                    .or_else(|| take(&mut self.waiting))
+                    .or_else(|| self.idle.iter().position(|task| task.get_id() == id).and_then(|idx| self.idle.remove(idx)))
+                    .or_else(|| self.sleeping.iter().position(|(_, task)| task.get_id() == id).map(|idx| self.sleeping.remove(idx).1))
+                    .or_else(|| self.releases.iter().position(|(_, task)| task.get_id() == id).map(|idx| self.releases.remove(idx).1));
+                match found {
+                    Some(task) => task,
+                    None => return false
+                }
+            }
+        };
+
+        let now = self.clock.time();
+        for device in self.devices.iter_mut() {
+            device.cancel(id);
+        }
+        for object in self.sync_objects.iter_mut() {
+            object.cancel(id);
+        }
+        for window in self.inversions.iter_mut().filter(|window| window.waiter == id && window.end.is_none()) {
+            window.end = Some(now);
+        }
+
+        // like a robust futex, the locks of a dead task are not left held
+        let held: Vec<String> = self.sync_objects
+            .iter()
+            .filter(|object| object.get_holders().contains(&id))
+            .map(|object| object.get_name().to_string())
+            .collect();
+        for name in held {
+            if let Ok(Some(waiter)) = self.sync_object(id, &name).release(id, now) {
+                self.unlock_waiter(waiter);
+            }
+        }
+        self.stuck.remove(&id);
+
+        task.kill();
+        self.exit(task);
+        self.update_boosts();
+        true
+    }
+
+    // tasks that can't go on until another task releases or signals something
+    pub fn get_locked(&self) -> &[Task] {
+        &self.locked
+    }
+
+    fn sync_object(&mut self, id: u16, name: &str) -> &mut SyncObject {
+        match self.sync_objects.iter_mut().find(|object| object.get_name() == name) {
+            Some(object) => object,
+            None => panic!("Task id {} used unknown synchronisation object {:?}", id, name)
+        }
+    }
+
+    // carries out the synchronisation steps the task is at until it either
+    // reaches another kind of step or has to block, in which case it is left idle
+    fn sync(&mut self, task: &mut Task) {
+        self.sync_steps(task);
+        self.update_boosts();
+    }
+
+    fn sync_steps(&mut self, task: &mut Task) {
+        let now = self.clock.time();
+        let id = task.get_id();
+
+        while let Some((op, name, _)) = task.get_sync() {
+            let name = name.to_string();
+            let done = match op {
+                SyncOp::Lock => self.sync_object(id, &name).try_acquire(id, now),
+                SyncOp::Unlock => self.sync_object(id, &name).release(id, now).map(|waiter| {
+                    if let Some(waiter) = waiter {
+                        self.unlock_waiter(waiter);
+                    }
+                    true
+                }),
+                SyncOp::Wait => match self.sync_object(id, &name) {
+                    object if object.get_kind().is_lock() => {
+                        Err(format!("Task id {} waited on {} {:?}", id, object.get_kind(), name))
+                    },
+                    _ => Ok(false)
+                },
+                SyncOp::Signal | SyncOp::Broadcast => {
+                    let nr = if op == SyncOp::Signal { Some(1) } else { None };
+                    self.sync_object(id, &name).wake(nr, now).map(|woken| {
+                        for waiter in woken {
+                            self.signal_waiter(waiter);
+                        }
+                        true
+                    })
+                }
+            };
+
+            match done {
+                Ok(true) => task.wake(),
+                Ok(false) => {
+                    task.to_idle();
+                    return;
+                },
+                Err(_) => {
+                    self.stuck.insert(id);
+                    task.to_idle();
+                    return;
+                }
+            };
+        }
+    }
+
+    // parks a task that blocked in sync on the object's wait list
+    fn lock_wait(&mut self, task: Task) {
+        let now = self.clock.time();
+        if self.stuck.contains(&task.get_id()) {
+            self.locked.push(task);
+            return;
+        }
+        let (_, name, _) = task.get_sync().unwrap();
+        let object = self.sync_object(task.get_id(), name);
+        object.enqueue(task.get_id(), now);
+
+        // a heavier task stuck behind a lighter one
+        if object.get_kind() == SyncKind::Mutex {
+            let holder = object.get_holders()[0];
+            let lock = object.get_name().to_string();
+            if self.base_weights.get(&holder).is_some_and(|weight| *weight < task.base_weight()) {
+                self.inversions.push(InversionWindow {
+                    waiter: task.get_id(),
+                    lock,
+                    holder,
+                    start: now,
+                    end: None,
+                    interference: 0
+                });
+            }
+        }
+        self.locked.push(task);
+        self.update_boosts();
+    }
+
+    // the lock was handed to a waiter, which carries on
+    fn unlock_waiter(&mut self, id: u16) {
+        let now = self.clock.time();
+        if let Some(window) = self.inversions.iter_mut().find(|window| window.waiter == id && window.end.is_none()) {
+            window.end = Some(now);
+        }
+        let idx = self.locked.iter().position(|task| task.get_id() == id).unwrap();
+        let mut task = self.locked.remove(idx);
+        task.wake();
+        self.insert(task);
+    }
+
+    // a waiter on a condition variable takes its mutex back before it carries on
+    fn signal_waiter(&mut self, id: u16) {
+        let idx = self.locked.iter().position(|task| task.get_id() == id).unwrap();
+        let mutex = self.locked[idx].get_sync().unwrap().2.map(String::from);
+
+        if let Some(mutex) = mutex {
+            let now = self.clock.time();
+            let object = self.sync_object(id, &mutex);
+            match object.try_acquire(id, now) {
+                Ok(true) => {},
+                Ok(false) => {
+                    object.enqueue(id, now);
+                    return;
+                },
+                Err(_) => {
+                    self.stuck.insert(id);
+                    return;
+                }
+            };
         }
+        self.unlock_waiter(id);
+    }
+
+    pub fn set_dynamic_weight(&mut self, dynamic: Option<DynamicWeight>) {
+        self.dynamic = dynamic;
+    }
+
+    pub fn get_dynamic_weight(&self) -> Option<&DynamicWeight> {
+        self.dynamic.as_ref()
     }
 
     #[inline]
     pub fn push(&mut self, tasks: Vec<Task>) {
+        self.tick_done = false;
         for task in tasks {
             self.insert(task);
         }
     }
 
+    fn spawn_children(&mut self, parent: &mut Task) {
+        for script in parent.take_forks() {
+            let pid = self.next_pid;
+            self.next_pid = pid.checked_add(1).expect("Ran out of pids");
+
+            // unless children run first, a child starts just behind its parent
+            let now = self.clock.time();
+            let vruntime = match (self.inherit_vruntime, self.child_runs_first) {
+                (true, true) => parent.peek_vruntime(now),
+                (true, false) => parent.peek_vruntime(now) + 1,
+                (false, _) => 0
+            };
+            let child = parent.fork(script, pid, now, vruntime);
+
+            self.parents.insert(pid, parent.get_id());
+            *self.nr_children.entry(parent.get_id()).or_insert(0) += 1;
+            self.stats.nr_forks += 1;
+            if self.child_runs_first && parent.get_status() == TaskStatus::Running {
+                self.need_resched = true;
+            }
+            self.insert(child);
+        }
+    }
+
+    fn exit(&mut self, mut task: Task) {
+        let id = task.get_id();
+        // at the end of the tick the task exited in
+        task.set_completion(self.clock.time() + 1);
+        self.trace_event(id, EventKind::Exit);
+        self.notify(|observer, now| observer.exit(now, &task));
+
+        // children outliving their parent are reparented to init, which reaps them right away
+        self.parents.retain(|_, parent| *parent != id);
+        self.nr_children.remove(&id);
+        self.reap(id);
+
+        match self.parents.remove(&id) {
+            Some(parent) => {
+                task.to_zombie();
+                self.zombies.push((parent, task));
+
+                let nr_children = self.nr_children.entry(parent).or_insert(1);
+                *nr_children -= 1;
+                if *nr_children == 0 {
+                    if let Some(idx) = self.waiting.iter().position(|task| task.get_id() == parent) {
+                        let waiter = self.waiting.remove(idx);
+                        self.wait(waiter);
+                    }
+                }
+            },
+            None => {
+                task.to_dead();
+                self.exited.push(task);
+            }
+        };
+    }
+
+    fn reap(&mut self, parent: u16) {
+        let (reaped, zombies): (Vec<_>, Vec<_>) = self.zombies
+            .drain(..)
+            .partition(|(zombie_parent, _)| *zombie_parent == parent);
+        self.zombies = zombies;
+
+        for (_, mut task) in reaped {
+            task.to_dead();
+            self.exited.push(task);
+        }
+    }
+
+    // blocks the task until it has no children left, then reaps them
+    fn wait(&mut self, mut task: Task) {
+        if self.get_nr_children(task.get_id()) > 0 {
+            self.waiting.push(task);
+            return;
+        }
+
+        self.reap(task.get_id());
+        task.wake();
+        self.insert(task);
+    }
+
     #[inline]
     pub fn insert(&mut self, mut task: Task) {
+        self.spawn_children(&mut task);
+        self.sync(&mut task);
+        self.base_weights.insert(task.get_id(), task.base_weight());
+        task.set_boost(self.boosts.get(&task.get_id()).copied());
+
         let state = task.get_status();
         if state == TaskStatus::Terminated {
+            match task.finish_job(self.clock.time()) {
+                Some(release) => self.releases.push((release, task)),
+                None => self.exit(task)
+            };
             return;
-        } else if state == TaskStatus::Idle {
-            self.idle.push_back(task);
+        }
+
+        if let Some(dynamic) = self.dynamic.as_mut() {
+            if let Some(adj) = dynamic.adjust(&mut task, self.clock.time()) {
+                self.notify(|observer, now| observer.reweight(now, &task, &adj));
+            }
+        }
+
+        if state == TaskStatus::Idle {
+            if task.get_step() == Step::Wait {
+                self.wait(task);
+                return;
+            }
+            if task.get_request().is_some() {
+                self.submit(task);
+                return;
+            }
+            if task.get_sync().is_some() {
+                self.lock_wait(task);
+                return;
+            }
+            match task.take_action() {
+                Some(Action::Sleep(ticks)) => {
+                    let wake_time = self.clock.time() + ticks as u128;
+                    self.sleeping.push((wake_time, task));
+                },
+                _ => self.idle.push_back(task)
+            };
+            return;
+        }
+        if self.stopping.contains(&task.get_id()) {
+            task.to_stopped();
+            self.stopped.push(task);
             return;
         }
+
         let key: u64 = task.vruntime(self.clock.time());
         task.schedule();
-        self.tree.insert(key, task);
+
+        let now = self.trace_time();
+        if let Some(trace) = self.trace.as_mut() {
+            // periodic tasks start every job as new, but only the first one is a fork
+            let id = task.get_id();
+            let first = trace.names.insert(id, task.get_name().to_string()).is_none();
+            trace.prios.insert(id, 120 + weight_to_nice(task.weight()));
+            match state {
+                TaskStatus::Running => {},
+                TaskStatus::New if first => trace.event(0, id, now, EventKind::WakeupNew),
+                _ => trace.event(0, id, now, EventKind::Wakeup)
+            };
+        }
+        if state != TaskStatus::Running {
+            self.notify(|observer, now| observer.wakeup(now, &task));
+            task.set_wake_time(self.clock.time());
+            self.stats.nr_wakeups += 1;
+            self.check_preempt_wakeup(key, &task, state == TaskStatus::New);
+        }
+
+        if self.autogroup {
+            // only waking tasks place their group, a preempted task's group never left the cpu
+            let min_vruntime = match state {
+                TaskStatus::Running => None,
+                _ => self.min_group_vruntime()
+            };
+            self.group_mut(task.get_session()).enqueue(min_vruntime);
+        }
+        self.seq += 1;
+        self.enqueued.insert(task.get_id(), self.trace_time());
+        self.notify(|observer, now| observer.enqueue(now, &task));
+        self.tree_insert((key, self.seq), task);
     }
 
     #[inline]
@@ -48,13 +1223,42 @@ impl FairAlgorithm {
         if self.is_empty() {
             panic!("Attempted to pop from an empty tree");
         }
-        let mut task = Box::new(
-            self.tree
-                .pop_first()
-                .unwrap()
-                .1
-        );
+        let (key, buddy) = self.pick_key();
+        let mut task = Box::new(self.tree_remove(&key));
+        let id = task.get_id();
+        self.clear_buddies(id);
+
+        match buddy {
+            Some(Buddy::Next) => self.stats.nr_next_buddy_picks += 1,
+            Some(Buddy::Last) => self.stats.nr_last_buddy_picks += 1,
+            Some(Buddy::Skip) => self.stats.nr_skip_buddy_picks += 1,
+            None => {}
+        };
+        if self.prev_id != Some(id) {
+            self.stats.nr_switches += 1;
+            self.prev_id = Some(id);
+        }
+        task.set_first_run(self.clock.time());
+        let now = self.clock.time();
+        self.slice_start = now;
+        if let Some(wake_time) = task.take_wake_time() {
+            let latency = now.saturating_sub(wake_time);
+            self.stats.wakeup_latency_count += 1;
+            self.stats.wakeup_latency_sum += latency;
+            self.stats.wakeup_latency_max = self.stats.wakeup_latency_max.max(latency);
+            self.latencies.wakeup.record(id, task.get_policy(), latency as u64);
+        }
+        if let Some(enqueued) = self.enqueued.remove(&id) {
+            self.latencies.wait.record(id, task.get_policy(), now.saturating_sub(enqueued) as u64);
+        }
+
+        if self.autogroup {
+            let group = self.group_mut(task.get_session());
+            group.dequeue();
+            group.charge(1);
+        }
         task.run();
+        self.notify(|observer, now| observer.pick(now, &task));
 
         task
     }
@@ -64,23 +1268,188 @@ impl FairAlgorithm {
         self.tree.is_empty()
     }
 
+    // done once nothing is left that could ever run again, tasks still waiting
+    // for children or blocked on a lock at that point are deadlocked
     #[inline]
     pub fn is_finished(&self) -> bool {
-        self.is_empty() && self.idle.len() == 0
+        self.curr.is_none() && self.is_empty() && self.idle.is_empty() && self.sleeping.is_empty()
+            && self.releases.is_empty() && self.blocked.is_empty()
     }
 
-    pub fn run(&mut self) {
-        if self.is_empty() {
-            return;
+    pub fn get_deadlocked(&self) -> Vec<&Task> {
+        if !self.is_finished() {
+            return Vec::new();
         }
+        self.waiting.iter().chain(self.locked.iter()).collect()
+    }
 
-        let mut task = *self.pop();
-        task.cpu_cycle();
-        self.insert(task);
+    // runs the current task for one tick, returning the id of the task that ran
+    pub fn run(&mut self) -> Option<u16> {
+        self.tick_done = false;
+        // counted by the clock rather than in idle, where the first io cycle of a
+        // task overlaps the tick it ran in
+        for task in self.idle.iter_mut().chain(self.blocked.iter_mut()) {
+            task.blocked_cycle();
+        }
+        if self.need_resched {
+            self.need_resched = false;
+            if let Some(curr) = self.curr.take() {
+                let id = curr.get_id();
+                self.end_slice(id, curr.get_policy());
+                self.notify(|observer, now| observer.preempt(now, &curr));
+                self.insert(curr);
+                self.trace_switch_out(id);
+            }
+        }
+
+        if self.curr.is_none() {
+            if self.is_empty() {
+                let now = self.clock.time();
+                if let Some(trace) = self.trace.as_mut() {
+                    trace.idle(0, now);
+                }
+                self.stats.nr_goidle += 1;
+                self.notify(|observer, now| observer.tick(now, None));
+                self.update_min_vruntime();
+                self.sample();
+                return None;
+            }
+            self.curr = Some(*self.pop());
+            self.slice_used = 0;
+        } else if self.autogroup {
+            // the first tick of a slice is charged when the task is picked
+            let session = self.curr.as_ref().unwrap().get_session();
+            self.group_mut(session).charge(1);
+        }
+
+        let (id, weight) = {
+            let curr = self.curr.as_ref().unwrap();
+            (curr.get_id(), curr.base_weight())
+        };
+        self.track_inversions(id, weight);
+        let now = self.clock.time();
+        if let Some(trace) = self.trace.as_mut() {
+            trace.run(0, id, now);
+        }
+        for (_, task) in self.tree.iter_mut() {
+            task.wait_cycle();
+        }
+        let runnable: Vec<(u16, u32)> = self.curr.iter()
+            .chain(self.tree.values())
+            .map(|task| (task.get_id(), task.weight()))
+            .collect();
+        self.fluid.tick(self.clock.time(), &runnable, Some(id));
+        self.update_min_vruntime();
+        self.sample();
+
+        let curr = self.curr.as_mut().unwrap();
+        // the tick is charged to the policy it ran under, even if the task switches policy
+        let policy = curr.get_policy();
+        curr.cpu_cycle();
+        let mut curr = self.curr.take().unwrap();
+        self.notify(|observer, now| observer.tick(now, Some(&curr)));
+        self.tick_done = true;
+        self.slice_used += 1;
+
+        self.spawn_children(&mut curr);
+        self.sync(&mut curr);
+        self.curr = Some(curr);
+        let curr = self.curr.as_mut().unwrap();
+
+        let id = curr.get_id();
+        *self.policy_runtime.entry(policy).or_insert(0) += 1;
+
+        let running = curr.get_status() == TaskStatus::Running;
+        let mut leave = !running || self.slice_used >= self.slice;
+        if running {
+            match curr.take_action() {
+                Some(Action::Yield) => {
+                    curr.count_yield();
+                    self.skip = Some(id);
+                    leave = true;
+                },
+                Some(Action::CondResched) => {
+                    leave = leave || self.resched_pending();
+                },
+                _ => {}
+            };
+        }
+
+        if leave {
+            let mut task = self.curr.take().unwrap();
+            self.end_slice(id, policy);
+            if running {
+                self.notify(|observer, now| observer.preempt(now, &task));
+                self.insert(task);
+                self.count_preemption(id);
+            } else {
+                task.count_switch(true);
+                self.clear_buddies(id);
+                if task.get_status() != TaskStatus::Terminated {
+                    self.notify(|observer, now| observer.block(now, &task));
+                }
+                self.insert(task);
+            }
+            self.trace_switch_out(id);
+        }
+
+        Some(id)
+    }
+
+    fn wake_sleepers(&mut self) {
+        let now = self.clock.time();
+        let (ready, sleeping): (Vec<_>, Vec<_>) = self.sleeping
+            .drain(..)
+            .partition(|(wake_time, _)| *wake_time <= now);
+        self.sleeping = sleeping;
+
+        for (_, mut task) in ready {
+            task.wake();
+            self.insert(task);
+        }
+    }
+
+    // starts the next job of every periodic task that is due
+    fn release_jobs(&mut self) {
+        let now = self.clock.time();
+        let (ready, releases): (Vec<_>, Vec<_>) = self.releases
+            .drain(..)
+            .partition(|(release, _)| *release <= now);
+        self.releases = releases;
+
+        for (release, mut task) in ready {
+            task.restart(release);
+            self.insert(task);
+        }
+    }
+
+    // advances every device by a tick and wakes the tasks whose request is done
+    fn tick_devices(&mut self) {
+        let now = self.clock.time();
+        let done: Vec<u16> = self.devices
+            .iter_mut()
+            .flat_map(|device| device.tick(now))
+            .collect();
+
+        for id in done {
+            // a killed task's request still runs to completion
+            let idx = match self.blocked.iter().position(|task| task.get_id() == id) {
+                Some(idx) => idx,
+                None => continue
+            };
+            let mut task = self.blocked.remove(idx);
+            task.wake();
+            self.insert(task);
+        }
     }
 
     pub fn idle(&mut self) {
-        if self.idle.len() == 0 {
+        self.tick_done = true;
+        self.wake_sleepers();
+        self.release_jobs();
+        self.tick_devices();
+
+        if self.idle.is_empty() {
             return;
         }
 
@@ -0,0 +1,81 @@
+// scheduler feature flags in the style of /sys/kernel/debug/sched_features
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SchedFeatures {
+    // prefer the task that just woke up on the next pick
+    pub next_buddy: bool,
+    // prefer the task that was just preempted by a wakeup on the next pick
+    pub last_buddy: bool,
+    // pass over a task that called sched_yield() on the next pick
+    pub skip_buddy: bool,
+}
+
+impl SchedFeatures {
+    pub fn none() -> Self {
+        Self {
+            next_buddy: false,
+            last_buddy: false,
+            skip_buddy: false
+        }
+    }
+
+    // sets a single flag by name, a NO_ prefix clears it
+    pub fn apply(&mut self, flag: &str) -> Result<(), String> {
+        let (name, enabled) = match flag.strip_prefix("NO_") {
+            Some(name) => (name, false),
+            None => (flag, true)
+        };
+
+        match name {
+            "NEXT_BUDDY" => self.next_buddy = enabled,
+            "LAST_BUDDY" => self.last_buddy = enabled,
+            "SKIP_BUDDY" => self.skip_buddy = enabled,
+            _ => return Err(format!("Unknown scheduler feature {:?}", flag))
+        };
+
+        Ok(())
+    }
+}
+
+// same defaults as the kernel, with yield always honoured
+impl Default for SchedFeatures {
+    fn default() -> Self {
+        Self {
+            next_buddy: false,
+            last_buddy: true,
+            skip_buddy: true
+        }
+    }
+}
+
+// parses a whitespace separated list of flags on top of the defaults
+impl FromStr for SchedFeatures {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut features = Self::default();
+        for flag in s.split_whitespace() {
+            features.apply(flag)?;
+        }
+
+        Ok(features)
+    }
+}
+
+impl fmt::Display for SchedFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flag = |enabled: bool, name: &str| {
+            if enabled { name.to_string() } else { format!("NO_{}", name) }
+        };
+
+        write!(
+            f, "{} {} {}",
+            flag(self.next_buddy, "NEXT_BUDDY"),
+            flag(self.last_buddy, "LAST_BUDDY"),
+            flag(self.skip_buddy, "SKIP_BUDDY")
+        )
+    }
+}
@@ -1,3 +1,12 @@
 pub mod fair;
 pub mod scheduler;
 pub mod clock;
+pub mod dynamic;
+pub mod weight;
+pub mod autogroup;
+pub mod features;
+pub mod procfs;
+pub mod observer;
+
+// the simulator models a single processor
+pub const NR_CPUS: u32 = 1;
@@ -0,0 +1,107 @@
+// hooks into the runqueue for whatever else wants to watch a run, metrics,
+// tracers or assertions, without the scheduler knowing about them; every
+// callback gets the system time it happened at, the end of a tick being
+// the start of the next, as in the traces. the trace, time series, latency
+// histograms and fairness the report carries are still kept by the runqueue
+
+use super::dynamic::WeightAdjustment;
+use crate::proc::signal::Signal;
+use crate::proc::task::Task;
+
+use std::sync::{Arc, Mutex};
+
+#[allow(unused_variables)]
+pub trait Observer: Send {
+    // the task went into the runqueue, woken, new or preempted
+    fn enqueue(&mut self, now: u128, task: &Task) {}
+    // the task was taken out of the runqueue without running, by a signal
+    fn dequeue(&mut self, now: u128, task: &Task) {}
+    // the task was picked to run next
+    fn pick(&mut self, now: u128, task: &Task) {}
+    // the task was put back into the runqueue while it could still run
+    fn preempt(&mut self, now: u128, task: &Task) {}
+    // the task ran for the tick starting at now, None if the cpu was idle
+    fn tick(&mut self, now: u128, curr: Option<&Task>) {}
+    // the task became runnable, new ones included
+    fn wakeup(&mut self, now: u128, task: &Task) {}
+    // the task left the cpu to sleep, do io, take a lock or wait for children
+    fn block(&mut self, now: u128, task: &Task) {}
+    // the task moved between cpus, which a single cpu never does
+    fn migrate(&mut self, now: u128, task: &Task, from: u32, to: u32) {}
+    // the task is gone, as a zombie until its parent reaps it
+    fn exit(&mut self, now: u128, task: &Task) {}
+    // the signal was delivered to the task with the given id
+    fn signal(&mut self, now: u128, id: u16, signal: Signal) {}
+    // dynamic weights changed the task's weight
+    fn reweight(&mut self, now: u128, task: &Task, adjustment: &WeightAdjustment) {}
+}
+
+// so that whoever registers an observer can keep a handle on it
+impl<O: Observer> Observer for Arc<Mutex<O>> {
+    fn enqueue(&mut self, now: u128, task: &Task) {
+        self.lock().unwrap().enqueue(now, task);
+    }
+
+    fn dequeue(&mut self, now: u128, task: &Task) {
+        self.lock().unwrap().dequeue(now, task);
+    }
+
+    fn pick(&mut self, now: u128, task: &Task) {
+        self.lock().unwrap().pick(now, task);
+    }
+
+    fn preempt(&mut self, now: u128, task: &Task) {
+        self.lock().unwrap().preempt(now, task);
+    }
+
+    fn tick(&mut self, now: u128, curr: Option<&Task>) {
+        self.lock().unwrap().tick(now, curr);
+    }
+
+    fn wakeup(&mut self, now: u128, task: &Task) {
+        self.lock().unwrap().wakeup(now, task);
+    }
+
+    fn block(&mut self, now: u128, task: &Task) {
+        self.lock().unwrap().block(now, task);
+    }
+
+    fn migrate(&mut self, now: u128, task: &Task, from: u32, to: u32) {
+        self.lock().unwrap().migrate(now, task, from, to);
+    }
+
+    fn exit(&mut self, now: u128, task: &Task) {
+        self.lock().unwrap().exit(now, task);
+    }
+
+    fn signal(&mut self, now: u128, id: u16, signal: Signal) {
+        self.lock().unwrap().signal(now, id, signal);
+    }
+
+    fn reweight(&mut self, now: u128, task: &Task, adjustment: &WeightAdjustment) {
+        self.lock().unwrap().reweight(now, task, adjustment);
+    }
+}
+
+// what a run prints as it goes, a line per tick a task ran, per signal
+// delivered and per weight dynamic weights changed
+pub struct LoggingObserver;
+
+impl Observer for LoggingObserver {
+    fn tick(&mut self, now: u128, curr: Option<&Task>) {
+        if let Some(curr) = curr {
+            println!("Running task id {:?} at system time {:?}", curr.get_id(), now);
+        }
+    }
+
+    fn signal(&mut self, now: u128, id: u16, signal: Signal) {
+        println!("Delivered {} to task id {:?} at system time {:?}", signal, id, now);
+    }
+
+    fn reweight(&mut self, _now: u128, _task: &Task, adjustment: &WeightAdjustment) {
+        println!(
+            "Adjusted weight of task id {:?} from {:?} to {:?} at system time {:?} (cpu share {:.2}, io ratio {:.2})",
+            adjustment.id, adjustment.old_weight, adjustment.new_weight, adjustment.time, adjustment.cpu_share, adjustment.io_ratio
+        );
+    }
+}
@@ -0,0 +1,183 @@
+// the runqueue as the kernel shows a cpu's in /proc/<pid>/sched,
+// /proc/<pid>/schedstat, /proc/schedstat and /proc/sched_debug, with a
+// tick taken to be a millisecond, as in the traces
+
+use super::fair::FairAlgorithm;
+use super::weight::weight_to_nice;
+use crate::proc::task::{SchedPolicy, Task, TaskStatus};
+use crate::stats::histogram::{Histogram, LatencyHistogram};
+use crate::trace::{SwitchState, TICK_US};
+
+// the version of /proc/schedstat the cpu lines follow
+pub const SCHEDSTAT_VERSION: u32 = 15;
+
+fn nsecs(ticks: u128) -> u128 {
+    ticks * TICK_US * 1000
+}
+
+// nanoseconds as milliseconds, as the kernel's SPLIT_NS
+fn msecs(ticks: u128) -> String {
+    let ns = nsecs(ticks);
+    format!("{}.{:06}", ns / 1_000_000, ns % 1_000_000)
+}
+
+fn field(name: &str, value: impl ToString) -> String {
+    format!("{:<45}:{:>21}\n", name, value.to_string())
+}
+
+fn time_field(name: &str, ticks: u128) -> String {
+    format!("{:<45}:{:>28}\n", name, msecs(ticks))
+}
+
+// the SCHED_* numbers of the sched_setscheduler() api
+fn policy_number(policy: SchedPolicy) -> u32 {
+    match policy {
+        SchedPolicy::Normal => 0,
+        SchedPolicy::Batch => 3,
+        SchedPolicy::Idle => 5
+    }
+}
+
+fn prio(task: &Task) -> i32 {
+    120 + weight_to_nice(task.weight())
+}
+
+// the letter ps and sched_debug show for the task
+fn state_char(rq: &FairAlgorithm, task: &Task) -> char {
+    if task.get_status() == TaskStatus::Running {
+        return 'R';
+    }
+    match rq.get_switch_state(task.get_id()) {
+        Some(SwitchState::Preempted) | None => 'R',
+        Some(SwitchState::Sleeping) => 'S',
+        Some(SwitchState::Blocked) => 'D',
+        Some(SwitchState::Stopped) => 'T',
+        Some(SwitchState::Dead) => 'Z'
+    }
+}
+
+fn of_task(latency: &LatencyHistogram, id: u16) -> Histogram {
+    latency.get_task(id).cloned().unwrap_or_default()
+}
+
+// the vruntime the task is, or would be, keyed by in the tree, in ticks
+// rather than as a time, as the time series samples it
+fn vruntime(rq: &FairAlgorithm, task: &Task) -> u64 {
+    if rq.get_curr().is_some_and(|curr| curr.get_id() == task.get_id()) {
+        return task.peek_vruntime(rq.get_time());
+    }
+    match rq.get_runnable().iter().find(|(_, other)| other.get_id() == task.get_id()) {
+        Some((key, _)) => *key,
+        None => task.get_vruntime()
+    }
+}
+
+// /proc/<pid>/sched, None if the runqueue does not hold the task
+pub fn task_sched(rq: &FairAlgorithm, id: u16) -> Option<String> {
+    let task = rq.get_task(id)?;
+    let stats = task.get_stats();
+    let latencies = rq.get_latencies();
+    let wait = of_task(&latencies.wait, id);
+
+    let mut out = format!("{} ({}, #threads: 1)\n", task.get_name(), id);
+    out.push_str(&"-".repeat(67));
+    out.push('\n');
+    out.push_str(&field("se.vruntime", vruntime(rq, task)));
+    out.push_str(&time_field("se.sum_exec_runtime", stats.cpu_time as u128));
+    out.push_str(&field("se.nr_migrations", 0));
+    out.push_str(&time_field("sum_block_runtime", stats.io_time));
+    out.push_str(&time_field("slice_max", of_task(&latencies.slice, id).max().unwrap_or(0) as u128));
+    out.push_str(&time_field("wait_max", wait.max().unwrap_or(0) as u128));
+    out.push_str(&time_field("wait_sum", stats.wait_time));
+    out.push_str(&field("wait_count", wait.count()));
+    out.push_str(&time_field("iowait_sum", stats.io_time));
+    out.push_str(&field("nr_wakeups", of_task(&latencies.wakeup, id).count()));
+    out.push_str(&field("nr_switches", stats.nr_voluntary_switches + stats.nr_involuntary_switches));
+    out.push_str(&field("nr_voluntary_switches", stats.nr_voluntary_switches));
+    out.push_str(&field("nr_involuntary_switches", stats.nr_involuntary_switches));
+    out.push_str(&field("se.load.weight", task.weight()));
+    out.push_str(&field("policy", policy_number(task.get_policy())));
+    out.push_str(&field("prio", prio(task)));
+    Some(out)
+}
+
+// /proc/<pid>/schedstat: time on the cpu and waiting for it in nanoseconds,
+// and the number of times it got the cpu
+pub fn task_schedstat(rq: &FairAlgorithm, id: u16) -> Option<String> {
+    let task = rq.get_task(id)?;
+    let stats = task.get_stats();
+    let timeslices = of_task(&rq.get_latencies().wait, id).count();
+    Some(format!("{} {} {}\n", nsecs(stats.cpu_time as u128), nsecs(stats.wait_time), timeslices))
+}
+
+// /proc/schedstat, the one cpu without scheduling domains
+pub fn schedstat(rq: &FairAlgorithm) -> String {
+    let stats = rq.get_stats();
+    let latencies = rq.get_latencies();
+    let yields: u64 = rq.get_tasks().into_iter()
+        .chain(rq.get_exited().iter())
+        .map(|task| task.get_stats().nr_yields)
+        .sum();
+    let cpu_time: u64 = SchedPolicy::ALL.iter().map(|policy| rq.get_policy_runtime(*policy)).sum();
+    let timeslices = latencies.wait.global.count();
+
+    format!(
+        "version {}\ntimestamp {}\ncpu0 {} 0 {} {} {} {} {} {} {}\n",
+        SCHEDSTAT_VERSION, rq.get_time(), yields, timeslices + stats.nr_goidle, stats.nr_goidle,
+        stats.nr_wakeups, stats.nr_wakeups, nsecs(cpu_time as u128), nsecs(latencies.wait.global.sum()), timeslices
+    )
+}
+
+// /proc/sched_debug, the cpu, its cfs runqueue and every task it holds
+pub fn sched_debug(rq: &FairAlgorithm) -> String {
+    let now = rq.get_time();
+    let stats = rq.get_stats();
+    let tasks = rq.get_tasks();
+    let runnable = rq.get_runnable();
+    let curr = rq.get_curr();
+    let nr_running = runnable.len() + curr.iter().count();
+    let load: u64 = curr.iter().copied().chain(runnable.iter().map(|(_, task)| *task))
+        .map(|task| task.weight() as u64)
+        .sum();
+    let short = |name: &str, value: String| format!("  .{:<30}: {}\n", name, value);
+
+    let mut out = String::from("Sched Debug Version: v0.11, rust-cfs\n");
+    out.push_str(&format!("{:<40}: {}\n", "ktime", msecs(now)));
+    out.push_str(&format!("{:<40}: {}\n", "jiffies", now));
+    out.push_str("\nsysctl_sched\n");
+    out.push_str(&format!("  .{:<40}: {}\n", "sysctl_sched_base_slice", msecs(rq.get_slice() as u128)));
+    out.push_str(&format!("  .{:<40}: {}\n", "sysctl_sched_wakeup_granularity", msecs(rq.get_wakeup_granularity() as u128)));
+    out.push_str(&format!("  .{:<40}: {}\n", "sysctl_sched_child_runs_first", rq.get_child_runs_first() as u8));
+    out.push_str(&format!("  .{:<40}: {}\n", "sysctl_sched_features", rq.get_features()));
+
+    out.push_str("\ncpu#0\n");
+    out.push_str(&short("nr_running", nr_running.to_string()));
+    out.push_str(&short("nr_switches", stats.nr_switches.to_string()));
+    let blocked = tasks.iter().filter(|task| state_char(rq, task) == 'D').count();
+    out.push_str(&short("nr_uninterruptible", blocked.to_string()));
+    out.push_str(&short("curr->pid", curr.map_or(0, |curr| curr.get_id()).to_string()));
+    out.push_str(&short("clock", msecs(now)));
+    out.push_str(&short("sched_goidle", stats.nr_goidle.to_string()));
+    out.push_str(&short("ttwu_count", stats.nr_wakeups.to_string()));
+
+    out.push_str("\ncfs_rq[0]:/\n");
+    out.push_str(&short("min_vruntime", rq.get_min_vruntime().to_string()));
+    out.push_str(&short("nr_running", nr_running.to_string()));
+    out.push_str(&short("load", load.to_string()));
+
+    out.push_str("\nrunnable tasks:\n");
+    out.push_str(" S            task   PID         tree-key  switches  prio     wait-time             sum-exec        sum-sleep\n");
+    out.push_str(&"-".repeat(109));
+    out.push('\n');
+    for task in tasks.iter() {
+        let stats = task.get_stats();
+        let on_cpu = curr.is_some_and(|curr| curr.get_id() == task.get_id());
+        out.push_str(&format!(
+            "{}{} {:>15} {:>5} {:>16} {:>9} {:>5} {:>13} {:>20} {:>16}\n",
+            if on_cpu { '>' } else { ' ' }, state_char(rq, task), task.get_name(), task.get_id(),
+            vruntime(rq, task), stats.nr_voluntary_switches + stats.nr_involuntary_switches, prio(task),
+            msecs(stats.wait_time), msecs(stats.cpu_time as u128), msecs(stats.io_time)
+        ));
+    }
+    out
+}
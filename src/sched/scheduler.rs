@@ -1,127 +1,344 @@
 use super::clock::Clock;
+use super::dynamic::DynamicWeight;
 use super::fair::FairAlgorithm;
+use super::observer::{LoggingObserver, Observer};
+use super::features::SchedFeatures;
+use super::procfs;
 
-use crate::proc::task::{Task, TaskChar};
+use crate::io::device::Device;
+use crate::proc::task::{SchedPolicy, Task, TaskChar};
 use crate::proc::queue::TaskQueue;
+use crate::proc::signal::Signal;
+use crate::stats::{DeviceReport, PolicyReport, Report, SyncReport};
+use crate::stats::series::TimeSeries;
+use crate::sync::SyncObject;
 
 use std::thread;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex, mpsc};
 
 pub struct Scheduler {
     clock: Arc<Mutex<Clock>>,
+    dynamic: Option<DynamicWeight>,
+    autogroup: bool,
+    autogroup_nice: HashMap<u32, i32>,
+    slice: u64,
+    wakeup_granularity: u64,
+    features: SchedFeatures,
+    seed: u64,
+    child_runs_first: bool,
+    inherit_vruntime: bool,
+    devices: Vec<Device>,
+    sync_objects: Vec<SyncObject>,
+    priority_inheritance: bool,
+    inversion_bound: u64,
+    signals: Vec<(u128, u16, Signal)>,
+    fairness_window: u64,
+    tracing: bool,
+    sampling: Option<u64>,
+    sched_debug: Vec<u128>,
+    logging: bool,
+    observers: Vec<Box<dyn Observer>>,
 }
 
 impl Scheduler {
     pub fn new() -> Self {
         let clock = Arc::new(Mutex::new(Clock::new()));
         
-        Self { clock }
+        Self {
+            clock,
+            dynamic: None,
+            autogroup: false,
+            autogroup_nice: HashMap::new(),
+            slice: 1,
+            wakeup_granularity: 0,
+            features: SchedFeatures::default(),
+            seed: 0,
+            child_runs_first: false,
+            inherit_vruntime: true,
+            devices: Vec::new(),
+            sync_objects: Vec::new(),
+            priority_inheritance: false,
+            inversion_bound: 0,
+            signals: Vec::new(),
+            fairness_window: 100,
+            tracing: false,
+            sampling: None,
+            sched_debug: Vec::new(),
+            logging: true,
+            observers: Vec::new()
+        }
+    }
+
+    // enables (or with None, disables) dynamic weight adjustment for the next run
+    pub fn set_dynamic_weight(&mut self, dynamic: Option<DynamicWeight>) {
+        self.dynamic = dynamic;
+    }
+
+    // divides cpu time between sessions before dividing it between their tasks
+    pub fn set_autogroup(&mut self, enabled: bool) {
+        self.autogroup = enabled;
+    }
+
+    pub fn set_autogroup_nice(&mut self, session: u32, nice: i32) {
+        self.autogroup_nice.insert(session, nice);
+    }
+
+    pub fn set_slice(&mut self, slice: u64) {
+        self.slice = slice;
+    }
+
+    pub fn set_wakeup_granularity(&mut self, granularity: u64) {
+        self.wakeup_granularity = granularity;
+    }
+
+    pub fn set_features(&mut self, features: SchedFeatures) {
+        self.features = features;
+    }
+
+    // seeds the burst distributions, the same seed always gives the same run
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn set_child_runs_first(&mut self, enabled: bool) {
+        self.child_runs_first = enabled;
+    }
+
+    pub fn set_inherit_vruntime(&mut self, enabled: bool) {
+        self.inherit_vruntime = enabled;
     }
 
-    pub fn run(&mut self, tasks: Vec<TaskChar>) {
-        let clk_1 = Arc::clone(&mut self.clock);
-        let clk_2 = Arc::clone(&mut self.clock);
+    // the shared devices tasks can block on, each run starts them afresh
+    pub fn set_devices(&mut self, devices: Vec<Device>) {
+        self.devices = devices;
+    }
+
+    // the locks and wait queues shared by the tasks
+    pub fn set_sync_objects(&mut self, objects: Vec<SyncObject>) {
+        self.sync_objects = objects;
+    }
+
+    pub fn set_priority_inheritance(&mut self, enabled: bool) {
+        self.priority_inheritance = enabled;
+    }
+
+    pub fn set_inversion_bound(&mut self, ticks: u64) {
+        self.inversion_bound = ticks;
+    }
+
+    // ticks per window that jain's fairness index is computed over
+    pub fn set_fairness_window(&mut self, ticks: u64) {
+        self.fairness_window = ticks;
+    }
+
+    // records the schedule of the next run in its report
+    pub fn set_tracing(&mut self, enabled: bool) {
+        self.tracing = enabled;
+    }
+
+    // samples the runqueue every given number of ticks of the next run, into
+    // the series of its report
+    pub fn set_sampling(&mut self, interval: Option<u64>) -> Result<(), String> {
+        // checked up front so that the run can't fail on it
+        interval.map(TimeSeries::new).transpose()?;
+        self.sampling = interval;
+        Ok(())
+    }
+
+    // delivers the signal to the task at the given system time, before that tick runs,
+    // signals to a task that is not around at that time are dropped
+    pub fn send_signal(&mut self, at: u128, id: u16, signal: Signal) {
+        self.signals.push((at, id, signal));
+    }
+
+    // keeps /proc/sched_debug and /proc/schedstat as they are at the end of
+    // the tick at the given system time, in the sched_debug of the report
+    pub fn show_sched_debug(&mut self, at: u128) {
+        self.sched_debug.push(at);
+    }
+
+    // a LoggingObserver is called back ahead of the added observers unless
+    // this turns it off
+    pub fn set_logging(&mut self, enabled: bool) {
+        self.logging = enabled;
+    }
+
+    // calls the observer back on every event of every run from here on, after
+    // the observers added before it; wrap it in an Arc<Mutex<_>> to keep a handle
+    pub fn add_observer(&mut self, observer: Box<dyn Observer>) {
+        self.observers.push(observer);
+    }
+
+    // runs the workload to completion and returns the per-task metrics of the run
+    pub fn run(&mut self, tasks: Vec<TaskChar>) -> Report {
+        *self.clock.lock().unwrap() = Clock::new();
+        let clk_1 = Arc::clone(&self.clock);
+        let clk_2 = Arc::clone(&self.clock);
 
         let mut threads = vec![];
 
-        let (clock_sender_1, spawner_clock_recv) = mpsc::channel();
-        let (clock_sender_2, clock_recv) = mpsc::channel();
-        let (born_sender, born_recv) = mpsc::channel();
+        let (clock_sender, clock_recv) = mpsc::channel();
+        let (ack_sender, ack_recv) = mpsc::channel();
 
+        // the clock only ticks once the running thread is done with the current
+        // tick, which keeps every run of the same workload identical
         let clocking = thread::spawn(move || {
             for _ in 0..u128::MAX {
-                let mut lock = clk_1.lock().unwrap();
-                match clock_sender_1.send(lock.time()) {
+                let time = clk_1.lock().unwrap().time();
+                match clock_sender.send(time) {
                     Ok(_) => {},
-                    _ => {}
+                    _ => break
                 };
-                match clock_sender_2.send(lock.time()) {
+                match ack_recv.recv() {
                     Ok(_) => {},
                     _ => break
                 };
-                lock.tick();
+                clk_1.lock().unwrap().tick();
             }
-            drop(clock_sender_1);
-            drop(clock_sender_2);
+            drop(clock_sender);
         });
         threads.push(clocking);
 
-        let my_tasks = Arc::new(Mutex::new(tasks));
-        let tasks_cp_1 = Arc::clone(&my_tasks);
-        let tasks_cp_2 = Arc::clone(&my_tasks);
-
-        let spawning = thread::spawn(move || {
-            let mut time = spawner_clock_recv.recv().unwrap();
-            
-            let born_tasks = tasks_cp_1.lock().unwrap().clone();
-
-            for raw in born_tasks {
-                time = match spawner_clock_recv.try_recv() {
-                    Ok(tick) => tick,
-                    _ => time
-                };
-
-                let task = Task::new(
-                    raw.get_id(),
-                    raw.get_cpu_time(),
-                    raw.get_cpu_burst_length(),
-                    raw.get_io_burst_length(),
-                    time,
-                    raw.get_weight()
-                );
-
-                match born_sender.send(task) {
-                    Ok(_) => {},
-                    _ => panic!("Running thread dropped unexpectedly")
-                };
-            }
+        let mut born_queue = TaskQueue::new();
+        for raw in tasks.iter() {
+            let mut task = raw.spawn();
+            task.seed(self.seed);
+            born_queue.add(task);
+        }
 
-            tasks_cp_1.lock().unwrap().clear();
-            drop(spawner_clock_recv);
-            drop(born_sender);
-        });
-        threads.push(spawning);
+        let mut policy_tasks: HashMap<SchedPolicy, usize> = HashMap::new();
+        for raw in tasks.iter() {
+            *policy_tasks.entry(raw.get_policy()).or_insert(0) += 1;
+        }
 
+        let dynamic = self.dynamic.clone();
+        let autogroup = self.autogroup;
+        let autogroup_nice = self.autogroup_nice.clone();
+        let slice = self.slice;
+        let wakeup_granularity = self.wakeup_granularity;
+        let features = self.features;
+        let child_runs_first = self.child_runs_first;
+        let inherit_vruntime = self.inherit_vruntime;
+        let sync_objects = self.sync_objects.clone();
+        let priority_inheritance = self.priority_inheritance;
+        let inversion_bound = self.inversion_bound;
+        let fairness_window = self.fairness_window;
+        let tracing = self.tracing;
+        let sampling = self.sampling;
+        let sched_debug = self.sched_debug.clone();
+        let logging = self.logging;
+        let observers = std::mem::take(&mut self.observers);
+        let mut signals = self.signals.clone();
+        // in order of delivery, signals due at the same time in the order they were sent
+        signals.sort_by_key(|(at, _, _)| *at);
+        let mut signals: VecDeque<(u128, u16, Signal)> = signals.into();
+        let mut devices = self.devices.clone();
+        for (idx, device) in devices.iter_mut().enumerate() {
+            // above the seeds of the tasks
+            device.seed(self.seed.wrapping_add((idx as u64 + 1) << 48));
+        }
+        // forked children get pids above every task in the workload
+        let next_pid = tasks.iter().map(|raw| raw.get_id()).max().unwrap_or(0) + 1;
         let running = thread::spawn(move || {
-            let mut task_queue = TaskQueue::new();
             let mut rq = FairAlgorithm::new(&mut clk_2.lock().unwrap());
+            rq.set_dynamic_weight(dynamic);
+            rq.set_autogroup(autogroup);
+            for (session, nice) in autogroup_nice {
+                rq.set_autogroup_nice(session, nice);
+            }
+            rq.set_slice(slice);
+            rq.set_wakeup_granularity(wakeup_granularity);
+            rq.set_features(features);
+            rq.set_child_runs_first(child_runs_first);
+            rq.set_inherit_vruntime(inherit_vruntime);
+            rq.set_next_pid(next_pid);
+            for device in devices {
+                rq.add_device(device);
+            }
+            for object in sync_objects {
+                rq.add_sync_object(object);
+            }
+            rq.set_priority_inheritance(priority_inheritance);
+            rq.set_inversion_bound(inversion_bound);
+            rq.set_fairness_window(fairness_window);
+            rq.set_tracing(tracing);
+            rq.set_sampling(sampling).unwrap();
+            if logging {
+                rq.add_observer(Box::new(LoggingObserver));
+            }
+            for observer in observers {
+                rq.add_observer(observer);
+            }
 
-            loop {
-                let time = match clock_recv.recv() {
-                    Ok(tick) => tick,
-                    _ => break
-                };
-
-                match born_recv.try_recv() {
-                    Ok(task) => task_queue.add(task),
-                    _ => {}
-                };
-
-                let born_tasks = task_queue.pop();
-                rq.push(born_tasks);
-
-                if !rq.is_empty() {
-                    let mut curr = rq.pop();
-                    println!("Running task id {:?} at system time {:?}", curr.get_id(), time);
-                    curr.cpu_cycle();
-                    rq.insert(*curr);
+            let mut snapshots = Vec::new();
+            while let Ok(time) = clock_recv.recv() {
+                rq.push(born_queue.release(time));
+                while signals.front().is_some_and(|(at, _, _)| *at <= time) {
+                    let (_, id, signal) = signals.pop_front().unwrap();
+                    rq.signal(id, signal);
                 }
+
+                rq.run();
                 rq.idle();
+                if sched_debug.contains(&time) {
+                    snapshots.push((time, format!("{}{}", procfs::sched_debug(&rq), procfs::schedstat(&rq))));
+                }
 
-                if rq.is_finished() && tasks_cp_2.lock().unwrap().is_empty() {
+                if rq.is_finished() && born_queue.is_empty() && signals.is_empty() {
+                    break;
+                }
+                if ack_sender.send(()).is_err() {
                     break;
                 }
             }
 
+            // zombies exited even if nothing reaped them
+            let exited: Vec<Task> = rq.get_exited().iter().chain(rq.get_zombies()).cloned().collect();
+            let mut report = Report::new(&exited, rq.get_fluid());
+            let unfinished: Vec<&Task> = rq.get_stopped().iter().chain(rq.get_deadlocked()).collect();
+            report.set_unfinished(&unfinished);
+            report.trace = rq.get_trace().cloned();
+            report.series = rq.get_series().cloned();
+            report.latencies = rq.get_latencies().clone();
+            if let Some(dynamic) = rq.get_dynamic_weight() {
+                report.weight_adjustments = dynamic.get_log().to_vec();
+            }
+            report.policies = SchedPolicy::ALL.iter().map(|policy| PolicyReport {
+                policy: *policy,
+                tasks: *policy_tasks.get(policy).unwrap_or(&0),
+                cpu_time: rq.get_policy_runtime(*policy)
+            }).collect();
+            report.features = rq.get_features();
+            report.stats = rq.get_stats();
+            report.devices = rq.get_devices().iter().map(DeviceReport::of).collect();
+            report.sync_objects = rq.get_sync_objects().iter().map(SyncReport::of).collect();
+            report.inversions = rq.get_inversions().to_vec();
+            report.inversion_bound = inversion_bound;
+            report.sched_debug = snapshots;
+
             drop(clock_recv);
-            drop(born_recv);
+            drop(ack_sender);
+            // the logging observer is added afresh every run
+            let mut observers = rq.take_observers();
+            if logging {
+                observers.remove(0);
+            }
+            (report, observers)
         });
-        threads.push(running);
 
+        let (report, observers) = running.join().unwrap();
+        self.observers = observers;
         for thread in threads {
             thread.join().unwrap();
         }
 
-        println!("Scheduler job complete");
+        report
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -0,0 +1,41 @@
+// nice to weight conversion, taken from the linux kernel's sched_prio_to_weight table
+
+pub const NICE_0_LOAD: u64 = 1024;
+
+// weight of sched_idle tasks
+pub const WEIGHT_IDLEPRIO: u32 = 3;
+
+pub const MIN_NICE: i32 = -20;
+pub const MAX_NICE: i32 = 19;
+
+const PRIO_TO_WEIGHT: [u32; 40] = [
+    /* -20 */     88761,     71755,     56483,     46273,     36291,
+    /* -15 */     29154,     23254,     18705,     14949,     11916,
+    /* -10 */      9548,      7620,      6100,      4904,      3906,
+    /*  -5 */      3121,      2501,      1991,      1586,      1277,
+    /*   0 */      1024,       820,       655,       526,       423,
+    /*   5 */       335,       272,       215,       172,       137,
+    /*  10 */       110,        87,        70,        56,        45,
+    /*  15 */        36,        29,        23,        18,        15,
+];
+
+pub fn nice_to_weight(nice: i32) -> u32 {
+    if !(MIN_NICE..=MAX_NICE).contains(&nice) {
+        panic!("Nice value {:?} is outside of [{:?}, {:?}]", nice, MIN_NICE, MAX_NICE);
+    }
+    PRIO_TO_WEIGHT[(nice - MIN_NICE) as usize]
+}
+
+// the nice value whose weight is closest to the given one
+pub fn weight_to_nice(weight: u32) -> i32 {
+    let idx = (0..PRIO_TO_WEIGHT.len())
+        .min_by_key(|idx| (PRIO_TO_WEIGHT[*idx] as i64 - weight as i64).abs())
+        .unwrap();
+    idx as i32 + MIN_NICE
+}
+
+// virtual time charged for one tick of cpu at the given weight
+#[inline]
+pub fn tick_delta(weight: u32) -> u64 {
+    NICE_0_LOAD * NICE_0_LOAD / (weight.max(1) as u64)
+}
@@ -0,0 +1,174 @@
+// an ideal weighted generalised processor sharing (gps) schedule, which
+// splits every tick between the runnable tasks in proportion to their
+// weight, and how far the real schedule strays from it
+
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TaskFairness {
+    pub id: u16,
+    // cpu time the task would have had under gps
+    pub ideal: f64,
+    pub actual: u64,
+    // ticks the task was runnable, running included
+    pub runnable: u64,
+    // the largest amount the task was behind (positive) and ahead (negative) of gps
+    pub max_lag: f64,
+    pub min_lag: f64,
+}
+
+impl TaskFairness {
+    // ideal minus actual service, positive while the task is owed cpu time
+    pub fn lag(&self) -> f64 {
+        self.ideal - self.actual as f64
+    }
+
+    // the mean fraction of the cpu its weight entitled the task to while runnable
+    pub fn weight_share(&self) -> f64 {
+        if self.runnable == 0 {
+            return 0.0;
+        }
+        self.ideal / self.runnable as f64
+    }
+
+    pub fn received_share(&self) -> f64 {
+        if self.runnable == 0 {
+            return 0.0;
+        }
+        self.actual as f64 / self.runnable as f64
+    }
+
+    pub fn share_error(&self) -> f64 {
+        self.received_share() - self.weight_share()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FairnessWindow {
+    pub start: u128,
+    pub end: u128,
+    pub nr_tasks: usize,
+    pub jain: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Window {
+    index: u128,
+    // ideal and actual service of every task runnable in the window
+    service: BTreeMap<u16, (f64, u64)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FluidModel {
+    window: u64,
+    tasks: BTreeMap<u16, TaskFairness>,
+    current: Option<Window>,
+    windows: Vec<FairnessWindow>,
+}
+
+impl FluidModel {
+    pub fn new(window: u64) -> Self {
+        if window == 0 {
+            panic!("Fairness windows need to be at least one tick long");
+        }
+
+        Self {
+            window,
+            tasks: BTreeMap::new(),
+            current: None,
+            windows: Vec::new()
+        }
+    }
+
+    #[inline]
+    pub fn get_window(&self) -> u64 { self.window }
+
+    // accounts one tick, split between the runnable tasks by weight, of which ran got the cpu
+    pub fn tick(&mut self, now: u128, runnable: &[(u16, u32)], ran: Option<u16>) {
+        let total: u64 = runnable.iter().map(|(_, weight)| *weight as u64).sum();
+        if total == 0 {
+            return;
+        }
+
+        let index = now / self.window as u128;
+        if self.current.as_ref().is_some_and(|window| window.index != index) {
+            let window = self.current.take().unwrap();
+            self.windows.extend(self.close(&window));
+        }
+        let window = self.current.get_or_insert_with(|| Window { index, service: BTreeMap::new() });
+
+        for (id, weight) in runnable {
+            let share = *weight as f64 / total as f64;
+            let got = if ran == Some(*id) { 1 } else { 0 };
+
+            let task = self.tasks.entry(*id).or_insert(TaskFairness { id: *id, ..TaskFairness::default() });
+            task.ideal += share;
+            task.actual += got;
+            task.runnable += 1;
+            task.max_lag = task.max_lag.max(task.lag());
+            task.min_lag = task.min_lag.min(task.lag());
+
+            let service = window.service.entry(*id).or_insert((0.0, 0));
+            service.0 += share;
+            service.1 += got;
+        }
+    }
+
+    fn close(&self, window: &Window) -> Option<FairnessWindow> {
+        let start = window.index * self.window as u128;
+        let shares: Vec<f64> = window.service.values()
+            .filter(|(ideal, _)| *ideal > 0.0)
+            .map(|(ideal, actual)| *actual as f64 / ideal)
+            .collect();
+        let jain = jain_index(&shares)?;
+
+        Some(FairnessWindow { start, end: start + self.window as u128, nr_tasks: shares.len(), jain })
+    }
+
+    // tasks in order of id
+    pub fn get_tasks(&self) -> Vec<TaskFairness> {
+        self.tasks.values().copied().collect()
+    }
+
+    pub fn get_task(&self, id: u16) -> Option<TaskFairness> {
+        self.tasks.get(&id).copied()
+    }
+
+    // every window with a runnable task so far, the one still open included
+    pub fn get_windows(&self) -> Vec<FairnessWindow> {
+        let mut windows = self.windows.clone();
+        if let Some(window) = self.current.as_ref() {
+            windows.extend(self.close(window));
+        }
+        windows
+    }
+
+    pub fn max_lag(&self) -> f64 {
+        self.tasks.values().map(|task| task.max_lag).fold(0.0, f64::max)
+    }
+
+    pub fn min_lag(&self) -> f64 {
+        self.tasks.values().map(|task| task.min_lag).fold(0.0, f64::min)
+    }
+
+    // the largest difference between weight share and received share of any task
+    pub fn max_share_error(&self) -> f64 {
+        self.tasks.values().map(|task| task.share_error().abs()).fold(0.0, f64::max)
+    }
+}
+
+impl Default for FluidModel {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+// 1 if every task got the same, down to 1/n if a single one got everything
+pub fn jain_index(samples: &[f64]) -> Option<f64> {
+    let sum: f64 = samples.iter().sum();
+    let squares: f64 = samples.iter().map(|x| x * x).sum();
+    if squares == 0.0 {
+        return None;
+    }
+    Some(sum * sum / (samples.len() as f64 * squares))
+}
@@ -0,0 +1,216 @@
+// high dynamic range histograms of latencies in ticks, log-linear buckets
+// that keep a fixed relative precision from one tick up to u64::MAX, so the
+// tail is as well resolved as the mean, and that merge across runs
+
+use crate::proc::task::SchedPolicy;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// 2^5 sub-buckets, values are kept to within 1/16 of themselves
+pub const DEFAULT_PRECISION: u32 = 5;
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Histogram {
+    // bits of each value that are kept
+    precision: u32,
+    counts: Vec<u64>,
+    count: u64,
+    min: u64,
+    max: u64,
+    sum: u128,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new(DEFAULT_PRECISION)
+    }
+}
+
+impl Histogram {
+    pub fn new(precision: u32) -> Self {
+        if !(1..=16).contains(&precision) {
+            panic!("A histogram keeps 1 to 16 bits of precision, not {}", precision);
+        }
+        Self { precision, counts: Vec::new(), count: 0, min: u64::MAX, max: 0, sum: 0 }
+    }
+
+    pub fn get_precision(&self) -> u32 { self.precision }
+
+    // the values below 2^precision each have a bucket of their own, every
+    // power of two above that is split into 2^(precision - 1) buckets
+    fn index(&self, value: u64) -> usize {
+        let sub_buckets = 1u64 << self.precision;
+        if value < sub_buckets {
+            return value as usize;
+        }
+        let shift = 64 - value.leading_zeros() - self.precision;
+        let half = sub_buckets / 2;
+        (sub_buckets + (shift as u64 - 1) * half + ((value >> shift) - half)) as usize
+    }
+
+    // the lowest and highest value that land in the bucket
+    fn bounds(&self, idx: usize) -> (u64, u64) {
+        let sub_buckets = 1usize << self.precision;
+        if idx < sub_buckets {
+            return (idx as u64, idx as u64);
+        }
+        let half = sub_buckets / 2;
+        let shift = (idx - sub_buckets) / half + 1;
+        let low = (((idx - sub_buckets) % half + half) as u64) << shift;
+        (low, low + ((1u64 << shift) - 1))
+    }
+
+    pub fn record(&mut self, value: u64) {
+        self.record_n(value, 1);
+    }
+
+    pub fn record_n(&mut self, value: u64, n: u64) {
+        if n == 0 {
+            return;
+        }
+        let idx = self.index(value);
+        if self.counts.len() <= idx {
+            self.counts.resize(idx + 1, 0);
+        }
+        self.counts[idx] += n;
+        self.count += n;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value as u128 * n as u128;
+    }
+
+    // adds the values of the other histogram, which has to keep the same precision
+    pub fn merge(&mut self, other: &Histogram) {
+        if other.precision != self.precision {
+            panic!("Cannot merge a histogram of {} bits of precision into one of {}", other.precision, self.precision);
+        }
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (idx, count) in other.counts.iter().enumerate() {
+            self.counts[idx] += count;
+        }
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum += other.sum;
+    }
+
+    pub fn count(&self) -> u64 { self.count }
+
+    pub fn is_empty(&self) -> bool { self.count == 0 }
+
+    // exact, unlike the quantiles
+    pub fn min(&self) -> Option<u64> {
+        if self.is_empty() { None } else { Some(self.min) }
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        if self.is_empty() { None } else { Some(self.max) }
+    }
+
+    // exact as well
+    pub fn sum(&self) -> u128 { self.sum }
+
+    pub fn mean(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        self.sum as f64 / self.count as f64
+    }
+
+    // the highest value of the bucket the q-th quantile falls in, by nearest
+    // rank, so at most the precision above the real one and never past the max
+    pub fn value_at_quantile(&self, q: f64) -> Option<u64> {
+        if self.is_empty() {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (idx, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(self.bounds(idx).1.clamp(self.min, self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    // the non-empty buckets, as (lowest value, highest value, count)
+    pub fn buckets(&self) -> Vec<(u64, u64, u64)> {
+        self.counts.iter().enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(idx, count)| {
+                let (low, high) = self.bounds(idx);
+                (low, high, *count)
+            })
+            .collect()
+    }
+}
+
+// one kind of latency, over every task, per policy and per task
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LatencyHistogram {
+    pub global: Histogram,
+    // by the name of the policy, such as SCHED_NORMAL
+    pub policies: BTreeMap<String, Histogram>,
+    pub tasks: BTreeMap<u16, Histogram>,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, id: u16, policy: SchedPolicy, value: u64) {
+        self.global.record(value);
+        self.policies.entry(policy.to_string()).or_default().record(value);
+        self.tasks.entry(id).or_default().record(value);
+    }
+
+    pub fn get_policy(&self, policy: SchedPolicy) -> Option<&Histogram> {
+        self.policies.get(&policy.to_string())
+    }
+
+    pub fn get_task(&self, id: u16) -> Option<&Histogram> {
+        self.tasks.get(&id)
+    }
+
+    // task ids are taken to name the same tasks in both
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        self.global.merge(&other.global);
+        for (policy, histogram) in other.policies.iter() {
+            self.policies.entry(policy.clone()).or_default().merge(histogram);
+        }
+        for (id, histogram) in other.tasks.iter() {
+            self.tasks.entry(*id).or_default().merge(histogram);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Latencies {
+    // from a wakeup to getting the cpu
+    pub wakeup: LatencyHistogram,
+    // from entering the runqueue, woken or preempted, to getting the cpu
+    pub wait: LatencyHistogram,
+    // ticks on the cpu each time a task got it
+    pub slice: LatencyHistogram,
+}
+
+impl Latencies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn merge(&mut self, other: &Latencies) {
+        self.wakeup.merge(&other.wakeup);
+        self.wait.merge(&other.wait);
+        self.slice.merge(&other.slice);
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(src: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(src)
+    }
+}
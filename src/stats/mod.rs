@@ -0,0 +1,442 @@
+// per-task scheduling metrics and aggregates over them, collected at the end of a run
+
+pub mod fairness;
+pub mod histogram;
+pub mod series;
+
+use crate::io::device::{Device, DeviceStats, IoPolicy};
+use crate::proc::periodic::JobRecord;
+use crate::proc::task::{SchedPolicy, Task, TaskStatus};
+use crate::sched::dynamic::WeightAdjustment;
+use crate::sched::fair::RunqueueStats;
+use crate::sched::features::SchedFeatures;
+use crate::sync::{InversionWindow, SyncKind, SyncObject, SyncStats};
+use crate::trace::Trace;
+
+use fairness::FluidModel;
+use histogram::Latencies;
+use series::TimeSeries;
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskReport {
+    pub id: u16,
+    pub name: String,
+    pub parent: Option<u16>,
+    pub arrival: u128,
+    // None if the task never got the cpu
+    pub first_run: Option<u128>,
+    pub completion: u128,
+    pub turnaround: u128,
+    // ticks spent runnable but not running
+    pub wait_time: u128,
+    // ticks from arrival to first run
+    pub response: u128,
+    pub context_switches: u64,
+    pub voluntary_switches: u64,
+    pub involuntary_switches: u64,
+    pub yields: u64,
+    pub io_time: u128,
+    pub cpu_time: u64,
+    // turnaround over cpu time, so time on io, asleep or blocked on a lock
+    // raises it as much as waiting in the runqueue does
+    pub slowdown: f64,
+    pub killed: bool,
+    pub stops: u64,
+    // dead, or a zombie its parent never got to reap
+    pub state: TaskStatus,
+    // the jobs of a periodic task, in order of release
+    pub jobs: Vec<JobRecord>,
+}
+
+impl TaskReport {
+    pub fn of(task: &Task) -> Self {
+        let stats = task.get_stats();
+        let arrival = task.get_arrival();
+        let completion = match stats.completion {
+            Some(completion) => completion,
+            None => panic!("Task id {} has not exited", task.get_id())
+        };
+        let turnaround = completion.saturating_sub(arrival);
+        let response = stats.first_run.unwrap_or(completion).saturating_sub(arrival);
+        let slowdown = if stats.cpu_time == 0 {
+            0.0
+        } else {
+            turnaround as f64 / stats.cpu_time as f64
+        };
+
+        Self {
+            id: task.get_id(),
+            name: task.get_name().to_string(),
+            parent: task.get_parent(),
+            arrival,
+            first_run: stats.first_run,
+            completion,
+            turnaround,
+            wait_time: stats.wait_time,
+            response,
+            context_switches: stats.nr_voluntary_switches + stats.nr_involuntary_switches,
+            voluntary_switches: stats.nr_voluntary_switches,
+            involuntary_switches: stats.nr_involuntary_switches,
+            yields: stats.nr_yields,
+            io_time: stats.io_time,
+            cpu_time: stats.cpu_time,
+            slowdown,
+            killed: stats.killed,
+            stops: stats.nr_stops,
+            state: task.get_status(),
+            jobs: task.get_jobs().to_vec()
+        }
+    }
+}
+
+// why a task was still around when the run ended
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Unfinished {
+    // stopped and never continued
+    Stopped,
+    // blocked for good on the named lock or wait queue
+    Blocked(String),
+    // waiting for children that can never exit
+    WaitingForChildren,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnfinishedTask {
+    pub id: u16,
+    pub name: String,
+    pub arrival: u128,
+    pub first_run: Option<u128>,
+    pub wait_time: u128,
+    pub io_time: u128,
+    pub cpu_time: u64,
+    pub state: TaskStatus,
+    pub reason: Unfinished,
+}
+
+impl UnfinishedTask {
+    pub fn of(task: &Task) -> Self {
+        let stats = task.get_stats();
+        let reason = match (task.get_status(), task.get_sync()) {
+            (TaskStatus::Stopped, _) => Unfinished::Stopped,
+            (_, Some((_, name, _))) => Unfinished::Blocked(name.to_string()),
+            (_, None) => Unfinished::WaitingForChildren
+        };
+
+        Self {
+            id: task.get_id(),
+            name: task.get_name().to_string(),
+            arrival: task.get_arrival(),
+            first_run: stats.first_run,
+            wait_time: stats.wait_time,
+            io_time: stats.io_time,
+            cpu_time: stats.cpu_time,
+            state: task.get_status(),
+            reason
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PolicyReport {
+    pub policy: SchedPolicy,
+    // tasks the workload started out with under the policy
+    pub tasks: usize,
+    pub cpu_time: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeviceReport {
+    pub name: String,
+    pub policy: IoPolicy,
+    pub stats: DeviceStats,
+}
+
+impl DeviceReport {
+    pub fn of(device: &Device) -> Self {
+        Self { name: device.get_name().to_string(), policy: device.get_policy(), stats: device.get_stats() }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyncReport {
+    pub name: String,
+    pub kind: SyncKind,
+    pub stats: SyncStats,
+}
+
+impl SyncReport {
+    pub fn of(object: &SyncObject) -> Self {
+        Self { name: object.get_name().to_string(), kind: object.get_kind(), stats: object.get_stats() }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    // nearest-rank percentiles, all zero for no samples
+    pub fn of(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let rank = |p: f64| {
+            let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+            sorted[rank.max(1) - 1]
+        };
+
+        Self {
+            count: sorted.len(),
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50: rank(50.0),
+            p95: rank(95.0),
+            p99: rank(99.0),
+            max: sorted[sorted.len() - 1]
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    // in order of task id
+    pub tasks: Vec<TaskReport>,
+    pub turnaround: Summary,
+    pub wait_time: Summary,
+    pub response: Summary,
+    pub slowdown: Summary,
+    pub fairness: FluidModel,
+    // the schedule, if the run was traced
+    pub trace: Option<Trace>,
+    // the runqueue over time, if it was sampled
+    pub series: Option<TimeSeries>,
+    // wakeup latency, runqueue wait and slice length histograms
+    pub latencies: Latencies,
+    // every change dynamic weights made, in order
+    pub weight_adjustments: Vec<WeightAdjustment>,
+    // tasks that never exited, stopped or deadlocked, in order of task id
+    pub unfinished: Vec<UnfinishedTask>,
+    // cpu time by policy, in the order of SchedPolicy::ALL
+    pub policies: Vec<PolicyReport>,
+    pub features: SchedFeatures,
+    pub stats: RunqueueStats,
+    pub devices: Vec<DeviceReport>,
+    pub sync_objects: Vec<SyncReport>,
+    pub inversions: Vec<InversionWindow>,
+    // the interference past which an inversion counts as unbounded
+    pub inversion_bound: u64,
+    // /proc/sched_debug and /proc/schedstat at the end of the asked for ticks
+    pub sched_debug: Vec<(u128, String)>,
+}
+
+impl Report {
+    pub fn new(exited: &[Task], fairness: &FluidModel) -> Self {
+        let mut tasks: Vec<TaskReport> = exited.iter().map(TaskReport::of).collect();
+        tasks.sort_by_key(|task| task.id);
+        let summary = |field: fn(&TaskReport) -> f64| {
+            Summary::of(&tasks.iter().map(field).collect::<Vec<f64>>())
+        };
+
+        Self {
+            turnaround: summary(|task| task.turnaround as f64),
+            wait_time: summary(|task| task.wait_time as f64),
+            response: summary(|task| task.response as f64),
+            slowdown: summary(|task| task.slowdown),
+            fairness: fairness.clone(),
+            trace: None,
+            series: None,
+            latencies: Latencies::new(),
+            weight_adjustments: Vec::new(),
+            unfinished: Vec::new(),
+            policies: Vec::new(),
+            features: SchedFeatures::default(),
+            stats: RunqueueStats::default(),
+            devices: Vec::new(),
+            sync_objects: Vec::new(),
+            inversions: Vec::new(),
+            inversion_bound: 0,
+            sched_debug: Vec::new(),
+            tasks
+        }
+    }
+
+    pub fn get_task(&self, id: u16) -> Option<&TaskReport> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    pub fn set_unfinished(&mut self, tasks: &[&Task]) {
+        self.unfinished = tasks.iter().map(|task| UnfinishedTask::of(task)).collect();
+        self.unfinished.sort_by_key(|task| task.id);
+    }
+
+    // true if every task exited
+    pub fn is_complete(&self) -> bool {
+        self.unfinished.is_empty()
+    }
+
+    pub fn is_deadlocked(&self) -> bool {
+        self.unfinished.iter().any(|task| task.reason != Unfinished::Stopped)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for task in self.tasks.iter() {
+            let parent = match task.parent {
+                Some(parent) => format!(" (child of {:?})", parent),
+                None => String::new()
+            };
+            let mut signalled = String::new();
+            if task.stops > 0 {
+                signalled += &format!(", stopped {:?} times", task.stops);
+            }
+            if task.killed {
+                signalled += ", killed";
+            }
+            writeln!(
+                f, "Task id {:?}{}: {:?} yields, {:?} voluntary and {:?} involuntary switches{}",
+                task.id, parent, task.yields, task.voluntary_switches, task.involuntary_switches, signalled
+            )?;
+
+            let jobs = &task.jobs;
+            for job in jobs {
+                writeln!(
+                    f, "Task id {:?} job {:?}: due at {:?}, released at {:?}, started at {:?}, finished at {:?}, response {:?}{}",
+                    task.id, job.job, job.nominal, job.release, job.start, job.finish, job.response(),
+                    if job.overrun { ", overrun" } else { "" }
+                )?;
+            }
+            if !jobs.is_empty() {
+                let min = jobs.iter().map(|job| job.response()).min().unwrap();
+                let max = jobs.iter().map(|job| job.response()).max().unwrap();
+                let mean = jobs.iter().map(|job| job.response()).sum::<u128>() as f64 / jobs.len() as f64;
+                let max_delay = jobs.iter().map(|job| job.start_delay()).max().unwrap();
+                writeln!(
+                    f, "Task id {:?}: {:?} jobs, response {:?} min, {:.2} mean, {:?} max, jitter {:?}, start delay {:?} max, {:?} overruns",
+                    task.id, jobs.len(), min, mean, max, max - min, max_delay,
+                    jobs.iter().filter(|job| job.overrun).count()
+                )?;
+            }
+        }
+
+        for task in self.tasks.iter() {
+            let first_run = match task.first_run {
+                Some(first_run) => format!("{:?}", first_run),
+                None => String::from("never")
+            };
+            writeln!(
+                f, "Task id {:?}: arrival {:?}, first run {}, completion {:?}, turnaround {:?}, wait {:?}, response {:?}, {:?} switches, i/o {:?}, slowdown {:.2}",
+                task.id, task.arrival, first_run, task.completion, task.turnaround, task.wait_time,
+                task.response, task.context_switches, task.io_time, task.slowdown
+            )?;
+        }
+        let summaries = [("Turnaround", &self.turnaround), ("Wait", &self.wait_time), ("Response", &self.response), ("Slowdown", &self.slowdown)];
+        for (what, summary) in summaries.iter() {
+            writeln!(
+                f, "{}: {:.2} mean, {:.2} p50, {:.2} p95, {:.2} p99, {:.2} max",
+                what, summary.mean, summary.p50, summary.p95, summary.p99, summary.max
+            )?;
+        }
+
+        let fairness = &self.fairness;
+        for task in fairness.get_tasks() {
+            writeln!(
+                f, "Task id {:?}: weight share {:.3}, received share {:.3}, share error {:+.3}, lag {:.2} max, {:.2} min",
+                task.id, task.weight_share(), task.received_share(), task.share_error(), task.max_lag, task.min_lag
+            )?;
+        }
+        let windows = fairness.get_windows();
+        if !windows.is_empty() {
+            let mean = windows.iter().map(|window| window.jain).sum::<f64>() / windows.len() as f64;
+            let min = windows.iter().map(|window| window.jain).fold(1.0, f64::min);
+            writeln!(
+                f, "Fairness: lag {:.2} max, {:.2} min, share error {:.3} max, Jain's index {:.3} mean, {:.3} min over {:?} windows of {:?} ticks",
+                fairness.max_lag(), fairness.min_lag(), fairness.max_share_error(), mean, min, windows.len(), fairness.get_window()
+            )?;
+        }
+
+        // tasks can change policy on the way, so a policy may have cpu time but no tasks
+        for policy in self.policies.iter().filter(|policy| policy.tasks > 0 || policy.cpu_time > 0) {
+            writeln!(f, "{} tasks: {:?}, cpu time: {:?}", policy.policy, policy.tasks, policy.cpu_time)?;
+        }
+
+        let stats = &self.stats;
+        writeln!(f, "Features: {}", self.features)?;
+        writeln!(
+            f, "Context switches: {:?}, buddy picks: {:?} next, {:?} last, {:?} skip",
+            stats.nr_switches, stats.nr_next_buddy_picks, stats.nr_last_buddy_picks, stats.nr_skip_buddy_picks
+        )?;
+        writeln!(
+            f, "Wakeups: {:?}, wake-to-run latency: {:.2} mean, {:?} max",
+            stats.nr_wakeups, stats.mean_wakeup_latency(), stats.wakeup_latency_max
+        )?;
+        let latencies = &self.latencies;
+        let kinds = [("Wakeup latency", &latencies.wakeup), ("Runqueue wait", &latencies.wait), ("Time slice", &latencies.slice)];
+        // per policy only when there is more than one to tell apart
+        let nr_policies = latencies.slice.policies.len();
+        for (what, latency) in kinds.iter() {
+            let histograms = std::iter::once((String::new(), &latency.global))
+                .chain(latency.policies.iter().filter(|_| nr_policies > 1).map(|(policy, histogram)| (format!(" ({})", policy), histogram)));
+            for (policy, histogram) in histograms.filter(|(_, histogram)| !histogram.is_empty()) {
+                let quantile = |q: f64| histogram.value_at_quantile(q).unwrap();
+                writeln!(
+                    f, "{}{}: {:?} samples, {:.2} mean, {:?} p50, {:?} p90, {:?} p99, {:?} p99.9, {:?} max",
+                    what, policy, histogram.count(), histogram.mean(), quantile(0.5), quantile(0.9),
+                    quantile(0.99), quantile(0.999), histogram.max().unwrap()
+                )?;
+            }
+        }
+        writeln!(f, "Forks: {:?}", stats.nr_forks)?;
+        for device in self.devices.iter() {
+            let stats = &device.stats;
+            writeln!(
+                f, "Device {} ({}): {:?} requests ({:?} reads, {:?} writes), busy {:?} ticks, wait {:.2} mean, {:?} max, max queue {:?}, {:?} expired",
+                device.name, device.policy, stats.nr_requests, stats.nr_reads, stats.nr_writes,
+                stats.busy_ticks, stats.mean_wait(), stats.wait_max, stats.max_queue, stats.nr_expired
+            )?;
+        }
+        for object in self.sync_objects.iter() {
+            let stats = &object.stats;
+            match object.kind {
+                SyncKind::Mutex | SyncKind::Semaphore(_) => writeln!(
+                    f, "{} {}: {:?} acquires, {:?} contended, hold {:.2} mean, {:?} max, wait {:.2} mean, {:?} max, {:?} waiters max, {:?} handoffs, longest convoy {:?}",
+                    object.kind, object.name, stats.nr_acquires, stats.nr_contended, stats.mean_hold(),
+                    stats.hold_max, stats.mean_wait(), stats.wait_max, stats.max_waiters, stats.nr_handoffs, stats.max_convoy
+                )?,
+                SyncKind::CondVar | SyncKind::WaitQueue => writeln!(
+                    f, "{} {}: {:?} waits, {:?} wakeups, wait {:.2} mean, {:?} max, {:?} waiters max",
+                    object.kind, object.name, stats.nr_contended, stats.nr_wakeups,
+                    stats.mean_wait(), stats.wait_max, stats.max_waiters
+                )?
+            };
+        }
+        for window in self.inversions.iter() {
+            let end = match window.end {
+                Some(end) => format!("{:?}", end),
+                None => String::from("the end")
+            };
+            writeln!(
+                f, "Priority inversion: task id {:?} waited on {} held by task id {:?} from {:?} to {}, {:?} ticks to lighter tasks{}",
+                window.waiter, window.lock, window.holder, window.start, end, window.interference,
+                if window.is_unbounded(self.inversion_bound) { ", unbounded" } else { "" }
+            )?;
+        }
+        for task in self.unfinished.iter() {
+            match &task.reason {
+                Unfinished::Stopped => writeln!(f, "Task id {:?} is still stopped", task.id)?,
+                Unfinished::Blocked(on) => writeln!(f, "Deadlock: task id {:?} is blocked on {}", task.id, on)?,
+                Unfinished::WaitingForChildren => writeln!(f, "Deadlock: task id {:?} is blocked waiting for its children", task.id)?
+            };
+        }
+        Ok(())
+    }
+}
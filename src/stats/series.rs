@@ -0,0 +1,229 @@
+// the state of the runqueue and of its tasks sampled every few ticks, as
+// tables written out as csv or, with the parquet feature, apache parquet
+
+use crate::proc::task::{Task, TaskStatus};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RunqueueSample {
+    pub time: u128,
+    pub cpu: u32,
+    // the running task included
+    pub nr_running: usize,
+    // the sum of the weights of the runnable tasks
+    pub load: u64,
+    pub min_vruntime: u64,
+    pub curr: Option<u16>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TaskSample {
+    pub time: u128,
+    pub id: u16,
+    pub vruntime: u64,
+    pub runtime: u64,
+    pub state: TaskStatus,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    Int(u64),
+    Text(String),
+    Null,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColumnKind {
+    Int,
+    Text,
+}
+
+pub const RUNQUEUE_COLUMNS: [(&str, ColumnKind); 6] = [
+    ("time", ColumnKind::Int),
+    ("cpu", ColumnKind::Int),
+    ("nr_running", ColumnKind::Int),
+    ("load", ColumnKind::Int),
+    ("min_vruntime", ColumnKind::Int),
+    ("curr", ColumnKind::Int),
+];
+
+pub const TASK_COLUMNS: [(&str, ColumnKind); 5] = [
+    ("time", ColumnKind::Int),
+    ("id", ColumnKind::Int),
+    ("vruntime", ColumnKind::Int),
+    ("runtime", ColumnKind::Int),
+    ("state", ColumnKind::Text),
+];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Table {
+    pub columns: Vec<(String, ColumnKind)>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    fn new(columns: &[(&str, ColumnKind)]) -> Self {
+        Self { columns: columns.iter().map(|(name, kind)| (name.to_string(), *kind)).collect(), rows: Vec::new() }
+    }
+
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|(column, _)| column == name)
+    }
+
+    // only the given columns, in the given order
+    pub fn select(&self, names: &[&str]) -> Result<Table, String> {
+        let idxs = names.iter().map(|name| match self.column(name) {
+            Some(idx) => Ok(idx),
+            None => Err(format!("No column named {} (expected one of {})", name, self.names().join(", ")))
+        }).collect::<Result<Vec<usize>, String>>()?;
+        Ok(Table {
+            columns: idxs.iter().map(|idx| self.columns[*idx].clone()).collect(),
+            rows: self.rows.iter().map(|row| idxs.iter().map(|idx| row[*idx].clone()).collect()).collect()
+        })
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.columns.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    // a header line, then a line per row, nulls left empty
+    pub fn to_csv(&self) -> String {
+        let mut out = self.names().join(",");
+        out.push('\n');
+        for row in self.rows.iter() {
+            let cells: Vec<String> = row.iter().map(|value| match value {
+                Value::Int(value) => value.to_string(),
+                Value::Text(text) if text.contains([',', '"', '\n']) => format!("\"{}\"", text.replace('"', "\"\"")),
+                Value::Text(text) => text.clone(),
+                Value::Null => String::new()
+            }).collect();
+            out.push_str(&cells.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    // a single row group, every column optional so that nulls fit
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet<W: std::io::Write + Send>(&self, writer: W) -> Result<(), parquet::errors::ParquetError> {
+        use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+        use std::sync::Arc;
+
+        let fields: Vec<String> = self.columns.iter().map(|(name, kind)| match kind {
+            ColumnKind::Int => format!("OPTIONAL INT64 {} (INTEGER(64,false));", name),
+            ColumnKind::Text => format!("OPTIONAL BYTE_ARRAY {} (UTF8);", name)
+        }).collect();
+        let schema = Arc::new(parse_message_type(&format!("message series {{ {} }}", fields.join(" ")))?);
+        let mut file = SerializedFileWriter::new(writer, schema, Arc::new(WriterProperties::builder().build()))?;
+
+        let mut group = file.next_row_group()?;
+        for idx in 0..self.columns.len() {
+            let levels: Vec<i16> = self.rows.iter().map(|row| if row[idx] == Value::Null { 0 } else { 1 }).collect();
+            let mut column = group.next_column()?.unwrap();
+            match self.columns[idx].1 {
+                ColumnKind::Int => {
+                    let values: Vec<i64> = self.rows.iter().filter_map(|row| match row[idx] {
+                        Value::Int(value) => Some(value as i64),
+                        _ => None
+                    }).collect();
+                    column.typed::<Int64Type>().write_batch(&values, Some(&levels), None)?;
+                }
+                ColumnKind::Text => {
+                    let values: Vec<ByteArray> = self.rows.iter().filter_map(|row| match &row[idx] {
+                        Value::Text(text) => Some(ByteArray::from(text.as_str())),
+                        _ => None
+                    }).collect();
+                    column.typed::<ByteArrayType>().write_batch(&values, Some(&levels), None)?;
+                }
+            };
+            column.close()?;
+        }
+        group.close()?;
+        file.close()?;
+        Ok(())
+    }
+}
+
+fn state_name(state: TaskStatus) -> String {
+    format!("{:?}", state).to_lowercase()
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimeSeries {
+    // ticks between samples
+    interval: u64,
+    runqueue: Vec<RunqueueSample>,
+    tasks: Vec<TaskSample>,
+}
+
+impl TimeSeries {
+    pub fn new(interval: u64) -> Result<Self, String> {
+        if interval == 0 {
+            return Err(String::from("Cannot sample every 0 ticks"));
+        }
+        Ok(Self { interval, runqueue: Vec::new(), tasks: Vec::new() })
+    }
+
+    pub fn get_interval(&self) -> u64 { self.interval }
+
+    pub fn get_runqueue(&self) -> &[RunqueueSample] { &self.runqueue }
+
+    pub fn get_tasks(&self) -> &[TaskSample] { &self.tasks }
+
+    pub fn is_due(&self, now: u128) -> bool {
+        now % self.interval as u128 == 0
+    }
+
+    // the runqueue's min_vruntime, curr and the runnable tasks with their
+    // vruntimes, then the rest of the tasks the runqueue holds
+    pub fn sample<'a>(&mut self, now: u128, min_vruntime: u64, curr: Option<(&Task, u64)>, runnable: &[(&Task, u64)], others: impl Iterator<Item = &'a Task>) {
+        let queued = curr.iter().chain(runnable.iter());
+        self.runqueue.push(RunqueueSample {
+            time: now,
+            cpu: 0,
+            nr_running: queued.clone().count(),
+            load: queued.clone().map(|(task, _)| task.weight() as u64).sum(),
+            min_vruntime,
+            curr: curr.map(|(task, _)| task.get_id())
+        });
+
+        let mut samples: Vec<TaskSample> = queued
+            .map(|(task, vruntime)| (*task, *vruntime))
+            .chain(others.map(|task| (task, task.get_vruntime())))
+            .map(|(task, vruntime)| TaskSample { time: now, id: task.get_id(), vruntime, runtime: task.get_runtime(), state: task.get_status() })
+            .collect();
+        samples.sort_by_key(|sample| sample.id);
+        self.tasks.extend(samples);
+    }
+
+    pub fn runqueue_table(&self) -> Table {
+        let mut table = Table::new(&RUNQUEUE_COLUMNS);
+        for sample in self.runqueue.iter() {
+            table.rows.push(vec![
+                Value::Int(sample.time as u64),
+                Value::Int(sample.cpu as u64),
+                Value::Int(sample.nr_running as u64),
+                Value::Int(sample.load),
+                Value::Int(sample.min_vruntime),
+                sample.curr.map_or(Value::Null, |id| Value::Int(id as u64))
+            ]);
+        }
+        table
+    }
+
+    pub fn task_table(&self) -> Table {
+        let mut table = Table::new(&TASK_COLUMNS);
+        for sample in self.tasks.iter() {
+            table.rows.push(vec![
+                Value::Int(sample.time as u64),
+                Value::Int(sample.id as u64),
+                Value::Int(sample.vruntime),
+                Value::Int(sample.runtime),
+                Value::Text(state_name(sample.state))
+            ]);
+        }
+        table
+    }
+}
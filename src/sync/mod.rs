@@ -0,0 +1,255 @@
+// mutexes, semaphores, condition variables and futex style wait queues
+// shared between tasks, which block on them outside of the run queue
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SyncKind {
+    Mutex,
+    // counting semaphore with its initial count
+    Semaphore(u32),
+    CondVar,
+    WaitQueue,
+}
+
+impl SyncKind {
+    // whether tasks lock and unlock it, rather than wait on it and wake each other
+    pub fn is_lock(&self) -> bool {
+        matches!(self, SyncKind::Mutex | SyncKind::Semaphore(_))
+    }
+}
+
+impl FromStr for SyncKind {
+    type Err = String;
+
+    // semaphores are parsed with a count of 1, the workload sets the actual count
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mutex" => Ok(SyncKind::Mutex),
+            "semaphore" => Ok(SyncKind::Semaphore(1)),
+            "condvar" => Ok(SyncKind::CondVar),
+            "waitqueue" | "futex" => Ok(SyncKind::WaitQueue),
+            _ => Err(format!("Unknown synchronisation object kind {:?}", s))
+        }
+    }
+}
+
+impl fmt::Display for SyncKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SyncKind::Mutex => write!(f, "Mutex"),
+            SyncKind::Semaphore(_) => write!(f, "Semaphore"),
+            SyncKind::CondVar => write!(f, "Condvar"),
+            SyncKind::WaitQueue => write!(f, "Wait queue")
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SyncStats {
+    pub nr_acquires: u64,
+    // acquires that had to wait, or for condvars and wait queues the waits
+    pub nr_contended: u64,
+    pub hold_sum: u128,
+    pub hold_max: u128,
+    pub nr_releases: u64,
+    pub wait_sum: u128,
+    pub wait_max: u128,
+    pub nr_waits: u64,
+    pub max_waiters: usize,
+    // releases that passed the lock straight on to a waiter
+    pub nr_handoffs: u64,
+    // the longest run of handoffs without the lock ever going free
+    pub max_convoy: u64,
+    pub nr_wakeups: u64,
+}
+
+impl SyncStats {
+    pub fn mean_hold(&self) -> f64 {
+        if self.nr_releases == 0 {
+            return 0.0;
+        }
+        self.hold_sum as f64 / self.nr_releases as f64
+    }
+
+    pub fn mean_wait(&self) -> f64 {
+        if self.nr_waits == 0 {
+            return 0.0;
+        }
+        self.wait_sum as f64 / self.nr_waits as f64
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SyncObject {
+    name: String,
+    kind: SyncKind,
+    // free units of a lock
+    count: u32,
+    // tasks holding the lock, with the time they took it
+    holders: Vec<(u16, u128)>,
+    // tasks blocked on the object, with the time they started waiting
+    waiters: VecDeque<(u16, u128)>,
+    convoy: u64,
+    stats: SyncStats,
+}
+
+impl SyncObject {
+    pub fn new(name: &str, kind: SyncKind) -> Self {
+        let count = match kind {
+            SyncKind::Mutex => 1,
+            SyncKind::Semaphore(count) => count,
+            SyncKind::CondVar | SyncKind::WaitQueue => 0
+        };
+
+        Self {
+            name: name.to_string(),
+            kind,
+            count,
+            holders: Vec::new(),
+            waiters: VecDeque::new(),
+            convoy: 0,
+            stats: SyncStats::default()
+        }
+    }
+
+    #[inline]
+    pub fn get_name(&self) -> &str { &self.name }
+
+    #[inline]
+    pub fn get_kind(&self) -> SyncKind { self.kind }
+
+    #[inline]
+    pub fn get_count(&self) -> u32 { self.count }
+
+    #[inline]
+    pub fn get_stats(&self) -> SyncStats { self.stats }
+
+    pub fn get_holders(&self) -> Vec<u16> {
+        self.holders.iter().map(|(id, _)| *id).collect()
+    }
+
+    pub fn get_waiters(&self) -> Vec<u16> {
+        self.waiters.iter().map(|(id, _)| *id).collect()
+    }
+
+    // takes a task off the wait list without handing it anything
+    pub fn cancel(&mut self, id: u16) {
+        self.waiters.retain(|(waiter, _)| *waiter != id);
+    }
+
+    fn grant(&mut self, id: u16, now: u128) {
+        self.holders.push((id, now));
+        self.stats.nr_acquires += 1;
+    }
+
+    // takes the lock if it is free, leaving it untouched otherwise
+    pub fn try_acquire(&mut self, id: u16, now: u128) -> Result<bool, String> {
+        if !self.kind.is_lock() {
+            return Err(format!("Task id {} tried to lock {} {:?}", id, self.kind, self.name));
+        }
+        if self.kind == SyncKind::Mutex && self.holders.iter().any(|(holder, _)| *holder == id) {
+            return Err(format!("Task id {} locked mutex {:?} it already holds", id, self.name));
+        }
+        if self.count == 0 {
+            return Ok(false);
+        }
+
+        self.count -= 1;
+        self.convoy = 0;
+        self.grant(id, now);
+        Ok(true)
+    }
+
+    // queues a task that has to wait for the object
+    pub fn enqueue(&mut self, id: u16, now: u128) {
+        self.waiters.push_back((id, now));
+        self.stats.nr_contended += 1;
+        self.stats.max_waiters = self.stats.max_waiters.max(self.waiters.len());
+    }
+
+    fn dequeue(&mut self, now: u128) -> Option<u16> {
+        let (id, since) = self.waiters.pop_front()?;
+        let wait = now - since;
+        self.stats.nr_waits += 1;
+        self.stats.wait_sum += wait;
+        self.stats.wait_max = self.stats.wait_max.max(wait);
+        Some(id)
+    }
+
+    // gives the lock back, handing it to the first waiter if there is one,
+    // whose id is returned
+    pub fn release(&mut self, id: u16, now: u128) -> Result<Option<u16>, String> {
+        if !self.kind.is_lock() {
+            return Err(format!("Task id {} tried to unlock {} {:?}", id, self.kind, self.name));
+        }
+        // anyone may post a semaphore, only the owner unlocks a mutex
+        match self.holders.iter().position(|(holder, _)| *holder == id) {
+            Some(idx) => {
+                let (_, since) = self.holders.remove(idx);
+                let hold = now - since;
+                self.stats.nr_releases += 1;
+                self.stats.hold_sum += hold;
+                self.stats.hold_max = self.stats.hold_max.max(hold);
+            },
+            None if self.kind == SyncKind::Mutex => {
+                return Err(format!("Task id {} unlocked mutex {:?} it does not hold", id, self.name));
+            },
+            None => {}
+        };
+
+        match self.dequeue(now) {
+            Some(waiter) => {
+                self.grant(waiter, now);
+                self.stats.nr_handoffs += 1;
+                self.convoy += 1;
+                self.stats.max_convoy = self.stats.max_convoy.max(self.convoy);
+                Ok(Some(waiter))
+            },
+            None => {
+                self.count += 1;
+                self.convoy = 0;
+                Ok(None)
+            }
+        }
+    }
+
+    // wakes up to the given number of waiters, all of them with None
+    pub fn wake(&mut self, nr: Option<usize>, now: u128) -> Result<Vec<u16>, String> {
+        if self.kind.is_lock() {
+            return Err(format!("{} {:?} can't be signalled", self.kind, self.name));
+        }
+
+        let nr = nr.unwrap_or(self.waiters.len()).min(self.waiters.len());
+        let woken: Vec<u16> = (0..nr).filter_map(|_| self.dequeue(now)).collect();
+        self.stats.nr_wakeups += woken.len() as u64;
+        Ok(woken)
+    }
+}
+
+// a task waiting on a mutex held by a task of lower weight
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InversionWindow {
+    pub waiter: u16,
+    pub lock: String,
+    // the holder when the wait began
+    pub holder: u16,
+    pub start: u128,
+    pub end: Option<u128>,
+    // ticks in which tasks lighter than the waiter ran instead of the holder
+    pub interference: u64,
+}
+
+impl InversionWindow {
+    pub fn length(&self, now: u128) -> u128 {
+        self.end.unwrap_or(now) - self.start
+    }
+
+    // the holder was kept from the cpu by unrelated tasks for longer than
+    // the bound, so the wait was no longer bounded by the critical section
+    pub fn is_unbounded(&self, bound: u64) -> bool {
+        self.interference > bound
+    }
+}
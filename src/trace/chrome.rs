@@ -0,0 +1,61 @@
+// exports a trace in the chrome trace event format, which ui.perfetto.dev
+// and chrome://tracing open, with a track per cpu
+
+use super::{EventKind, Trace, TICK_US};
+
+use serde_json::{json, Value};
+
+fn event_name(kind: EventKind) -> &'static str {
+    match kind {
+        EventKind::Switch { .. } => "sched_switch",
+        EventKind::WakeupNew => "sched_wakeup_new",
+        EventKind::Wakeup => "sched_wakeup",
+        EventKind::Preempt => "sched_preempt",
+        EventKind::Migrate { .. } => "sched_migrate_task",
+        EventKind::Exit => "sched_process_exit"
+    }
+}
+
+pub fn to_value(trace: &Trace) -> Value {
+    let mut events = vec![json!({
+        "name": "process_name", "ph": "M", "pid": 0,
+        "args": { "name": "CPUs" }
+    })];
+    for cpu in 0..trace.nr_cpus() {
+        events.push(json!({
+            "name": "thread_name", "ph": "M", "pid": 0, "tid": cpu,
+            "args": { "name": format!("CPU {}", cpu) }
+        }));
+    }
+
+    for slice in trace.slices.iter() {
+        let state = match slice.state {
+            Some(state) => state.to_string(),
+            None => String::from("running")
+        };
+        events.push(json!({
+            "name": trace.name(slice.id), "cat": "sched", "ph": "X", "pid": 0, "tid": slice.cpu,
+            "ts": (slice.start * TICK_US) as u64, "dur": ((slice.end - slice.start) * TICK_US) as u64,
+            "args": { "pid": slice.id, "end_state": state }
+        }));
+    }
+
+    // switches are already the edges of the slices
+    for event in trace.events.iter().filter(|event| !matches!(event.kind, EventKind::Switch { .. })) {
+        let mut args = json!({ "pid": event.id, "comm": trace.name(event.id) });
+        if let EventKind::Migrate { from, to } = event.kind {
+            args["orig_cpu"] = json!(from);
+            args["dest_cpu"] = json!(to);
+        }
+        events.push(json!({
+            "name": event_name(event.kind), "cat": "sched", "ph": "i", "s": "t", "pid": 0, "tid": event.cpu,
+            "ts": (event.time * TICK_US) as u64, "args": args
+        }));
+    }
+
+    json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+}
+
+pub fn to_json(trace: &Trace) -> String {
+    to_value(trace).to_string()
+}
@@ -0,0 +1,113 @@
+// writes a trace as the text of the ftrace trace file, with the sched
+// tracepoints enabled, which perf sched and trace-cmd style parsers read
+
+use super::{EventKind, SwitchState, Trace, TICK_US};
+
+use std::collections::BTreeMap;
+
+// the kernel keeps at most 15 characters of a task's name
+fn comm(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .take(15)
+        .collect()
+}
+
+fn timestamp(time: u128) -> String {
+    let us = time * TICK_US;
+    format!("{}.{:06}", us / 1_000_000, us % 1_000_000)
+}
+
+struct Writer<'a> {
+    trace: &'a Trace,
+    lines: Vec<String>,
+    // the task on every cpu, None for the idle task
+    current: BTreeMap<u32, Option<u16>>,
+}
+
+impl Writer<'_> {
+    // the comm, pid and prio of a task, or of the cpu's idle task
+    fn task(&self, id: Option<u16>, cpu: u32) -> (String, u16, i32) {
+        match id {
+            Some(id) => (comm(&self.trace.name(id)), id, self.trace.prio(id)),
+            None => (format!("swapper/{}", cpu), 0, 120)
+        }
+    }
+
+    fn line(&mut self, cpu: u32, time: u128, name: &str, fields: String) {
+        let current = *self.current.get(&cpu).unwrap_or(&None);
+        let (comm, pid, _) = match current {
+            Some(_) => self.task(current, cpu),
+            None => (String::from("<idle>"), 0, 0)
+        };
+        self.lines.push(format!(
+            "{:>16}-{:<7} [{:03}] d... {:>13}: {}: {}",
+            comm, pid, cpu, timestamp(time), name, fields
+        ));
+    }
+
+    fn switch(&mut self, cpu: u32, time: u128, prev: Option<u16>, prev_state: SwitchState, next: Option<u16>) {
+        let (prev_comm, prev_pid, prev_prio) = self.task(prev, cpu);
+        let (next_comm, next_pid, next_prio) = self.task(next, cpu);
+        let fields = format!(
+            "prev_comm={} prev_pid={} prev_prio={} prev_state={} ==> next_comm={} next_pid={} next_prio={}",
+            prev_comm, prev_pid, prev_prio, prev_state, next_comm, next_pid, next_prio
+        );
+        self.line(cpu, time, "sched_switch", fields);
+        self.current.insert(cpu, next);
+    }
+}
+
+pub fn to_text(trace: &Trace) -> String {
+    let mut writer = Writer { trace, lines: Vec::new(), current: BTreeMap::new() };
+
+    for event in trace.events.iter() {
+        let (comm, pid, prio) = writer.task(Some(event.id), event.cpu);
+        match event.kind {
+            EventKind::Switch { prev, prev_state, next } => writer.switch(event.cpu, event.time, prev, prev_state, next),
+            EventKind::WakeupNew | EventKind::Wakeup => {
+                let name = if event.kind == EventKind::WakeupNew { "sched_wakeup_new" } else { "sched_wakeup" };
+                let fields = format!("comm={} pid={} prio={} target_cpu={:03}", comm, pid, prio, event.cpu);
+                writer.line(event.cpu, event.time, name, fields);
+            },
+            EventKind::Migrate { from, to } => {
+                let fields = format!("comm={} pid={} prio={} orig_cpu={} dest_cpu={}", comm, pid, prio, from, to);
+                writer.line(event.cpu, event.time, "sched_migrate_task", fields);
+            },
+            EventKind::Exit => {
+                let fields = format!("comm={} pid={} prio={}", comm, pid, prio);
+                writer.line(event.cpu, event.time, "sched_process_exit", fields);
+            },
+            // a preemption shows as a switch away from a runnable task
+            EventKind::Preempt => {}
+        };
+    }
+
+    // cpus whose last task left once the run was over go idle
+    for cpu in 0..trace.nr_cpus() {
+        let last = trace.slices.iter().rev().find(|slice| slice.cpu == cpu).copied();
+        if let Some(slice) = last.filter(|slice| writer.current.get(&cpu) == Some(&Some(slice.id))) {
+            if let Some(state) = slice.state {
+                writer.switch(cpu, slice.end, Some(slice.id), state, None);
+            }
+        }
+    }
+
+    let mut text = String::from("# tracer: nop\n#\n");
+    text += &format!(
+        "# entries-in-buffer/entries-written: {}/{}   #P:{}\n#\n",
+        writer.lines.len(), writer.lines.len(), trace.nr_cpus().max(1)
+    );
+    text += "#                                _-----=> irqs-off\n";
+    text += "#                               / _----=> need-resched\n";
+    text += "#                              | / _---=> hardirq/softirq\n";
+    text += "#                              || / _--=> preempt-depth\n";
+    text += "#                              ||| /     delay\n";
+    text += "#           TASK-PID     CPU#  ||||   TIMESTAMP  FUNCTION\n";
+    text += "#              | |         |   ||||      |         |\n";
+    for line in writer.lines {
+        text += &line;
+        text.push('\n');
+    }
+    text
+}
@@ -0,0 +1,387 @@
+// draws a trace as a gantt chart, as ascii text for the terminal or as a
+// standalone svg file, with a row for every task or for every cpu
+
+use super::{EventKind, SwitchState, Trace};
+use crate::proc::task::TaskStatus;
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GanttRows {
+    // what every task was doing
+    Tasks,
+    // which task every cpu ran
+    Cpus,
+}
+
+// the ascii cells and svg colours of the task states
+fn symbol(status: TaskStatus) -> char {
+    match status {
+        TaskStatus::Running => '#',
+        TaskStatus::Waiting => '-',
+        TaskStatus::Idle => '.',
+        TaskStatus::Stopped => 's',
+        _ => 'x'
+    }
+}
+
+fn colour(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Running => "#43a047",
+        TaskStatus::Waiting => "#fdd835",
+        TaskStatus::Idle => "#42a5f5",
+        TaskStatus::Stopped => "#9e9e9e",
+        _ => "#e53935"
+    }
+}
+
+const LEGEND: [(TaskStatus, &str); 5] = [
+    (TaskStatus::Running, "running"),
+    (TaskStatus::Waiting, "waiting"),
+    (TaskStatus::Idle, "idle (i/o)"),
+    (TaskStatus::Stopped, "stopped"),
+    (TaskStatus::Terminated, "terminated"),
+];
+
+// the cells of the tasks on a cpu row, in order of id
+const TASK_SYMBOLS: &str = "123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+// and their svg colours, taken in turn
+const TASK_COLOURS: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd",
+    "#8c564b", "#e377c2", "#7f7f7f", "#bcbd22", "#17becf",
+];
+const CPU_IDLE: &str = "#eeeeee";
+
+// svg sizes in pixels
+const COLUMN_WIDTH: u128 = 8;
+const ROW_HEIGHT: u128 = 20;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+pub struct Gantt<'a> {
+    trace: &'a Trace,
+    rows: GanttRows,
+    from: u128,
+    to: Option<u128>,
+    // ticks per column
+    zoom: u128,
+}
+
+impl<'a> Gantt<'a> {
+    pub fn new(trace: &'a Trace) -> Self {
+        Self { trace, rows: GanttRows::Tasks, from: 0, to: None, zoom: 1 }
+    }
+
+    pub fn set_rows(&mut self, rows: GanttRows) {
+        self.rows = rows;
+    }
+
+    // the ticks from up to, not including, to; None to draw up to the end
+    pub fn set_window(&mut self, from: u128, to: Option<u128>) {
+        if to.is_some_and(|to| to <= from) {
+            panic!("The window has to end after it starts");
+        }
+        self.from = from;
+        self.to = to;
+    }
+
+    pub fn set_zoom(&mut self, ticks: u128) {
+        if ticks == 0 {
+            panic!("A column has to be at least a tick wide");
+        }
+        self.zoom = ticks;
+    }
+
+    // up to the tick after the trace ends, so the last exit shows
+    fn end(&self) -> u128 {
+        self.to.unwrap_or_else(|| self.trace.end() + 1).max(self.from + 1)
+    }
+
+    fn nr_columns(&self) -> u128 {
+        (self.end() - self.from).div_ceil(self.zoom)
+    }
+
+    // the ticks of a column
+    fn column(&self, col: u128) -> std::ops::Range<u128> {
+        let start = self.from + col * self.zoom;
+        start..(start + self.zoom).min(self.end())
+    }
+
+    fn ids(&self) -> Vec<u16> {
+        let ids: BTreeSet<u16> = self.trace.names.keys().copied()
+            .chain(self.trace.slices.iter().map(|slice| slice.id))
+            .chain(self.trace.events.iter().map(|event| event.id))
+            .collect();
+        ids.into_iter().collect()
+    }
+
+    // the ticks at which the task changed state, None before it arrived
+    // and after its last slice if the trace stopped while it was running
+    pub fn timeline(&self, id: u16) -> Vec<(u128, Option<TaskStatus>)> {
+        // leaving the cpu comes before a wakeup at the same tick, and both
+        // before getting back on it
+        let mut changes: Vec<(u128, u8, Option<TaskStatus>)> = Vec::new();
+        for slice in self.trace.slices.iter().filter(|slice| slice.id == id) {
+            changes.push((slice.start, 2, Some(TaskStatus::Running)));
+            let status = slice.state.map(|state| match state {
+                SwitchState::Preempted => TaskStatus::Waiting,
+                SwitchState::Sleeping | SwitchState::Blocked => TaskStatus::Idle,
+                SwitchState::Stopped => TaskStatus::Stopped,
+                SwitchState::Dead => TaskStatus::Terminated
+            });
+            changes.push((slice.end, 0, status));
+        }
+        for event in self.trace.events.iter().filter(|event| event.id == id) {
+            match event.kind {
+                EventKind::WakeupNew | EventKind::Wakeup => changes.push((event.time, 1, Some(TaskStatus::Waiting))),
+                EventKind::Exit => changes.push((event.time, 1, Some(TaskStatus::Terminated))),
+                _ => ()
+            };
+        }
+        changes.sort_by_key(|(time, order, _)| (*time, *order));
+
+        let mut timeline: Vec<(u128, Option<TaskStatus>)> = Vec::new();
+        for (time, _, status) in changes {
+            // nothing comes back from the dead
+            if timeline.last().is_some_and(|(_, last)| *last == Some(TaskStatus::Terminated)) {
+                break;
+            }
+            match timeline.last_mut() {
+                Some(last) if last.0 == time => last.1 = status,
+                _ => timeline.push((time, status))
+            };
+        }
+        timeline
+    }
+
+    // the tick the task terminated at
+    fn exit(&self, id: u16) -> Option<u128> {
+        match self.timeline(id).last() {
+            Some((time, Some(TaskStatus::Terminated))) => Some(*time),
+            _ => None
+        }
+    }
+
+    // the runs of equal states within the window, as (first tick, ticks,
+    // state), from the ticks the state changed at and the state before them
+    fn runs<T: Copy + Eq>(&self, changes: &[(u128, T)], mut state: T) -> Vec<(u128, u128, T)> {
+        let end = self.end();
+        let mut runs: Vec<(u128, u128, T)> = Vec::new();
+        let mut push = |start: u128, stop: u128, state: T| match runs.last_mut() {
+            Some(run) if run.2 == state => run.1 += stop - start,
+            _ => runs.push((start, stop - start, state))
+        };
+        let mut start = self.from;
+        for (time, next) in changes.iter().copied().take_while(|(time, _)| *time < end) {
+            if time > start && next != state {
+                push(start, time, state);
+                start = time;
+            }
+            state = next;
+        }
+        push(start, end, state);
+        runs
+    }
+
+    // what the task was doing over the window
+    fn states(&self, id: u16) -> Vec<(u128, u128, Option<TaskStatus>)> {
+        self.runs(&self.timeline(id), None)
+    }
+
+    // the task on the cpu over the window
+    fn cpu_states(&self, cpu: u32) -> Vec<(u128, u128, Option<u16>)> {
+        // a slice ending comes before the next one starting at the same tick
+        let mut changes: Vec<(u128, u8, Option<u16>)> = Vec::new();
+        for slice in self.trace.slices.iter().filter(|slice| slice.cpu == cpu) {
+            changes.push((slice.start, 1, Some(slice.id)));
+            changes.push((slice.end, 0, None));
+        }
+        changes.sort_by_key(|(time, order, _)| (*time, *order));
+        let changes: Vec<(u128, Option<u16>)> = changes.into_iter().map(|(time, _, id)| (time, id)).collect();
+        self.runs(&changes, None)
+    }
+
+    // what a column shows, the state of most of its ticks, the first listed
+    // on a tie
+    fn pick<T: Copy + Eq>(&self, runs: &[(u128, u128, T)], col: u128, order: &[T]) -> T {
+        let ticks = self.column(col);
+        let first = runs.partition_point(|(start, len, _)| start + len <= ticks.start);
+        let cells = runs[first..].iter().take_while(|(start, _, _)| *start < ticks.end);
+        let count = |state: &T| -> u128 {
+            cells.clone()
+                .filter(|run| run.2 == *state)
+                .map(|(start, len, _)| (start + len).min(ticks.end) - (*start).max(ticks.start))
+                .sum()
+        };
+        *order.iter().rev().max_by_key(|state| count(state)).unwrap()
+    }
+
+    // the svg colour of a task on a cpu row
+    fn task_colour(&self, ids: &[u16], id: u16) -> &'static str {
+        TASK_COLOURS[ids.iter().position(|other| *other == id).unwrap() % TASK_COLOURS.len()]
+    }
+
+    fn label(&self, id: u16) -> String {
+        format!("{} ({})", self.trace.name(id), id)
+    }
+
+    fn row_labels(&self) -> Vec<String> {
+        match self.rows {
+            GanttRows::Tasks => self.ids().into_iter().map(|id| self.label(id)).collect(),
+            GanttRows::Cpus => (0..self.trace.nr_cpus().max(1)).map(|cpu| format!("CPU {}", cpu)).collect()
+        }
+    }
+
+    pub fn to_ascii(&self) -> String {
+        let labels = self.row_labels();
+        let width = labels.iter().map(|label| label.len()).max().unwrap_or(0).max(4);
+        let mut out = String::new();
+
+        // a time every ten columns
+        let mut ruler = String::new();
+        for col in (0..self.nr_columns()).step_by(10) {
+            let time = (self.from + col * self.zoom).to_string();
+            if ruler.len() <= col as usize {
+                ruler.push_str(&" ".repeat(col as usize - ruler.len()));
+                ruler.push_str(&time);
+            }
+        }
+        writeln!(out, "{:<width$}  {}", "tick", ruler.trim_end(), width = width).unwrap();
+
+        let ids = self.ids();
+        match self.rows {
+            GanttRows::Tasks => {
+                let order = [
+                    Some(TaskStatus::Running),
+                    Some(TaskStatus::Waiting),
+                    Some(TaskStatus::Idle),
+                    Some(TaskStatus::Stopped),
+                    Some(TaskStatus::Terminated),
+                    None
+                ];
+                for (id, label) in ids.iter().zip(labels.iter()) {
+                    let states = self.states(*id);
+                    // terminated is marked once, in the column it happened in
+                    let exit = self.exit(*id);
+                    let row: String = (0..self.nr_columns()).map(|col| match self.pick(&states, col, &order) {
+                        _ if exit.is_some_and(|exit| self.column(col).contains(&exit)) => symbol(TaskStatus::Terminated),
+                        Some(TaskStatus::Terminated) | None => ' ',
+                        Some(status) => symbol(status)
+                    }).collect();
+                    writeln!(out, "{}", format!("{:<width$}  {}", label, row, width = width).trim_end()).unwrap();
+                }
+                let legend: Vec<String> = LEGEND.iter().map(|(status, name)| format!("{} {}", symbol(*status), name)).collect();
+                writeln!(out, "{}", legend.join("  ")).unwrap();
+            }
+            GanttRows::Cpus => {
+                let mut order: Vec<Option<u16>> = ids.iter().map(|id| Some(*id)).collect();
+                order.push(None);
+                let cell = |id: Option<u16>| match id {
+                    Some(id) => TASK_SYMBOLS.chars().nth(ids.iter().position(|other| *other == id).unwrap()).unwrap_or('*'),
+                    None => '.'
+                };
+                for (cpu, label) in labels.iter().enumerate() {
+                    let states = self.cpu_states(cpu as u32);
+                    let row: String = (0..self.nr_columns()).map(|col| cell(self.pick(&states, col, &order))).collect();
+                    writeln!(out, "{:<width$}  {}", label, row, width = width).unwrap();
+                }
+                let mut legend: Vec<String> = ids.iter().map(|id| format!("{} {}", cell(Some(*id)), self.label(*id))).collect();
+                legend.push(String::from(". idle"));
+                writeln!(out, "{}", legend.join("  ")).unwrap();
+            }
+        };
+        out
+    }
+
+    pub fn to_svg(&self) -> String {
+        let labels = self.row_labels();
+        let label_width = labels.iter().map(|label| label.len() as u128).max().unwrap_or(0) * 7 + 10;
+        let chart_width = self.nr_columns() * COLUMN_WIDTH;
+        let width = label_width + chart_width + 10;
+        let ids = self.ids();
+
+        // the states on task rows, the tasks on cpu rows, on as many lines as they need
+        let legend: Vec<(&str, String)> = match self.rows {
+            GanttRows::Tasks => LEGEND.iter().map(|(status, name)| (colour(*status), name.to_string())).collect(),
+            GanttRows::Cpus => ids.iter()
+                .map(|id| (self.task_colour(&ids, *id), self.label(*id)))
+                .chain(std::iter::once((CPU_IDLE, String::from("idle"))))
+                .collect()
+        };
+        let mut legend_lines: Vec<Vec<(u128, &str, String)>> = vec![Vec::new()];
+        let mut legend_x = 4;
+        for (fill, name) in legend {
+            let entry_width = 14 + name.len() as u128 * 7 + 16;
+            if legend_x > 4 && legend_x + entry_width > width {
+                legend_lines.push(Vec::new());
+                legend_x = 4;
+            }
+            legend_lines.last_mut().unwrap().push((legend_x, fill, name));
+            legend_x += entry_width;
+        }
+        let height = (labels.len() + 2 + legend_lines.len()) as u128 * ROW_HEIGHT;
+        // x of the start of a tick
+        let x = |tick: u128| label_width as f64 + (tick - self.from) as f64 * COLUMN_WIDTH as f64 / self.zoom as f64;
+        let mut out = String::new();
+
+        writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="11">"#, width, height).unwrap();
+        writeln!(out, r#"<rect width="{}" height="{}" fill="white"/>"#, width, height).unwrap();
+        for col in (0..self.nr_columns()).step_by(10) {
+            let tick = self.from + col * self.zoom;
+            writeln!(out, r##"<line x1="{0:.1}" y1="{1}" x2="{0:.1}" y2="{2}" stroke="#cccccc"/>"##, x(tick), ROW_HEIGHT - 4, (labels.len() as u128 + 1) * ROW_HEIGHT).unwrap();
+            writeln!(out, r#"<text x="{:.1}" y="{}">{}</text>"#, x(tick) + 2.0, ROW_HEIGHT - 6, tick).unwrap();
+        }
+
+        for (row, label) in labels.iter().enumerate() {
+            let y = (row as u128 + 1) * ROW_HEIGHT;
+            writeln!(out, r#"<text x="4" y="{}">{}</text>"#, y + ROW_HEIGHT - 6, escape(label)).unwrap();
+            let mut rect = |start: u128, ticks: u128, fill: &str, title: String| {
+                writeln!(
+                    out,
+                    r#"<rect x="{:.1}" y="{}" width="{:.1}" height="{}" fill="{}"><title>{}</title></rect>"#,
+                    x(start), y + 2, x(start + ticks) - x(start), ROW_HEIGHT - 4, fill, escape(&title)
+                ).unwrap();
+            };
+            match self.rows {
+                GanttRows::Tasks => {
+                    let exit = self.exit(ids[row]);
+                    for (start, ticks, status) in self.states(ids[row]) {
+                        let name = match LEGEND.iter().find(|(other, _)| Some(*other) == status) {
+                            Some((_, name)) => name,
+                            None => continue
+                        };
+                        // a mark where the task terminated, not a bar up to the end
+                        if status == Some(TaskStatus::Terminated) {
+                            if exit == Some(start) {
+                                rect(start, 1, colour(TaskStatus::Terminated), format!("{} {} at {}", label, name, start));
+                            }
+                            continue;
+                        }
+                        rect(start, ticks, colour(status.unwrap()), format!("{} {} {}-{}", label, name, start, start + ticks));
+                    }
+                }
+                GanttRows::Cpus => {
+                    for (start, ticks, id) in self.cpu_states(row as u32) {
+                        match id {
+                            Some(id) => rect(start, ticks, self.task_colour(&ids, id), format!("{} {}-{}", self.label(id), start, start + ticks)),
+                            None => rect(start, ticks, CPU_IDLE, format!("idle {}-{}", start, start + ticks))
+                        };
+                    }
+                }
+            };
+        }
+
+        for (line, entries) in legend_lines.iter().enumerate() {
+            let y = (labels.len() + 2 + line) as u128 * ROW_HEIGHT;
+            for (legend_x, fill, name) in entries {
+                writeln!(out, r#"<rect x="{}" y="{}" width="10" height="10" fill="{}"/>"#, legend_x, y - 10, fill).unwrap();
+                writeln!(out, r#"<text x="{}" y="{}">{}</text>"#, legend_x + 14, y, escape(name)).unwrap();
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
@@ -0,0 +1,160 @@
+// a record of the schedule, which task was on which cpu when and the
+// wakeups, preemptions, migrations and exits in between
+
+pub mod chrome;
+pub mod ftrace;
+pub mod gantt;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+// a tick is taken to be a millisecond, as with HZ=1000
+pub const TICK_US: u128 = 1000;
+
+// what the task went on to do once it left the cpu
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SwitchState {
+    // back in the runqueue
+    Preempted,
+    // sleeping, waiting for children or for its next job
+    Sleeping,
+    // doing io or blocked on a lock
+    Blocked,
+    Stopped,
+    Dead,
+}
+
+impl fmt::Display for SwitchState {
+    // the prev_state letters of sched_switch
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SwitchState::Preempted => write!(f, "R"),
+            SwitchState::Sleeping => write!(f, "S"),
+            SwitchState::Blocked => write!(f, "D"),
+            SwitchState::Stopped => write!(f, "T"),
+            SwitchState::Dead => write!(f, "X")
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Slice {
+    pub cpu: u32,
+    pub id: u16,
+    pub start: u128,
+    pub end: u128,
+    // None while the task is still on the cpu
+    pub state: Option<SwitchState>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventKind {
+    // the cpu went from prev to next, None being the idle task
+    Switch { prev: Option<u16>, prev_state: SwitchState, next: Option<u16> },
+    WakeupNew,
+    Wakeup,
+    Preempt,
+    Migrate { from: u32, to: u32 },
+    Exit,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TraceEvent {
+    pub time: u128,
+    pub cpu: u32,
+    pub id: u16,
+    pub kind: EventKind,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Trace {
+    // the names and kernel priorities of the tasks by id
+    pub names: BTreeMap<u16, String>,
+    pub prios: BTreeMap<u16, i32>,
+    // in order of start time
+    pub slices: Vec<Slice>,
+    // in the order they happened
+    pub events: Vec<TraceEvent>,
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(&self, id: u16) -> String {
+        match self.names.get(&id) {
+            Some(name) => name.clone(),
+            None => format!("task-{}", id)
+        }
+    }
+
+    // 120 for nice 0, as in the prio fields of sched events
+    pub fn prio(&self, id: u16) -> i32 {
+        *self.prios.get(&id).unwrap_or(&120)
+    }
+
+    // the number of cpus with at least one slice or event
+    pub fn nr_cpus(&self) -> u32 {
+        self.slices.iter().map(|slice| slice.cpu)
+            .chain(self.events.iter().map(|event| event.cpu))
+            .max()
+            .map_or(0, |cpu| cpu + 1)
+    }
+
+    // the tick the last slice or event ended at
+    pub fn end(&self) -> u128 {
+        self.slices.iter().map(|slice| slice.end)
+            .chain(self.events.iter().map(|event| event.time))
+            .max()
+            .unwrap_or(0)
+    }
+
+    // the task ran on the cpu for the tick starting at now
+    pub fn run(&mut self, cpu: u32, id: u16, now: u128) {
+        let last = self.slices.iter().rposition(|slice| slice.cpu == cpu);
+        if let Some(idx) = last {
+            let slice = &mut self.slices[idx];
+            // a task picked again right after it was put back never left the cpu
+            if slice.end == now && slice.id == id && matches!(slice.state, None | Some(SwitchState::Preempted)) {
+                slice.end = now + 1;
+                slice.state = None;
+                return;
+            }
+        }
+
+        let prev = last.map(|idx| self.slices[idx]).filter(|slice| slice.end == now);
+        if let Some(slice) = prev.filter(|slice| slice.state == Some(SwitchState::Preempted)) {
+            self.event(cpu, slice.id, now, EventKind::Preempt);
+        }
+        let kind = EventKind::Switch {
+            prev: prev.map(|slice| slice.id),
+            prev_state: prev.and_then(|slice| slice.state).unwrap_or(SwitchState::Preempted),
+            next: Some(id)
+        };
+        self.event(cpu, id, now, kind);
+        self.slices.push(Slice { cpu, id, start: now, end: now + 1, state: None });
+    }
+
+    // nothing ran on the cpu for the tick starting at now
+    pub fn idle(&mut self, cpu: u32, now: u128) {
+        let last = self.slices.iter().rev().find(|slice| slice.cpu == cpu).copied();
+        if let Some(slice) = last.filter(|slice| slice.end == now) {
+            let kind = EventKind::Switch { prev: Some(slice.id), prev_state: slice.state.unwrap_or(SwitchState::Preempted), next: None };
+            self.event(cpu, slice.id, now, kind);
+        }
+    }
+
+    // the task left the cpu, if it was on it
+    pub fn switch_out(&mut self, id: u16, state: SwitchState) {
+        if let Some(slice) = self.slices.iter_mut().rev().find(|slice| slice.id == id) {
+            if slice.state.is_none() {
+                slice.state = Some(state);
+            }
+        }
+    }
+
+    pub fn event(&mut self, cpu: u32, id: u16, time: u128, kind: EventKind) {
+        self.events.push(TraceEvent { time, cpu, id, kind });
+    }
+}
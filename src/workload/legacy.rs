@@ -0,0 +1,83 @@
+// the original whitespace separated format, one task per line:
+// cpu_time cpu_burst_length io_burst_length weight [session [policy [arrival]]]
+// the optional columns also go by name in any order, `100 10 5 1024 arrival=50`,
+// after whichever of them are given by position.
+// tasks are numbered by the line they are on
+use super::WorkloadError;
+use crate::proc::task::{SchedPolicy, TaskChar};
+
+use std::str::FromStr;
+
+// the columns after the weight, in the order they go by position
+const OPTIONAL: [&str; 3] = ["session", "policy", "arrival"];
+
+fn column<T: FromStr>(value: &str, name: &str, line: usize) -> Result<T, WorkloadError> {
+    value.parse::<T>()
+        .map_err(|_| WorkloadError::new(line, &format!("invalid {} {:?}", name, value)))
+}
+
+pub fn parse(src: &str) -> Result<Vec<TaskChar>, WorkloadError> {
+    let mut tasks = Vec::new();
+
+    for (idx, text) in src.lines().enumerate() {
+        let line = idx + 1;
+        let columns: Vec<&str> = text.split_whitespace().collect();
+        if columns.is_empty() {
+            continue;
+        }
+        if columns.len() < 4 || columns.len() > 7 {
+            return Err(WorkloadError::new(
+                line,
+                &format!("expected between 4 and 7 columns, found {}", columns.len())
+            ));
+        }
+        if line > u16::MAX as usize {
+            return Err(WorkloadError::new(line, "too many tasks"));
+        }
+
+        let mut raw = TaskChar::new(
+            line as u16,
+            column(columns[0], "cpu time", line)?,
+            column(columns[1], "cpu burst length", line)?,
+            column(columns[2], "io burst length", line)?,
+            column(columns[3], "weight", line)?
+        );
+        if raw.get_cpu_time() == 0 || raw.get_cpu_burst_length() == 0 || raw.get_weight() == 0 {
+            return Err(WorkloadError::new(line, "cpu time, cpu burst length and weight must be positive"));
+        }
+
+        let mut optional: [Option<&str>; 3] = [None; 3];
+        let mut named = false;
+        for (pos, text) in columns[4..].iter().enumerate() {
+            let (idx, value) = match text.split_once('=') {
+                Some((name, value)) => {
+                    named = true;
+                    let idx = OPTIONAL.iter().position(|column| *column == name)
+                        .ok_or_else(|| WorkloadError::new(line, &format!("unknown column {:?}", name)))?;
+                    (idx, value)
+                },
+                None if named => {
+                    return Err(WorkloadError::new(line, &format!("{:?} comes after a named column", text)));
+                },
+                None => (pos, *text)
+            };
+            if optional[idx].replace(value).is_some() {
+                return Err(WorkloadError::new(line, &format!("{} is given twice", OPTIONAL[idx])));
+            }
+        }
+
+        if let Some(session) = optional[0] {
+            raw.set_session(column(session, "session", line)?);
+        }
+        if let Some(policy) = optional[1] {
+            raw.set_policy(column::<SchedPolicy>(policy, "policy", line)?);
+        }
+        if let Some(arrival) = optional[2] {
+            raw.set_arrival(column(arrival, "arrival", line)?);
+        }
+
+        tasks.push(raw);
+    }
+
+    Ok(tasks)
+}
@@ -0,0 +1,73 @@
+// workload files describing the tasks of a run
+
+pub mod legacy;
+pub mod replay;
+pub mod spec;
+
+use crate::io::device::Device;
+use crate::proc::task::TaskChar;
+use crate::sync::SyncObject;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WorkloadError {
+    line: usize,
+    message: String,
+}
+
+impl WorkloadError {
+    pub fn new(line: usize, message: &str) -> Self {
+        Self { line, message: message.to_string() }
+    }
+
+    // an error at a byte offset into the source
+    pub fn at(src: &str, offset: usize, message: &str) -> Self {
+        let offset = offset.min(src.len());
+        let line = src[..offset].matches('\n').count() + 1;
+
+        Self::new(line, message)
+    }
+
+    // the one-based line of the error, 0 if it is not tied to a line
+    #[inline]
+    pub fn get_line(&self) -> usize { self.line }
+
+    #[inline]
+    pub fn get_message(&self) -> &str { &self.message }
+}
+
+impl fmt::Display for WorkloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for WorkloadError {}
+
+pub struct Workload {
+    pub tasks: Vec<TaskChar>,
+    pub devices: Vec<Device>,
+    pub sync_objects: Vec<SyncObject>,
+}
+
+// loads a structured workload from .toml, .json or .yaml files, replays recorded
+// scheduler traces and reads the legacy column format otherwise
+pub fn load(path: &Path) -> Result<Workload, WorkloadError> {
+    let src = fs::read_to_string(path)
+        .map_err(|err| WorkloadError::new(0, &format!("Could not read {}: {}", path.display(), err)))?;
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => spec::parse_workload(&src),
+        Some("json") => spec::parse_json_workload(&src),
+        Some("yaml") | Some("yml") => spec::parse_yaml_workload(&src),
+        _ if replay::is_trace(&src) => Ok(Workload { tasks: replay::parse(&src)?, devices: Vec::new(), sync_objects: Vec::new() }),
+        _ => Ok(Workload { tasks: legacy::parse(&src)?, devices: Vec::new(), sync_objects: Vec::new() })
+    }
+}
@@ -0,0 +1,264 @@
+// recorded scheduler traces, ftrace text or perf sched script output, read
+// back as a workload: every task that ran gets a script of the cpu bursts
+// and sleeps it was seen doing, starting at the time it was first seen
+
+use super::WorkloadError;
+use crate::proc::dist::Distribution;
+use crate::proc::phase::Phase;
+use crate::proc::task::TaskChar;
+use crate::sched::weight::{nice_to_weight, MAX_NICE, MIN_NICE};
+use crate::trace::TICK_US;
+
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum State {
+    Running(u64),
+    Runnable,
+    Blocked(u64),
+    Exited,
+}
+
+struct Replayed {
+    id: u16,
+    name: String,
+    prio: i32,
+    arrival: u64,
+    state: State,
+    // cpu time since the task last blocked
+    burst: u64,
+    // bursts and sleeps in microseconds
+    phases: Vec<(bool, u64)>,
+}
+
+impl Replayed {
+    fn new(id: u16, name: &str, prio: i32, arrival: u64) -> Self {
+        Self { id, name: name.to_string(), prio, arrival, state: State::Runnable, burst: 0, phases: Vec::new() }
+    }
+
+    fn switch_in(&mut self, now: u64) {
+        // a wakeup that was not traced, the task was asleep until now
+        if let State::Blocked(since) = self.state {
+            self.phases.push((false, now - since));
+        }
+        self.state = State::Running(now);
+    }
+
+    fn switch_out(&mut self, now: u64, prev_state: &str) {
+        let since = match self.state {
+            State::Running(since) => since,
+            State::Exited => return,
+            // on the cpu since before the trace started
+            _ => self.arrival
+        };
+        self.burst += now - since;
+
+        if prev_state.starts_with('R') {
+            self.state = State::Runnable;
+            return;
+        }
+        self.phases.push((true, self.burst));
+        self.burst = 0;
+        self.state = match prev_state.chars().next() {
+            Some('X') | Some('Z') => State::Exited,
+            _ => State::Blocked(now)
+        };
+    }
+
+    fn wake(&mut self, now: u64) {
+        if let State::Blocked(since) = self.state {
+            self.phases.push((false, now - since));
+            self.state = State::Runnable;
+        }
+    }
+
+    // also ends the tasks still around at the end of the trace
+    fn exit(&mut self, now: u64) {
+        if let State::Running(since) = self.state {
+            self.burst += now - since;
+        }
+        if self.burst > 0 {
+            self.phases.push((true, self.burst));
+            self.burst = 0;
+        }
+        self.state = State::Exited;
+    }
+
+    fn to_task(&self, origin: u64, tick_us: u64) -> Option<TaskChar> {
+        let ticks = |us: u64| (us + tick_us / 2) / tick_us;
+        let mut script: Vec<Phase> = Vec::new();
+        for (compute, us) in self.phases.iter() {
+            if *compute {
+                // a burst too short to see still took the cpu
+                let ticks = ticks(*us).max(1);
+                match script.last_mut() {
+                    Some(Phase::Compute(Distribution::Constant(last))) => *last += ticks,
+                    _ => script.push(Phase::Compute(Distribution::Constant(ticks)))
+                };
+            } else if ticks(*us) > 0 && !script.is_empty() {
+                script.push(Phase::Sleep(Distribution::Constant(ticks(*us))));
+            }
+        }
+        // a task that never ran, or only slept at the end of the trace
+        while let Some(Phase::Sleep(_)) = script.last() {
+            script.pop();
+        }
+        if script.is_empty() {
+            return None;
+        }
+
+        let nice = (self.prio - 120).clamp(MIN_NICE, MAX_NICE);
+        let mut task = TaskChar::new(self.id, u64::MAX, 0, 0, nice_to_weight(nice));
+        task.set_name(&self.name);
+        task.set_arrival(ticks(self.arrival - origin) as u128);
+        task.set_phases(script);
+        Some(task)
+    }
+}
+
+// the values of the given keys, in order, a value running up to the next key
+// so that names with spaces in them are kept whole, the last one up to a space
+fn fields<'a>(text: &'a str, keys: &[&str]) -> Option<Vec<&'a str>> {
+    let mut starts = Vec::new();
+    let mut from = 0;
+    for key in keys {
+        let at = from + text[from..].find(key)?;
+        starts.push((at, at + key.len()));
+        from = at + key.len();
+    }
+
+    let mut values = Vec::new();
+    for (idx, (_, start)) in starts.iter().enumerate() {
+        let end = match starts.get(idx + 1) {
+            Some((next, _)) => *next,
+            None => text[*start..].find(char::is_whitespace).map_or(text.len(), |end| start + end)
+        };
+        values.push(text[*start..end].trim());
+    }
+    Some(values)
+}
+
+// a timestamp in seconds, such as 1234.567890, in microseconds
+fn timestamp(token: &str) -> Option<u64> {
+    let token = token.trim_end_matches(':');
+    let (secs, frac) = token.split_once('.')?;
+    if frac.is_empty() || frac.len() > 9 || !frac.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let frac = format!("{:0<6}", frac);
+    Some(secs.parse::<u64>().ok()? * 1_000_000 + frac[..6].parse::<u64>().ok()?)
+}
+
+const EVENTS: [&str; 4] = ["sched_switch:", "sched_wakeup:", "sched_wakeup_new:", "sched_process_exit:"];
+
+#[derive(Default)]
+struct Importer {
+    tasks: Vec<Replayed>,
+    ids: HashMap<u64, usize>,
+}
+
+impl Importer {
+    // the task with the given pid, None for the idle task
+    fn task(&mut self, pid: i64, comm: &str, prio: i64, now: u64, line: usize) -> Result<Option<usize>, WorkloadError> {
+        if pid <= 0 {
+            return Ok(None);
+        }
+        if let Some(idx) = self.ids.get(&(pid as u64)) {
+            self.tasks[*idx].prio = prio as i32;
+            return Ok(Some(*idx));
+        }
+        if self.tasks.len() >= u16::MAX as usize {
+            return Err(WorkloadError::new(line, "too many tasks"));
+        }
+        self.tasks.push(Replayed::new(self.tasks.len() as u16 + 1, comm, prio as i32, now));
+        self.ids.insert(pid as u64, self.tasks.len() - 1);
+        Ok(Some(self.tasks.len() - 1))
+    }
+}
+
+pub fn parse(src: &str) -> Result<Vec<TaskChar>, WorkloadError> {
+    parse_with_tick(src, TICK_US as u64)
+}
+
+// reads the trace with a tick of the given number of microseconds
+pub fn parse_with_tick(src: &str, tick_us: u64) -> Result<Vec<TaskChar>, WorkloadError> {
+    if tick_us == 0 {
+        return Err(WorkloadError::new(0, "a tick needs to be at least a microsecond"));
+    }
+    let mut importer = Importer::default();
+    let mut origin = None;
+    let mut last = 0;
+    let mut nr_switches = 0;
+
+    for (idx, text) in src.lines().enumerate() {
+        let line = idx + 1;
+        if text.trim_start().starts_with('#') {
+            continue;
+        }
+        // perf sched script writes sched:sched_switch
+        let found = EVENTS.iter().find_map(|event| {
+            let at = text.find(&format!("{} ", event))?;
+            let before = text[..at].strip_suffix("sched:").unwrap_or(&text[..at]);
+            before.ends_with(' ').then_some((*event, at, before))
+        });
+        let (event, at, before) = match found {
+            Some(found) => found,
+            None => continue
+        };
+        let now = before.split_whitespace().rev()
+            .find_map(timestamp)
+            .ok_or_else(|| WorkloadError::new(line, "missing timestamp"))?;
+        let origin = *origin.get_or_insert(now);
+        if now < last {
+            return Err(WorkloadError::new(line, "timestamps go back in time"));
+        }
+        last = now;
+
+        let rest = &text[at + event.len() + 1..];
+        let malformed = || WorkloadError::new(line, &format!("malformed {} event", event.trim_end_matches(':')));
+        let number = |value: &str| value.parse::<i64>().map_err(|_| malformed());
+
+        if event == "sched_switch:" {
+            let keys = ["prev_comm=", " prev_pid=", " prev_prio=", " prev_state=", " ==> next_comm=", " next_pid=", " next_prio="];
+            let values = fields(rest, &keys).ok_or_else(malformed)?;
+            let nr_tasks = importer.tasks.len();
+            let prev = importer.task(number(values[1])?, values[0], number(values[2])?, now, line)?;
+            if let Some(prev) = prev {
+                // on the cpu since before the trace started
+                if importer.tasks.len() > nr_tasks {
+                    importer.tasks[prev].arrival = origin;
+                }
+                importer.tasks[prev].switch_out(now, values[3]);
+            }
+            if let Some(next) = importer.task(number(values[5])?, values[4], number(values[6])?, now, line)? {
+                importer.tasks[next].switch_in(now);
+            }
+            nr_switches += 1;
+            continue;
+        }
+
+        let values = fields(rest, &["comm=", " pid=", " prio="]).ok_or_else(malformed)?;
+        if let Some(idx) = importer.task(number(values[1])?, values[0], number(values[2])?, now, line)? {
+            match event {
+                "sched_process_exit:" => importer.tasks[idx].exit(now),
+                _ => importer.tasks[idx].wake(now)
+            };
+        }
+    }
+
+    if nr_switches == 0 {
+        return Err(WorkloadError::new(0, "no sched_switch events found"));
+    }
+    let origin = origin.unwrap();
+    Ok(importer.tasks.iter_mut()
+        .filter_map(|task| {
+            task.exit(last);
+            task.to_task(origin, tick_us)
+        })
+        .collect())
+}
+
+// whether the source looks like a scheduler trace rather than a task list
+pub fn is_trace(src: &str) -> bool {
+    src.lines().any(|line| line.contains(" sched_switch: ") || line.contains(" sched:sched_switch: "))
+}
@@ -0,0 +1,852 @@
+// structured toml workloads, one [[task]] table per task:
+//
+// [[task]]
+// id = 1
+// name = "cc1"
+// arrival = 10
+// cpu_time = 500
+// cpu_burst = 20
+// io_burst = 5
+// nice = 0              # or weight = 10, but not both
+// policy = "batch"
+// affinity = [0]
+// group = 2
+// actions = [{ at = 100, action = "sleep", ticks = 30 }]
+//
+// bursts are either a fixed length or drawn from a distribution:
+//
+// cpu_burst = { dist = "uniform", min = 5, max = 40 }
+// cpu_burst = { dist = "exponential", mean = 20 }
+// cpu_burst = { dist = "lognormal", mu = 3.0, sigma = 0.5 }
+// io_burst = { dist = "pareto", scale = 2, shape = 1.5 }
+// io_burst = { dist = "empirical", bins = [[1, 90], [200, 10]] }
+//
+// or the task follows a script of phases instead of bursts, in which
+// case cpu_time is optional unless the script loops forever:
+//
+// phases = [
+//   { compute = 500 },
+//   { repeat = 10, phases = [{ sleep = 20 }, { compute = 30 }] },
+//   { nice = 10 },
+//   { policy = "batch" },
+//   { compute = 5000 },
+// ]
+//
+// a periodic task releases a job every period, each job running the
+// task's bursts or phases with wcet ticks of cpu time, cpu_time is unused:
+//
+// period = 100
+// offset = 10           # first release after arrival, defaults to 0
+// wcet = 20
+// jitter = 5            # releases are late by up to this much, defaults to 0
+// jobs = 50
+//
+// phases can also fork children, exec a new image, wait for the
+// children to exit or exit early:
+//
+// phases = [
+//   { repeat = 4, phases = [
+//     { fork = [{ exec = "cc1", phases = [{ compute = 200 }, { io = 5 }] }] },
+//   ] },
+//   { wait = true },
+//   { compute = 50 },
+//   { exit = true },
+// ]
+//
+// shared i/o devices get a [[device]] table each, tasks then block on
+// them with read and write phases, or send all of their io bursts to one
+// with io_device, in which case the device's service time is used:
+//
+// [[device]]
+// name = "disk"
+// service = { dist = "uniform", min = 5, max = 15 }
+// policy = "deadline"   # or "fifo", the default
+// read_expire = 50      # deadline policy only, these are the defaults
+// write_expire = 500
+// depth = 1             # requests served at once
+//
+// phases = [{ compute = 10 }, { read = "disk" }, { write = "disk" }]
+// io_device = "disk"
+//
+// locks and wait queues shared by the tasks get a [[sync]] table each:
+//
+// [[sync]]
+// name = "buffer"
+// kind = "mutex"        # or "semaphore", "condvar" or "waitqueue"
+// count = 4             # semaphores only, defaults to 1
+//
+// phases = [
+//   { lock = "buffer" },
+//   { wait_on = "nonempty", mutex = "buffer" },  # wait queues take no mutex
+//   { signal = "nonfull" },                      # or broadcast to wake every waiter
+//   { unlock = "buffer" },
+// ]
+//
+// the same tables can be written as json or yaml, a top level object with
+// task, device and sync arrays, which is checked as the toml it translates to
+
+use super::{Workload, WorkloadError};
+use crate::io::device::{Device, IoKind, IoPolicy};
+use crate::proc::dist::Distribution;
+use crate::proc::periodic::Periodic;
+use crate::proc::phase::Phase;
+use crate::proc::task::{Action, SchedPolicy, TaskChar};
+use crate::sched::weight::{nice_to_weight, MAX_NICE, MIN_NICE};
+use crate::sched::NR_CPUS;
+use crate::sync::{SyncKind, SyncObject};
+
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use toml::Spanned;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawWorkload {
+    #[serde(default)]
+    device: Vec<RawDevice>,
+    #[serde(default)]
+    sync: Vec<RawSync>,
+    #[serde(default)]
+    task: Vec<RawTask>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSync {
+    name: Spanned<String>,
+    kind: Spanned<String>,
+    count: Option<Spanned<u32>>,
+}
+
+// the shared objects tasks can refer to by name
+struct Shared {
+    devices: HashSet<String>,
+    sync: HashMap<String, SyncKind>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawDevice {
+    name: Spanned<String>,
+    service: Spanned<toml::Value>,
+    policy: Option<Spanned<String>>,
+    read_expire: Option<u64>,
+    write_expire: Option<u64>,
+    depth: Option<Spanned<usize>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTask {
+    id: Spanned<u16>,
+    name: Option<String>,
+    arrival: Option<u64>,
+    cpu_time: Option<Spanned<u64>>,
+    cpu_burst: Option<Spanned<toml::Value>>,
+    io_burst: Option<Spanned<toml::Value>>,
+    io_device: Option<Spanned<String>>,
+    phases: Option<Vec<Spanned<RawPhase>>>,
+    period: Option<Spanned<u64>>,
+    offset: Option<Spanned<u64>>,
+    wcet: Option<Spanned<u64>>,
+    jitter: Option<Spanned<u64>>,
+    jobs: Option<Spanned<u64>>,
+    weight: Option<Spanned<u32>>,
+    nice: Option<Spanned<i32>>,
+    policy: Option<Spanned<String>>,
+    affinity: Option<Spanned<Vec<u32>>>,
+    group: Option<u32>,
+    #[serde(default)]
+    actions: Vec<Spanned<RawAction>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawDist {
+    dist: String,
+    value: Option<u64>,
+    min: Option<u64>,
+    max: Option<u64>,
+    mean: Option<f64>,
+    mu: Option<f64>,
+    sigma: Option<f64>,
+    scale: Option<f64>,
+    shape: Option<f64>,
+    bins: Option<Vec<(u64, u64)>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPhase {
+    compute: Option<Spanned<toml::Value>>,
+    io: Option<Spanned<toml::Value>>,
+    sleep: Option<Spanned<toml::Value>>,
+    read: Option<String>,
+    write: Option<String>,
+    lock: Option<String>,
+    unlock: Option<String>,
+    wait_on: Option<String>,
+    mutex: Option<String>,
+    signal: Option<String>,
+    broadcast: Option<String>,
+    nice: Option<i32>,
+    weight: Option<u32>,
+    policy: Option<String>,
+    repeat: Option<Spanned<toml::Value>>,
+    fork: Option<Vec<Spanned<RawPhase>>>,
+    exec: Option<String>,
+    wait: Option<bool>,
+    exit: Option<bool>,
+    phases: Option<Vec<Spanned<RawPhase>>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawAction {
+    at: u64,
+    action: String,
+    ticks: Option<u64>,
+}
+
+fn error<T>(src: &str, value: &Spanned<T>, message: &str) -> WorkloadError {
+    WorkloadError::at(src, value.span().start, message)
+}
+
+fn distribution(src: &str, raw: &Spanned<toml::Value>) -> Result<Distribution, WorkloadError> {
+    let table = match raw.get_ref() {
+        toml::Value::Integer(value) if *value >= 0 => return Ok(Distribution::Constant(*value as u64)),
+        toml::Value::Table(_) => raw.get_ref().clone(),
+        _ => return Err(error(src, raw, "expected a burst length or a distribution table"))
+    };
+    let dist: RawDist = table.try_into().map_err(|err: toml::de::Error| error(src, raw, err.message()))?;
+
+    let missing = |params: &str| error(src, raw, &format!("{} bursts need {}", dist.dist, params));
+    let parsed = match dist.dist.as_str() {
+        "constant" => match dist.value {
+            Some(value) => Distribution::Constant(value),
+            None => return Err(missing("a value"))
+        },
+        "uniform" => match dist.min.zip(dist.max) {
+            Some((min, max)) => Distribution::Uniform { min, max },
+            None => return Err(missing("a min and a max"))
+        },
+        "exponential" => match dist.mean {
+            Some(mean) => Distribution::Exponential { mean },
+            None => return Err(missing("a mean"))
+        },
+        "lognormal" => match dist.mu.zip(dist.sigma) {
+            Some((mu, sigma)) => Distribution::LogNormal { mu, sigma },
+            None => return Err(missing("a mu and a sigma"))
+        },
+        "pareto" => match dist.scale.zip(dist.shape) {
+            Some((scale, shape)) => Distribution::Pareto { scale, shape },
+            None => return Err(missing("a scale and a shape"))
+        },
+        "empirical" => match dist.bins {
+            Some(bins) => Distribution::Empirical(bins),
+            None => return Err(missing("bins of [length, count] pairs"))
+        },
+        other => return Err(error(src, raw, &format!("unknown distribution {:?}", other)))
+    };
+    parsed.validate().map_err(|err| error(src, raw, &err))?;
+
+    Ok(parsed)
+}
+
+fn phases(src: &str, raw: &[Spanned<RawPhase>], shared: &Shared) -> Result<Vec<Phase>, WorkloadError> {
+    let mut parsed = Vec::new();
+
+    for spanned in raw.iter() {
+        let inner = spanned.get_ref();
+        let kinds = [
+            inner.compute.is_some(),
+            inner.io.is_some(),
+            inner.sleep.is_some(),
+            inner.read.is_some(),
+            inner.write.is_some(),
+            inner.lock.is_some(),
+            inner.unlock.is_some(),
+            inner.wait_on.is_some(),
+            inner.signal.is_some(),
+            inner.broadcast.is_some(),
+            inner.nice.is_some(),
+            inner.weight.is_some(),
+            inner.policy.is_some(),
+            inner.repeat.is_some(),
+            inner.fork.is_some(),
+            inner.exec.is_some(),
+            inner.wait.is_some(),
+            inner.exit.is_some()
+        ];
+        if kinds.iter().filter(|kind| **kind).count() != 1 {
+            return Err(error(
+                src,
+                spanned,
+                "a phase is exactly one of compute, io, sleep, read, write, lock, unlock, wait_on, signal, broadcast, nice, weight, policy, repeat, fork, exec, wait or exit"
+            ));
+        }
+        if inner.phases.is_some() != (inner.repeat.is_some() || inner.exec.is_some()) {
+            return Err(error(src, spanned, "repeat and exec phases come with the phases to run"));
+        }
+        if inner.mutex.is_some() && inner.wait_on.is_none() {
+            return Err(error(src, spanned, "only wait_on phases take a mutex"));
+        }
+        if inner.wait == Some(false) || inner.exit == Some(false) {
+            return Err(error(src, spanned, "wait and exit phases are written as `wait = true` and `exit = true`"));
+        }
+
+        let phase = if let Some(dist) = &inner.compute {
+            Phase::Compute(distribution(src, dist)?)
+        } else if let Some(dist) = &inner.io {
+            Phase::Io(distribution(src, dist)?)
+        } else if let Some(dist) = &inner.sleep {
+            Phase::Sleep(distribution(src, dist)?)
+        } else if let Some(device) = inner.read.as_ref().or(inner.write.as_ref()) {
+            if !shared.devices.contains(device) {
+                return Err(error(src, spanned, &format!("unknown device {:?}", device)));
+            }
+            let kind = if inner.read.is_some() { IoKind::Read } else { IoKind::Write };
+            Phase::Request(device.clone(), kind)
+        } else if let Some(name) = inner.lock.as_ref().or(inner.unlock.as_ref()) {
+            if !shared.sync.get(name).is_some_and(|kind| kind.is_lock()) {
+                return Err(error(src, spanned, &format!("{:?} is not a mutex or a semaphore", name)));
+            }
+            if inner.lock.is_some() { Phase::Lock(name.clone()) } else { Phase::Unlock(name.clone()) }
+        } else if let Some(name) = &inner.wait_on {
+            match (shared.sync.get(name), &inner.mutex) {
+                (Some(SyncKind::CondVar), None) => {
+                    return Err(error(src, spanned, &format!("waiting on condition variable {:?} needs a mutex", name)));
+                },
+                (Some(SyncKind::WaitQueue), Some(_)) => {
+                    return Err(error(src, spanned, &format!("waiting on wait queue {:?} takes no mutex", name)));
+                },
+                (Some(SyncKind::CondVar), Some(_)) | (Some(SyncKind::WaitQueue), None) => {},
+                _ => return Err(error(src, spanned, &format!("{:?} is not a condition variable or a wait queue", name)))
+            };
+            if let Some(mutex) = &inner.mutex {
+                if shared.sync.get(mutex) != Some(&SyncKind::Mutex) {
+                    return Err(error(src, spanned, &format!("{:?} is not a mutex", mutex)));
+                }
+            }
+            Phase::WaitOn(name.clone(), inner.mutex.clone())
+        } else if let Some(name) = inner.signal.as_ref().or(inner.broadcast.as_ref()) {
+            if shared.sync.get(name).is_none_or(|kind| kind.is_lock()) {
+                return Err(error(src, spanned, &format!("{:?} is not a condition variable or a wait queue", name)));
+            }
+            if inner.signal.is_some() { Phase::Signal(name.clone()) } else { Phase::Broadcast(name.clone()) }
+        } else if let Some(nice) = inner.nice {
+            Phase::SetNice(nice)
+        } else if let Some(weight) = inner.weight {
+            Phase::SetWeight(weight)
+        } else if let Some(policy) = &inner.policy {
+            Phase::SetPolicy(policy.parse::<SchedPolicy>().map_err(|err| error(src, spanned, &err))?)
+        } else if let Some(child) = &inner.fork {
+            Phase::Fork(phases(src, child, shared)?)
+        } else if let Some(name) = &inner.exec {
+            Phase::Exec(name.clone(), phases(src, inner.phases.as_ref().unwrap(), shared)?)
+        } else if inner.wait.is_some() {
+            Phase::Wait
+        } else if inner.exit.is_some() {
+            Phase::Exit
+        } else {
+            let repeat = inner.repeat.as_ref().unwrap();
+            let times = match repeat.get_ref() {
+                toml::Value::Integer(times) if *times >= 0 => Some(*times as u64),
+                toml::Value::String(forever) if forever == "forever" => None,
+                _ => return Err(error(src, repeat, "repeat takes a count or \"forever\""))
+            };
+            Phase::Repeat(times, phases(src, inner.phases.as_ref().unwrap(), shared)?)
+        };
+        Phase::validate(std::slice::from_ref(&phase)).map_err(|err| error(src, spanned, &err))?;
+
+        parsed.push(phase);
+    }
+
+    Ok(parsed)
+}
+
+fn periodic(src: &str, raw: &RawTask) -> Result<Option<Periodic>, WorkloadError> {
+    let period = match &raw.period {
+        Some(period) => period,
+        None => {
+            let stray = [&raw.offset, &raw.wcet, &raw.jitter, &raw.jobs];
+            if let Some(field) = stray.iter().find_map(|field| field.as_ref()) {
+                return Err(error(src, field, "only periodic tasks take an offset, wcet, jitter or jobs"));
+            }
+            return Ok(None);
+        }
+    };
+    let wcet = raw.wcet.as_ref().ok_or_else(|| error(src, period, "a periodic task needs a wcet"))?;
+    let jobs = raw.jobs.as_ref().ok_or_else(|| error(src, period, "a periodic task needs a number of jobs"))?;
+
+    let periodic = Periodic {
+        period: *period.get_ref(),
+        offset: raw.offset.as_ref().map(|offset| *offset.get_ref()).unwrap_or(0),
+        wcet: *wcet.get_ref(),
+        jitter: raw.jitter.as_ref().map(|jitter| *jitter.get_ref()).unwrap_or(0),
+        jobs: *jobs.get_ref()
+    };
+    periodic.validate().map_err(|err| error(src, period, &err))?;
+
+    Ok(Some(periodic))
+}
+
+fn action(src: &str, raw: &Spanned<RawAction>) -> Result<(u64, Action), WorkloadError> {
+    let inner = raw.get_ref();
+    let action = match (inner.action.as_str(), inner.ticks) {
+        ("yield", None) => Action::Yield,
+        ("cond_resched", None) => Action::CondResched,
+        ("sleep", Some(ticks)) => Action::Sleep(ticks),
+        ("sleep", None) => return Err(error(src, raw, "sleep actions need a number of ticks")),
+        ("yield", Some(_)) | ("cond_resched", Some(_)) => {
+            return Err(error(src, raw, &format!("{} actions take no ticks", inner.action)));
+        },
+        (other, _) => return Err(error(src, raw, &format!("unknown action {:?}", other)))
+    };
+
+    Ok((inner.at, action))
+}
+
+fn device(src: &str, raw: &RawDevice) -> Result<Device, WorkloadError> {
+    let name = raw.name.get_ref();
+    if name.is_empty() {
+        return Err(error(src, &raw.name, "a device needs a name"));
+    }
+    let policy = match &raw.policy {
+        Some(policy) => policy.get_ref().parse::<IoPolicy>().map_err(|err| error(src, policy, &err))?,
+        None => IoPolicy::Fifo
+    };
+
+    let mut device = Device::new(name, distribution(src, &raw.service)?, policy);
+    if let Some(depth) = &raw.depth {
+        if *depth.get_ref() == 0 {
+            return Err(error(src, depth, "depth must be positive"));
+        }
+        device.set_depth(*depth.get_ref());
+    }
+    if let Some(ticks) = raw.read_expire {
+        device.set_read_expire(ticks);
+    }
+    if let Some(ticks) = raw.write_expire {
+        device.set_write_expire(ticks);
+    }
+
+    Ok(device)
+}
+
+fn sync_object(src: &str, raw: &RawSync) -> Result<SyncObject, WorkloadError> {
+    let name = raw.name.get_ref();
+    if name.is_empty() {
+        return Err(error(src, &raw.name, "a synchronisation object needs a name"));
+    }
+    let kind = match (raw.kind.get_ref().parse::<SyncKind>().map_err(|err| error(src, &raw.kind, &err))?, &raw.count) {
+        (SyncKind::Semaphore(_), Some(count)) => SyncKind::Semaphore(*count.get_ref()),
+        (_, Some(count)) => return Err(error(src, count, "only semaphores take a count")),
+        (kind, None) => kind
+    };
+
+    Ok(SyncObject::new(name, kind))
+}
+
+// follows the mutexes the task holds through its phases, so that it never
+// locks one it already holds nor unlocks or waits with one it doesn't
+fn mutexes(
+    src: &str,
+    raw: &[Spanned<RawPhase>],
+    parsed: &[Phase],
+    shared: &Shared,
+    held: &mut Vec<String>
+) -> Result<(), WorkloadError> {
+    let is_mutex = |name: &String| shared.sync.get(name) == Some(&SyncKind::Mutex);
+
+    for (spanned, phase) in raw.iter().zip(parsed.iter()) {
+        let inner = spanned.get_ref();
+        match phase {
+            Phase::Lock(name) if is_mutex(name) => {
+                if held.contains(name) {
+                    return Err(error(src, spanned, &format!("mutex {:?} is already held here", name)));
+                }
+                held.push(name.clone());
+            },
+            Phase::Unlock(name) if is_mutex(name) => {
+                match held.iter().position(|mutex| mutex == name) {
+                    Some(idx) => { held.remove(idx); },
+                    None => return Err(error(src, spanned, &format!("mutex {:?} is not held here", name)))
+                };
+            },
+            Phase::WaitOn(_, Some(mutex)) if !held.contains(mutex) => {
+                return Err(error(src, spanned, &format!("mutex {:?} is not held here", mutex)));
+            },
+            Phase::Repeat(times, body) if *times != Some(0) => {
+                let mut after = held.clone();
+                mutexes(src, inner.phases.as_ref().unwrap(), body, shared, &mut after)?;
+                after.sort();
+                let mut before = held.clone();
+                before.sort();
+                if *times != Some(1) && after != before {
+                    return Err(error(src, spanned, "a repeat has to leave the same mutexes held as it found"));
+                }
+                *held = after;
+            },
+            // the child holds none of its parent's locks
+            Phase::Fork(body) => mutexes(src, inner.fork.as_ref().unwrap(), body, shared, &mut Vec::new())?,
+            // nothing after these runs
+            Phase::Exec(_, body) => return mutexes(src, inner.phases.as_ref().unwrap(), body, shared, held),
+            Phase::Exit => return Ok(()),
+            _ => {}
+        };
+    }
+
+    Ok(())
+}
+
+fn task(src: &str, raw: &RawTask, shared: &Shared) -> Result<TaskChar, WorkloadError> {
+    let id = *raw.id.get_ref();
+    if id == 0 {
+        return Err(error(src, &raw.id, "task ids start at 1"));
+    }
+    if let Some(cpu_time) = &raw.cpu_time {
+        if *cpu_time.get_ref() == 0 {
+            return Err(error(src, cpu_time, "cpu_time must be positive"));
+        }
+    }
+
+    let script = match &raw.phases {
+        Some(raw_phases) => {
+            if let Some(burst) = raw.cpu_burst.as_ref().or(raw.io_burst.as_ref()) {
+                return Err(error(src, burst, "a task has either bursts or phases"));
+            }
+            if let Some(io_device) = &raw.io_device {
+                return Err(error(src, io_device, "io_device only applies to io bursts, use read and write phases"));
+            }
+            let parsed = phases(src, raw_phases, shared)?;
+            mutexes(src, raw_phases, &parsed, shared, &mut Vec::new())?;
+            if raw.cpu_time.is_none() && Phase::is_endless(&parsed) {
+                return Err(error(src, &raw.id, "a task whose phases repeat forever needs a cpu_time"));
+            }
+            Some(parsed)
+        },
+        None => None
+    };
+    let periodic = periodic(src, raw)?;
+    let cpu_time = match (&raw.cpu_time, &periodic) {
+        (Some(cpu_time), Some(_)) => return Err(error(src, cpu_time, "periodic tasks have a wcet instead of a cpu_time")),
+        (Some(cpu_time), None) => *cpu_time.get_ref(),
+        (None, Some(periodic)) => periodic.wcet,
+        (None, None) if script.is_some() => u64::MAX,
+        (None, None) => return Err(error(src, &raw.id, "missing field `cpu_time`"))
+    };
+    let cpu_burst = match (&raw.cpu_burst, &script) {
+        (Some(cpu_burst), _) => {
+            let dist = distribution(src, cpu_burst)?;
+            if dist == Distribution::Constant(0) {
+                return Err(error(src, cpu_burst, "cpu_burst must be positive"));
+            }
+            dist
+        },
+        (None, Some(_)) => Distribution::Constant(1),
+        (None, None) if periodic.is_some() => Distribution::Constant(cpu_time),
+        (None, None) => return Err(error(src, &raw.id, "missing field `cpu_burst`"))
+    };
+    let io_burst = match &raw.io_burst {
+        Some(io_burst) => distribution(src, io_burst)?,
+        None => Distribution::Constant(0)
+    };
+
+    let weight = match (&raw.weight, &raw.nice) {
+        (Some(_), Some(nice)) => return Err(error(src, nice, "a task has either a weight or a nice value")),
+        (Some(weight), None) => {
+            if *weight.get_ref() == 0 {
+                return Err(error(src, weight, "weight must be positive"));
+            }
+            *weight.get_ref()
+        },
+        (None, Some(nice)) => {
+            if !(MIN_NICE..=MAX_NICE).contains(nice.get_ref()) {
+                return Err(error(src, nice, &format!("nice must be within [{}, {}]", MIN_NICE, MAX_NICE)));
+            }
+            nice_to_weight(*nice.get_ref())
+        },
+        (None, None) => nice_to_weight(0)
+    };
+
+    let mut task = TaskChar::new(id, cpu_time, 0, 0, weight);
+    task.set_cpu_burst(cpu_burst);
+    task.set_io_burst(io_burst);
+    if let Some(script) = script {
+        task.set_phases(script);
+    }
+    if let Some(io_device) = &raw.io_device {
+        if !shared.devices.contains(io_device.get_ref()) {
+            return Err(error(src, io_device, &format!("unknown device {:?}", io_device.get_ref())));
+        }
+        task.set_io_device(io_device.get_ref());
+    }
+    if let Some(periodic) = periodic {
+        task.set_periodic(periodic);
+    }
+
+    if let Some(name) = &raw.name {
+        task.set_name(name);
+    }
+    if let Some(arrival) = raw.arrival {
+        task.set_arrival(arrival as u128);
+    }
+    if let Some(policy) = &raw.policy {
+        let parsed = policy.get_ref()
+            .parse::<SchedPolicy>()
+            .map_err(|err| error(src, policy, &err))?;
+        task.set_policy(parsed);
+    }
+    if let Some(affinity) = &raw.affinity {
+        let mut mask = 0;
+        for cpu in affinity.get_ref() {
+            // a cpu that isn't simulated would be dropped from the mask without a word
+            if *cpu >= NR_CPUS {
+                return Err(error(src, affinity, &format!("cpu {} is out of range, NR_CPUS is {}", cpu, NR_CPUS)));
+            }
+            mask |= 1 << cpu;
+        }
+        if mask == 0 {
+            return Err(error(src, affinity, "affinity needs at least one cpu"));
+        }
+        task.set_affinity(mask);
+    }
+    if let Some(group) = raw.group {
+        task.set_session(group);
+    }
+    for raw_action in raw.actions.iter() {
+        let (at, action) = action(src, raw_action)?;
+        task.add_action(at, action);
+    }
+
+    Ok(task)
+}
+
+pub fn parse(src: &str) -> Result<Vec<TaskChar>, WorkloadError> {
+    Ok(parse_workload(src)?.tasks)
+}
+
+// the tasks along with the devices they use
+pub fn parse_workload(src: &str) -> Result<Workload, WorkloadError> {
+    let workload: RawWorkload = toml::from_str(src).map_err(|err| match err.span() {
+        Some(span) => WorkloadError::at(src, span.start, err.message()),
+        None => WorkloadError::new(0, err.message())
+    })?;
+
+    let mut names = HashSet::new();
+    let mut devices = Vec::new();
+    for raw in workload.device.iter() {
+        if !names.insert(raw.name.get_ref().clone()) {
+            return Err(error(src, &raw.name, &format!("duplicate device {:?}", raw.name.get_ref())));
+        }
+        devices.push(device(src, raw)?);
+    }
+
+    let mut sync_objects = Vec::new();
+    let mut kinds = HashMap::new();
+    for raw in workload.sync.iter() {
+        let object = sync_object(src, raw)?;
+        if kinds.insert(object.get_name().to_string(), object.get_kind()).is_some() {
+            return Err(error(src, &raw.name, &format!("duplicate synchronisation object {:?}", raw.name.get_ref())));
+        }
+        sync_objects.push(object);
+    }
+    let shared = Shared { devices: names, sync: kinds };
+
+    let mut ids = HashSet::new();
+    let mut tasks = Vec::new();
+    for raw in workload.task.iter() {
+        if !ids.insert(*raw.id.get_ref()) {
+            return Err(error(src, &raw.id, &format!("duplicate task id {}", raw.id.get_ref())));
+        }
+        tasks.push(task(src, raw, &shared)?);
+    }
+
+    Ok(Workload { tasks, devices, sync_objects })
+}
+
+// the message of a json or yaml syntax error without the position, which
+// the line of the error already gives
+fn syntax_error(message: String, line: usize) -> WorkloadError {
+    let message = message.split(" at line ").next().unwrap_or_default();
+    WorkloadError::new(line, message)
+}
+
+// checks a json or yaml workload as toml, whose lines mean nothing to the
+// author, so errors name the [[task]], [[device]] or [[sync]] table instead
+fn parse_translated(value: toml::Value) -> Result<Workload, WorkloadError> {
+    let src = toml::to_string(&value).map_err(|err| WorkloadError::new(0, &err.to_string()))?;
+    parse_workload(&src).map_err(|err| {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut table = None;
+        for text in src.lines().take(err.get_line()) {
+            if let Some(name) = text.strip_prefix("[[").and_then(|text| text.strip_suffix("]]")) {
+                if !name.contains('.') {
+                    let count = counts.entry(name).or_insert(0);
+                    *count += 1;
+                    table = Some((name, *count));
+                }
+            }
+        }
+        match table {
+            Some((name, count)) => WorkloadError::new(0, &format!("{} {}: {}", name, count, err.get_message())),
+            None => WorkloadError::new(0, err.get_message())
+        }
+    })
+}
+
+pub fn parse_json_workload(src: &str) -> Result<Workload, WorkloadError> {
+    let value = serde_json::from_str(src).map_err(|err| syntax_error(err.to_string(), err.line()))?;
+    parse_translated(value)
+}
+
+pub fn parse_yaml_workload(src: &str) -> Result<Workload, WorkloadError> {
+    let value = serde_yaml::from_str(src).map_err(|err| {
+        let line = err.location().map_or(0, |location| location.line());
+        syntax_error(err.to_string(), line)
+    })?;
+    parse_translated(value)
+}
+
+fn distribution_toml(dist: &Distribution) -> String {
+    match dist {
+        Distribution::Constant(value) => value.to_string(),
+        Distribution::Uniform { min, max } => {
+            format!("{{ dist = \"uniform\", min = {}, max = {} }}", min, max)
+        },
+        Distribution::Exponential { mean } => {
+            format!("{{ dist = \"exponential\", mean = {:?} }}", mean)
+        },
+        Distribution::LogNormal { mu, sigma } => {
+            format!("{{ dist = \"lognormal\", mu = {:?}, sigma = {:?} }}", mu, sigma)
+        },
+        Distribution::Pareto { scale, shape } => {
+            format!("{{ dist = \"pareto\", scale = {:?}, shape = {:?} }}", scale, shape)
+        },
+        Distribution::Empirical(bins) => {
+            let bins: Vec<String> = bins.iter()
+                .map(|(value, count)| format!("[{}, {}]", value, count))
+                .collect();
+            format!("{{ dist = \"empirical\", bins = [{}] }}", bins.join(", "))
+        }
+    }
+}
+
+fn phase_toml(phase: &Phase) -> String {
+    match phase {
+        Phase::Compute(dist) => format!("{{ compute = {} }}", distribution_toml(dist)),
+        Phase::Io(dist) => format!("{{ io = {} }}", distribution_toml(dist)),
+        Phase::Sleep(dist) => format!("{{ sleep = {} }}", distribution_toml(dist)),
+        Phase::Request(device, IoKind::Read) => format!("{{ read = {} }}", toml::Value::String(device.clone())),
+        Phase::Request(device, IoKind::Write) => format!("{{ write = {} }}", toml::Value::String(device.clone())),
+        Phase::Lock(name) => format!("{{ lock = {} }}", toml::Value::String(name.clone())),
+        Phase::Unlock(name) => format!("{{ unlock = {} }}", toml::Value::String(name.clone())),
+        Phase::WaitOn(name, None) => format!("{{ wait_on = {} }}", toml::Value::String(name.clone())),
+        Phase::WaitOn(name, Some(mutex)) => format!(
+            "{{ wait_on = {}, mutex = {} }}",
+            toml::Value::String(name.clone()), toml::Value::String(mutex.clone())
+        ),
+        Phase::Signal(name) => format!("{{ signal = {} }}", toml::Value::String(name.clone())),
+        Phase::Broadcast(name) => format!("{{ broadcast = {} }}", toml::Value::String(name.clone())),
+        Phase::SetNice(nice) => format!("{{ nice = {} }}", nice),
+        Phase::SetWeight(weight) => format!("{{ weight = {} }}", weight),
+        Phase::SetPolicy(policy) => format!("{{ policy = {:?} }}", policy_name(*policy)),
+        Phase::Repeat(times, body) => {
+            let times = match times {
+                Some(times) => times.to_string(),
+                None => String::from("\"forever\"")
+            };
+            let body: Vec<String> = body.iter().map(phase_toml).collect();
+            format!("{{ repeat = {}, phases = [{}] }}", times, body.join(", "))
+        },
+        Phase::Fork(body) => {
+            let body: Vec<String> = body.iter().map(phase_toml).collect();
+            format!("{{ fork = [{}] }}", body.join(", "))
+        },
+        Phase::Exec(name, body) => {
+            let body: Vec<String> = body.iter().map(phase_toml).collect();
+            format!(
+                "{{ exec = {}, phases = [{}] }}",
+                toml::Value::String(name.clone()), body.join(", ")
+            )
+        },
+        Phase::Wait => String::from("{ wait = true }"),
+        Phase::Exit => String::from("{ exit = true }")
+    }
+}
+
+fn policy_name(policy: SchedPolicy) -> &'static str {
+    match policy {
+        SchedPolicy::Normal => "normal",
+        SchedPolicy::Batch => "batch",
+        SchedPolicy::Idle => "idle"
+    }
+}
+
+// writes tasks back out in the structured format
+pub fn to_toml(tasks: &[TaskChar]) -> String {
+    let mut out = String::new();
+
+    for (idx, task) in tasks.iter().enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        let _ = writeln!(out, "[[task]]");
+        let _ = writeln!(out, "id = {}", task.get_id());
+        let _ = writeln!(out, "name = {}", toml::Value::String(task.get_name().to_string()));
+        let _ = writeln!(out, "arrival = {}", task.get_arrival());
+        match task.get_periodic() {
+            Some(periodic) => {
+                let _ = writeln!(out, "period = {}", periodic.period);
+                let _ = writeln!(out, "offset = {}", periodic.offset);
+                let _ = writeln!(out, "wcet = {}", periodic.wcet);
+                let _ = writeln!(out, "jitter = {}", periodic.jitter);
+                let _ = writeln!(out, "jobs = {}", periodic.jobs);
+            },
+            None if task.get_cpu_time() != u64::MAX => {
+                let _ = writeln!(out, "cpu_time = {}", task.get_cpu_time());
+            },
+            None => {}
+        };
+        match task.get_phases() {
+            Some(phases) => {
+                let phases: Vec<String> = phases.iter().map(phase_toml).collect();
+                let _ = writeln!(out, "phases = [{}]", phases.join(", "));
+            },
+            None => {
+                let _ = writeln!(out, "cpu_burst = {}", distribution_toml(task.get_cpu_burst()));
+                let _ = writeln!(out, "io_burst = {}", distribution_toml(task.get_io_burst()));
+                if let Some(device) = task.get_io_device() {
+                    let _ = writeln!(out, "io_device = {}", toml::Value::String(device.to_string()));
+                }
+            }
+        };
+        let _ = writeln!(out, "weight = {}", task.get_weight());
+        let _ = writeln!(out, "policy = {:?}", policy_name(task.get_policy()));
+        let _ = writeln!(out, "group = {}", task.get_session());
+
+        if task.get_affinity() != u64::MAX {
+            let cpus: Vec<String> = (0..64)
+                .filter(|cpu| task.get_affinity() & (1 << cpu) != 0)
+                .map(|cpu| cpu.to_string())
+                .collect();
+            let _ = writeln!(out, "affinity = [{}]", cpus.join(", "));
+        }
+
+        if !task.get_actions().is_empty() {
+            let actions: Vec<String> = task.get_actions()
+                .iter()
+                .map(|(at, action)| match action {
+                    Action::Yield => format!("{{ at = {}, action = \"yield\" }}", at),
+                    Action::CondResched => format!("{{ at = {}, action = \"cond_resched\" }}", at),
+                    Action::Sleep(ticks) => format!("{{ at = {}, action = \"sleep\", ticks = {} }}", at, ticks)
+                })
+                .collect();
+            let _ = writeln!(out, "actions = [{}]", actions.join(", "));
+        }
+    }
+
+    out
+}
@@ -105,12 +105,12 @@ fn test_with_update() {
 
 #[test]
 fn test_multithreaded_clock() {
-    let mut sysclock = Arc::new(Mutex::new(Clock::new()));
+    let sysclock = Arc::new(Mutex::new(Clock::new()));
 
     let mut threads = vec![];
     let (sender, receiver) = std::sync::mpsc::channel();
 
-    let clk = Arc::clone(&mut sysclock);
+    let clk = Arc::clone(&sysclock);
     let sending = thread::spawn(move || {
         for _ in 0..50 {
             let mut lock = clk.lock().unwrap();
@@ -121,7 +121,7 @@ fn test_multithreaded_clock() {
     });
     threads.push(sending);
 
-    let c_clk = Arc::clone(&mut sysclock);
+    let c_clk = Arc::clone(&sysclock);
     let receiving = thread::spawn(move || {
         let mut rq = FairAlgorithm::new(&mut c_clk.lock().unwrap());
 
@@ -144,12 +144,7 @@ fn test_multithreaded_clock() {
             rq.push(tasks);
         }
 
-        loop {
-            let _time = match receiver.recv() {
-                Ok(tick) => tick,
-                Err(_) => break
-            };
-
+        while let Ok(_time) = receiver.recv() {
             if !rq.is_empty() {
                 let mut curr = rq.pop();
                 curr.cpu_cycle();
@@ -170,21 +165,18 @@ fn test_multithreaded_clock() {
 
 #[test]
 fn test_efficient_threads() {
-    let mut sysclock = Arc::new(Mutex::new(Clock::new()));
+    let sysclock = Arc::new(Mutex::new(Clock::new()));
 
     let mut threads = vec![];
     let (clock_sender_1, spawner_clock_recv) = std::sync::mpsc::channel();
     let (clock_sender_2, clock_recv) = std::sync::mpsc::channel();
     let (born_sender, born_recv) = std::sync::mpsc::channel();
 
-    let clk = Arc::clone(&mut sysclock);
+    let clk = Arc::clone(&sysclock);
     let ticking = thread::spawn(move || {
         for _ in 0..100 {
             let mut lock = clk.lock().unwrap();
-            match clock_sender_1.send(lock.time()) {
-                Ok(_) => {},
-                _ => {}
-            };
+            let _ = clock_sender_1.send(lock.time());
             clock_sender_2.send(lock.time()).unwrap();
             lock.tick();
         }
@@ -193,7 +185,7 @@ fn test_efficient_threads() {
     });
     threads.push(ticking);
 
-    let c_clk = Arc::clone(&mut sysclock);
+    let c_clk = Arc::clone(&sysclock);
 
     let task_spawning = thread::spawn(move || {
         let mut time = match spawner_clock_recv.recv() {
@@ -201,19 +193,13 @@ fn test_efficient_threads() {
             Err(_) => panic!("Ran out of time before the processes could be born, check bounds")
         };
         let task_one = Task::new(1, 15, 5, 3, time, 1);
-        match born_sender.send(task_one) {
-            Ok(_) => {},
-            _ => {}
-        };
+        let _ = born_sender.send(task_one);
         time = match spawner_clock_recv.try_recv() {
             Ok(tick) => tick,
             Err(_) => time
         };
         let task_two = Task::new(2, 15, 3, 5, time, 1);
-        match born_sender.send(task_two) {
-            Ok(_) => {},
-            _ => {}
-        };
+        let _ = born_sender.send(task_two);
     });
     threads.push(task_spawning);
 
@@ -221,16 +207,10 @@ fn test_efficient_threads() {
         let mut task_queue = TaskQueue::new();
         let mut rq = FairAlgorithm::new(&mut c_clk.lock().unwrap());
         
-        loop {
-            let _time = match clock_recv.recv() {
-                Ok(tick) => tick,
-                Err(_) => break
-            };
-
-            match born_recv.try_recv() {
-                Ok(task) => task_queue.add(task),
-                _ => {}
-            };
+        while let Ok(_time) = clock_recv.recv() {
+            if let Ok(task) = born_recv.try_recv() {
+                task_queue.add(task);
+            }
 
             let born_tasks = task_queue.pop();
             rq.push(born_tasks);
@@ -0,0 +1,85 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::task::Task;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+
+// eight cpu bound tasks in session 1 ("make -j8") against one in session 2 (the shell)
+fn shell_share(autogroup: bool, make_nice: i32) -> f64 {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_autogroup(autogroup);
+    rq.set_autogroup_nice(1, make_nice);
+
+    let mut tasks = Vec::new();
+    for id in 1..=8 {
+        let mut task = Task::new(id, 100000, 100000, 0, 0, 1);
+        task.set_session(1);
+        tasks.push(task);
+    }
+    let mut shell = Task::new(9, 100000, 100000, 0, 0, 1);
+    shell.set_session(2);
+    tasks.push(shell);
+    rq.push(tasks);
+
+    let mut shell_ticks = 0;
+    let total = 900;
+    for _ in 0..total {
+        let mut curr = rq.pop();
+        if curr.get_session() == 2 {
+            shell_ticks += 1;
+        }
+        curr.cpu_cycle();
+        sysclock.tick();
+        rq.insert(*curr);
+    }
+
+    shell_ticks as f64 / total as f64
+}
+
+#[test]
+fn test_without_autogroup() {
+    let share = shell_share(false, 0);
+    assert!(share < 0.2);
+}
+
+#[test]
+fn test_sessions_share_fairly() {
+    let share = shell_share(true, 0);
+    assert!((share - 0.5).abs() < 0.02);
+}
+
+#[test]
+fn test_session_nice() {
+    // nice 5 gives the make session a weight of 335 against the shell's 1024
+    let share = shell_share(true, 5);
+    let expected = 1024.0 / (1024.0 + 335.0);
+    assert!((share - expected).abs() < 0.02);
+}
+
+#[test]
+fn test_group_placement() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_autogroup(true);
+
+    let mut first = Task::new(1, 1000, 1000, 0, 0, 1);
+    first.set_session(1);
+    rq.insert(first);
+
+    for _ in 0..50 {
+        rq.run();
+        sysclock.tick();
+    }
+
+    // a session that shows up late starts at the current minimum instead of zero
+    let mut late = Task::new(2, 1000, 1000, 0, 50, 1);
+    late.set_session(2);
+    rq.insert(late);
+
+    let group_one = rq.get_autogroup(1).unwrap().get_vruntime();
+    let group_two = rq.get_autogroup(2).unwrap().get_vruntime();
+    assert_eq!(group_one, group_two);
+    assert_eq!(rq.get_autogroup(2).unwrap().get_nr_running(), 1);
+}
@@ -0,0 +1,129 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::task::Task;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm, features::SchedFeatures};
+
+fn woken(id: u16, cpu_time: u64, start_time: u128, weight: u32) -> Task {
+    let mut task = Task::new(id, cpu_time, cpu_time, 0, start_time, weight);
+    task.schedule();
+    task
+}
+
+#[test]
+fn test_features_parsing() {
+    let defaults = SchedFeatures::default();
+    assert!(!defaults.next_buddy && defaults.last_buddy && defaults.skip_buddy);
+
+    let features = "NEXT_BUDDY NO_LAST_BUDDY".parse::<SchedFeatures>().unwrap();
+    assert!(features.next_buddy && !features.last_buddy && features.skip_buddy);
+    assert_eq!(features.to_string(), "NEXT_BUDDY NO_LAST_BUDDY SKIP_BUDDY");
+
+    assert!("NO_SUCH_FEATURE".parse::<SchedFeatures>().is_err());
+}
+
+fn next_buddy_pick(next_buddy: bool) -> (Vec<u16>, FairAlgorithm) {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    let mut features = SchedFeatures::none();
+    features.next_buddy = next_buddy;
+    rq.set_features(features);
+    rq.set_slice(100);
+    rq.set_wakeup_granularity(10);
+
+    rq.insert(Task::new(1, 3, 3, 0, 0, 1000));
+    rq.insert(Task::new(2, 1, 1, 0, 0, 1));
+
+    let mut order = vec![rq.run().unwrap()];
+    sysclock.tick();
+
+    // wakes up slightly behind the leftmost task, not far enough to preempt
+    rq.insert(woken(3, 1, 0, 1));
+    while let Some(id) = rq.run() {
+        order.push(id);
+        sysclock.tick();
+    }
+
+    (order, rq)
+}
+
+#[test]
+fn test_next_buddy() {
+    let (order, rq) = next_buddy_pick(true);
+    assert_eq!(order, vec![1, 1, 1, 3, 2]);
+
+    let stats = rq.get_stats();
+    assert_eq!(stats.nr_next_buddy_picks, 1);
+    assert_eq!(stats.nr_switches, 3);
+    assert_eq!(stats.nr_wakeups, 3);
+    assert_eq!(stats.wakeup_latency_max, 4);
+    assert_eq!(stats.wakeup_latency_sum, 6);
+
+    let (order, rq) = next_buddy_pick(false);
+    assert_eq!(order, vec![1, 1, 1, 2, 3]);
+    assert_eq!(rq.get_stats().nr_next_buddy_picks, 0);
+}
+
+fn last_buddy_pick(last_buddy: bool) -> Vec<u16> {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    let mut features = SchedFeatures::none();
+    features.last_buddy = last_buddy;
+    rq.set_features(features);
+    rq.set_slice(100);
+    rq.set_wakeup_granularity(5);
+
+    for _ in 0..20 {
+        sysclock.tick();
+    }
+    rq.insert(Task::new(1, 10, 10, 0, 0, 2));
+
+    let mut order = vec![rq.run().unwrap()];
+    rq.insert(Task::new(2, 5, 5, 0, 2, 1));
+
+    // far enough behind the running task to preempt it
+    rq.insert(woken(3, 1, sysclock.time(), 1));
+    sysclock.tick();
+
+    for _ in 0..2 {
+        order.push(rq.run().unwrap());
+        sysclock.tick();
+    }
+
+    order
+}
+
+#[test]
+fn test_last_buddy() {
+    assert_eq!(last_buddy_pick(true), vec![1, 3, 1]);
+    assert_eq!(last_buddy_pick(false), vec![1, 3, 2]);
+}
+
+fn wakeup_preempts(wakee_start: u128) -> bool {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_features(SchedFeatures::none());
+    rq.set_slice(100);
+
+    // 40 ticks of vruntime by the time it is picked
+    for _ in 0..40 {
+        sysclock.tick();
+    }
+    rq.insert(woken(1, 50, 0, 1));
+    for _ in 0..3 {
+        assert_eq!(rq.run(), Some(1));
+        sysclock.tick();
+    }
+
+    rq.insert(woken(2, 1, wakee_start, 1));
+    rq.run() == Some(2)
+}
+
+#[test]
+fn test_wakeup_preempt_charges_curr() {
+    // curr is at 43 with the three ticks of its slice, not at its whole
+    // lifetime on top of the 40 it was picked with
+    assert!(!wakeup_preempts(0));
+    assert!(wakeup_preempts(13));
+}
@@ -0,0 +1,62 @@
+// the loop the scheduler runs the runqueue in, one tick at a time, for the
+// tests that look at the runqueue rather than at a report
+
+// each test crate uses only some of it
+#![allow(dead_code)]
+
+use cfs::proc::queue::TaskQueue;
+use cfs::proc::task::Task;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+
+pub struct Sim {
+    // declared before the clock it points to, so it is dropped first
+    pub rq: FairAlgorithm,
+    born_queue: TaskQueue,
+    clock: Box<Clock>,
+}
+
+impl Sim {
+    // the tasks released as they arrive, into a runqueue set up by configure
+    pub fn new(tasks: impl IntoIterator<Item = Task>, configure: impl FnOnce(&mut FairAlgorithm)) -> Self {
+        let mut clock = Box::new(Clock::new());
+        let mut rq = FairAlgorithm::new(&mut clock);
+        configure(&mut rq);
+        let mut born_queue = TaskQueue::new();
+        for task in tasks {
+            born_queue.add(task);
+        }
+        Self { rq, born_queue, clock }
+    }
+
+    pub fn time(&self) -> u128 {
+        self.clock.time()
+    }
+
+    // the current tick, without moving the clock on, and the task it ran
+    pub fn step(&mut self) -> Option<u16> {
+        self.rq.push(self.born_queue.release(self.clock.time()));
+        let ran = self.rq.run();
+        self.rq.idle();
+        ran
+    }
+
+    // the ticks up to end and the tasks that ran in them
+    pub fn run_until(&mut self, end: u128) -> Vec<(u128, u16)> {
+        let mut ran = Vec::new();
+        while self.clock.time() < end {
+            ran.extend(self.step().map(|id| (self.clock.time(), id)));
+            self.clock.tick();
+        }
+        ran
+    }
+
+    // the ticks up to every task having exited and the tasks that ran in them
+    pub fn run(&mut self) -> Vec<(u128, u16)> {
+        let mut ran = Vec::new();
+        while !self.rq.is_finished() || !self.born_queue.is_empty() {
+            ran.extend(self.step().map(|id| (self.clock.time(), id)));
+            self.clock.tick();
+        }
+        ran
+    }
+}
@@ -0,0 +1,170 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::io::device::{Device, IoKind, IoPolicy};
+use cfs::proc::dist::Distribution;
+use cfs::proc::phase::Phase;
+use cfs::proc::task::TaskChar;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use cfs::workload::spec;
+
+fn disk(service: u64, policy: IoPolicy) -> Device {
+    Device::new("disk", Distribution::Constant(service), policy)
+}
+
+// the ids of the finished requests at every tick until the device is idle
+fn drain(device: &mut Device) -> Vec<(u128, u16)> {
+    let mut done = Vec::new();
+    let mut now = 0;
+    while !device.is_idle() {
+        done.extend(device.tick(now).into_iter().map(|id| (now, id)));
+        now += 1;
+    }
+    done
+}
+
+#[test]
+fn test_dispatch_order() {
+    for (policy, expected) in [(IoPolicy::Fifo, [(2, 1), (4, 2), (6, 3)]), (IoPolicy::Deadline, [(2, 2), (4, 3), (6, 1)])] {
+        let mut device = disk(2, policy);
+        device.submit(1, IoKind::Write, 0);
+        device.submit(2, IoKind::Read, 0);
+        device.submit(3, IoKind::Read, 0);
+
+        assert_eq!(drain(&mut device), expected);
+        let stats = device.get_stats();
+        assert_eq!((stats.nr_requests, stats.nr_reads, stats.nr_writes), (3, 2, 1));
+        assert_eq!(stats.busy_ticks, 6);
+        assert_eq!(stats.max_queue, 3);
+    }
+}
+
+#[test]
+fn test_depth_and_expiry() {
+    let mut device = disk(4, IoPolicy::Deadline);
+    device.set_depth(2);
+    device.set_read_expire(2);
+    for id in 1..=3 {
+        device.submit(id, IoKind::Read, 0);
+    }
+
+    assert_eq!(drain(&mut device), [(4, 1), (4, 2), (8, 3)]);
+    let stats = device.get_stats();
+    assert_eq!(stats.wait_max, 4);
+    assert_eq!(stats.mean_wait(), 4.0 / 3.0);
+    assert_eq!(stats.nr_expired, 1);
+}
+
+#[test]
+fn test_tasks_block_on_device() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_device(disk(3, IoPolicy::Fifo));
+
+    let phases = vec![
+        Phase::Compute(Distribution::Constant(1)),
+        Phase::Request(String::from("disk"), IoKind::Read),
+        Phase::Compute(Distribution::Constant(1))
+    ];
+    for id in 1..=2 {
+        let mut raw = TaskChar::new(id, u64::MAX, 1, 0, 1);
+        raw.set_phases(phases.clone());
+        rq.insert(raw.spawn());
+    }
+
+    // the second request queues behind the first, each task runs the tick after its request is served
+    let mut ran = Vec::new();
+    while !rq.is_finished() {
+        if let Some(id) = rq.run() {
+            ran.push((sysclock.time(), id));
+        }
+        rq.idle();
+        sysclock.tick();
+    }
+    assert_eq!(ran, [(0, 1), (1, 2), (4, 1), (7, 2)]);
+    assert_eq!(rq.get_devices()[0].get_stats().wait_sum, 2);
+}
+
+#[test]
+fn test_io_device_bursts() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_device(disk(5, IoPolicy::Fifo));
+
+    let mut raw = TaskChar::new(1, 6, 2, 1, 1);
+    raw.set_io_device("disk");
+    rq.insert(raw.spawn());
+
+    let mut ran = Vec::new();
+    while !rq.is_finished() {
+        if rq.run().is_some() {
+            ran.push(sysclock.time());
+        }
+        rq.idle();
+        sysclock.tick();
+    }
+    // the device's service time replaces the io burst
+    assert_eq!(ran, [0, 1, 7, 8, 14, 15]);
+    assert_eq!(rq.get_devices()[0].get_stats().nr_requests, 2);
+}
+
+#[test]
+#[should_panic]
+fn test_unknown_device() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    let mut raw = TaskChar::new(1, u64::MAX, 1, 0, 1);
+    raw.set_phases(vec![Phase::Request(String::from("nic"), IoKind::Write)]);
+    rq.insert(raw.spawn());
+}
+
+#[test]
+fn test_workload_devices() {
+    let src = r#"
+[[device]]
+name = "disk"
+service = { dist = "uniform", min = 5, max = 15 }
+policy = "deadline"
+read_expire = 20
+depth = 2
+
+[[device]]
+name = "nic"
+service = 1
+
+[[task]]
+id = 1
+phases = [{ compute = 10 }, { read = "disk" }, { write = "nic" }]
+
+[[task]]
+id = 2
+cpu_time = 50
+cpu_burst = 10
+io_device = "disk"
+"#;
+    let workload = spec::parse_workload(src).unwrap();
+    assert_eq!(workload.devices.len(), 2);
+    let disk = &workload.devices[0];
+    assert_eq!(disk.get_name(), "disk");
+    assert_eq!(disk.get_service(), &Distribution::Uniform { min: 5, max: 15 });
+    assert_eq!(disk.get_policy(), IoPolicy::Deadline);
+    assert_eq!((disk.get_read_expire(), disk.get_write_expire(), disk.get_depth()), (20, 500, 2));
+    assert_eq!(workload.devices[1].get_policy(), IoPolicy::Fifo);
+
+    let tasks = &workload.tasks;
+    assert_eq!(tasks[0].get_phases().unwrap()[2], Phase::Request(String::from("nic"), IoKind::Write));
+    assert_eq!(tasks[1].get_io_device(), Some("disk"));
+
+    let again = spec::parse(&format!("{}\n{}", &src[..src.find("[[task]]").unwrap()], spec::to_toml(tasks))).unwrap();
+    assert_eq!(again[0].get_phases(), tasks[0].get_phases());
+    assert_eq!(again[1].get_io_device(), Some("disk"));
+
+    let error_line = |src: &str| spec::parse(src).err().unwrap().get_line();
+    assert_eq!(error_line("[[task]]\nid = 1\nphases = [{ read = \"disk\" }]\n"), 3);
+    assert_eq!(error_line("[[task]]\nid = 1\ncpu_time = 5\ncpu_burst = 1\nio_device = \"disk\"\n"), 5);
+    assert_eq!(error_line("[[device]]\nname = \"disk\"\nservice = 1\n[[device]]\nname = \"disk\"\nservice = 2\n"), 5);
+    assert_eq!(error_line("[[device]]\nname = \"disk\"\nservice = 1\npolicy = \"cfq\"\n"), 4);
+    assert_eq!(error_line("[[device]]\nname = \"disk\"\nservice = 1\ndepth = 0\n"), 4);
+}
@@ -0,0 +1,75 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::dist::{Distribution, Rng};
+use cfs::proc::task::{TaskChar, TaskStatus};
+
+fn samples(dist: &Distribution, seed: u64, n: usize) -> Vec<u64> {
+    let mut rng = Rng::new(seed);
+    (0..n).map(|_| dist.sample(&mut rng)).collect()
+}
+
+#[test]
+fn test_reproducible() {
+    let dist = Distribution::LogNormal { mu: 2.0, sigma: 1.0 };
+    assert_eq!(samples(&dist, 42, 100), samples(&dist, 42, 100));
+    assert_ne!(samples(&dist, 42, 100), samples(&dist, 43, 100));
+}
+
+#[test]
+fn test_shapes() {
+    assert!(samples(&Distribution::Constant(7), 1, 100).iter().all(|v| *v == 7));
+
+    let uniform = samples(&Distribution::Uniform { min: 3, max: 9 }, 1, 10000);
+    assert!(uniform.iter().all(|v| (3..=9).contains(v)));
+    assert!(uniform.contains(&3) && uniform.contains(&9));
+
+    let exponential = Distribution::Exponential { mean: 20.0 };
+    let mean = samples(&exponential, 1, 10000).iter().sum::<u64>() as f64 / 10000.0;
+    assert!((mean - exponential.mean()).abs() < 1.0);
+
+    let pareto = samples(&Distribution::Pareto { scale: 2.0, shape: 1.5 }, 1, 10000);
+    assert!(pareto.iter().all(|v| *v >= 2));
+    assert!(pareto.iter().any(|v| *v > 100));
+
+    let empirical = samples(&Distribution::Empirical(vec![(1, 9), (50, 1)]), 1, 10000);
+    let long = empirical.iter().filter(|v| **v == 50).count();
+    assert_eq!(empirical.iter().filter(|v| **v == 1).count() + long, 10000);
+    assert!(long > 800 && long < 1200);
+}
+
+#[test]
+fn test_validate() {
+    assert!(Distribution::Uniform { min: 5, max: 4 }.validate().is_err());
+    assert!(Distribution::Exponential { mean: 0.0 }.validate().is_err());
+    assert!(Distribution::LogNormal { mu: 1.0, sigma: -1.0 }.validate().is_err());
+    assert!(Distribution::Pareto { scale: 1.0, shape: 0.0 }.validate().is_err());
+    assert!(Distribution::Empirical(vec![(3, 0)]).validate().is_err());
+    assert!(Distribution::Empirical(vec![(3, 0), (4, 1)]).validate().is_ok());
+}
+
+#[test]
+fn test_task_bursts() {
+    let mut raw = TaskChar::new(1, 10000, 1, 0, 1);
+    raw.set_cpu_burst(Distribution::Uniform { min: 4, max: 12 });
+
+    let mut task = raw.spawn();
+    task.seed(3);
+    let mut bursts = Vec::new();
+    let mut burst = 0;
+    task.run();
+    while task.get_status() != TaskStatus::Terminated {
+        task.cpu_cycle();
+        burst += 1;
+        if task.get_status() == TaskStatus::Idle {
+            bursts.push(burst);
+            burst = 0;
+            task.io_cycle();
+            task.run();
+        }
+    }
+
+    assert!(bursts.iter().all(|b| (4..=12).contains(b)));
+    assert!(bursts.iter().any(|b| *b != bursts[0]));
+}
@@ -0,0 +1,112 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::task::{SchedPolicy, Task};
+use cfs::sched::{clock::Clock, dynamic::DynamicWeight, fair::FairAlgorithm};
+
+// the runqueue keeps a pointer to the clock, which has to outlive it
+fn simulate(sysclock: &mut Clock, dynamic: Option<DynamicWeight>, weight: u32) -> FairAlgorithm {
+    let mut rq = FairAlgorithm::new(sysclock);
+    rq.set_dynamic_weight(dynamic);
+
+    let hog = Task::new(1, 100000, 100000, 0, 0, weight);
+    let interactive = Task::new(2, 100000, 1, 20, 0, weight);
+    rq.push(vec![hog, interactive]);
+
+    for _ in 0..500 {
+        rq.run();
+        rq.idle();
+        sysclock.tick();
+    }
+
+    rq
+}
+
+#[test]
+fn test_punish_and_reward() {
+    let mut sysclock = Clock::new();
+    let rq = simulate(&mut sysclock, Some(DynamicWeight::new(4, 16, 50)), 10);
+    let log = rq.get_dynamic_weight().unwrap().get_log();
+
+    assert!(!log.is_empty());
+
+    let hog_last = log.iter().rfind(|adj| adj.id == 1).unwrap();
+    assert!(hog_last.new_weight < 10);
+    assert!(hog_last.cpu_share >= 0.5);
+
+    let io_last = log.iter().rfind(|adj| adj.id == 2).unwrap();
+    assert!(io_last.new_weight > 10);
+    assert!(io_last.io_ratio >= 0.5);
+
+    for adj in log {
+        assert!(adj.new_weight >= 4 && adj.new_weight <= 16);
+        assert_ne!(adj.old_weight, adj.new_weight);
+    }
+}
+
+#[test]
+fn test_bounds() {
+    let mut sysclock = Clock::new();
+    let rq = simulate(&mut sysclock, Some(DynamicWeight::new(8, 12, 10)), 10);
+    let log = rq.get_dynamic_weight().unwrap().get_log();
+
+    let hog_weights: Vec<u32> = log.iter()
+        .filter(|adj| adj.id == 1)
+        .map(|adj| adj.new_weight)
+        .collect();
+    assert_eq!(*hog_weights.last().unwrap(), 8);
+    assert!(hog_weights.iter().all(|w| *w >= 8));
+
+    let io_weights: Vec<u32> = log.iter()
+        .filter(|adj| adj.id == 2)
+        .map(|adj| adj.new_weight)
+        .collect();
+    assert_eq!(*io_weights.last().unwrap(), 12);
+}
+
+#[test]
+fn test_disabled() {
+    let mut sysclock = Clock::new();
+    let mut rq = simulate(&mut sysclock, None, 10);
+    assert!(rq.get_dynamic_weight().is_none());
+
+    while !rq.is_empty() {
+        assert_eq!(rq.pop().weight(), 10);
+    }
+}
+
+#[test]
+fn test_outside_bounds() {
+    // the hog is only punished towards the bounds and the io task is never
+    // pulled down to them by a reward
+    let mut sysclock = Clock::new();
+    let rq = simulate(&mut sysclock, Some(DynamicWeight::new(1, 32, 50)), 1024);
+    let log = rq.get_dynamic_weight().unwrap().get_log();
+
+    assert!(log.iter().any(|adj| adj.id == 1));
+    assert!(log.iter().filter(|adj| adj.id == 1).all(|adj| adj.new_weight == adj.old_weight - 1));
+    assert!(log.iter().all(|adj| adj.id != 2));
+}
+
+#[test]
+fn test_idle_policy() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_dynamic_weight(Some(DynamicWeight::new(1, 32, 10)));
+    let mut hog = Task::new(1, 100000, 100000, 0, 0, 10);
+    hog.set_policy(SchedPolicy::Idle);
+    rq.push(vec![hog]);
+
+    for _ in 0..100 {
+        rq.run();
+        rq.idle();
+        sysclock.tick();
+    }
+
+    // the nice weight is left for when the task leaves sched_idle
+    assert!(rq.get_dynamic_weight().unwrap().get_log().is_empty());
+    let mut task = rq.pop();
+    task.set_policy(SchedPolicy::Normal);
+    assert_eq!(task.weight(), 10);
+}
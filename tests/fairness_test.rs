@@ -0,0 +1,82 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::task::TaskChar;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use cfs::stats::fairness::{jain_index, FluidModel};
+
+// runs the given task ids in turn, both tasks runnable all along
+fn replay(order: &[u16]) -> FluidModel {
+    let mut fluid = FluidModel::new(4);
+    for (now, id) in order.iter().enumerate() {
+        fluid.tick(now as u128, &[(1, 1), (2, 3)], Some(*id));
+    }
+    fluid
+}
+
+#[test]
+fn test_jain_index() {
+    assert_eq!(jain_index(&[1.0, 1.0, 1.0]), Some(1.0));
+    assert_eq!(jain_index(&[1.0, 0.0, 0.0, 0.0]), Some(0.25));
+    assert_eq!(jain_index(&[]), None);
+}
+
+#[test]
+fn test_weighted_schedule() {
+    // the heavier task runs three ticks out of four, as gps would have it
+    let fluid = replay(&[2, 1, 2, 2, 2, 1, 2, 2]);
+
+    let light = fluid.get_task(1).unwrap();
+    assert_eq!((light.ideal, light.actual, light.runnable), (2.0, 2, 8));
+    assert_eq!(light.weight_share(), 0.25);
+    assert_eq!(light.share_error(), 0.0);
+    assert_eq!((light.max_lag, light.min_lag), (0.25, -0.5));
+    assert_eq!(fluid.max_share_error(), 0.0);
+
+    let windows = fluid.get_windows();
+    assert_eq!(windows.iter().map(|window| (window.start, window.end)).collect::<Vec<_>>(), [(0, 4), (4, 8)]);
+    assert!(windows.iter().all(|window| window.nr_tasks == 2 && window.jain == 1.0));
+}
+
+#[test]
+fn test_weight_ignored() {
+    // taking turns gives the light task twice and the heavy one two thirds of their share
+    let fluid = replay(&[1, 2, 1, 2, 1, 2, 1, 2, 1, 2]);
+
+    assert_eq!(fluid.get_task(1).unwrap().share_error(), 0.25);
+    assert_eq!(fluid.get_task(2).unwrap().share_error(), -0.25);
+    assert_eq!(fluid.max_lag(), 2.75);
+    assert_eq!(fluid.min_lag(), -2.75);
+
+    let windows = fluid.get_windows();
+    assert_eq!(windows.len(), 3);
+    assert!((windows[0].jain - 0.8).abs() < 1e-9);
+}
+
+#[test]
+fn test_equal_weights() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_fairness_window(10);
+    for id in 1..=3 {
+        rq.insert(TaskChar::new(id, 20, 20, 0, 1).spawn());
+    }
+
+    while !rq.is_finished() {
+        rq.run();
+        rq.idle();
+        sysclock.tick();
+    }
+    let fluid = rq.get_fluid();
+    assert_eq!(fluid.get_tasks().len(), 3);
+    assert!(fluid.max_lag() <= 1.0 && fluid.min_lag() >= -1.0);
+    assert_eq!(fluid.get_windows().len(), 6);
+    assert!(fluid.get_windows().iter().all(|window| window.jain > 0.9));
+}
+
+#[test]
+#[should_panic]
+fn test_empty_window() {
+    FluidModel::new(0);
+}
@@ -0,0 +1,111 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::dist::Distribution;
+use cfs::proc::phase::Phase;
+use cfs::proc::task::{TaskChar, TaskStatus};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+
+fn compute(ticks: u64) -> Phase {
+    Phase::Compute(Distribution::Constant(ticks))
+}
+
+fn parent(phases: Vec<Phase>) -> TaskChar {
+    let mut raw = TaskChar::new(1, u64::MAX, 1, 0, 1);
+    raw.set_phases(phases);
+    raw
+}
+
+fn run(rq: &mut FairAlgorithm, sysclock: &mut Clock) -> Vec<u16> {
+    let mut ran = Vec::new();
+    while !rq.is_finished() {
+        if let Some(id) = rq.run() {
+            ran.push(id);
+        }
+        rq.idle();
+        sysclock.tick();
+    }
+    ran
+}
+
+#[test]
+fn test_child_runs_first() {
+    let phases = vec![compute(3), Phase::Fork(vec![compute(2)]), compute(5)];
+
+    for (child_runs_first, expected) in [(false, [1, 1, 1, 1, 1, 1, 1, 1, 2, 2]), (true, [1, 1, 1, 2, 2, 1, 1, 1, 1, 1])] {
+        let mut sysclock = Clock::new();
+        let mut rq = FairAlgorithm::new(&mut sysclock);
+        rq.set_slice(100);
+        rq.set_next_pid(2);
+        rq.set_child_runs_first(child_runs_first);
+        rq.insert(parent(phases.clone()).spawn());
+
+        assert_eq!(run(&mut rq, &mut sysclock), expected);
+        assert_eq!(rq.get_stats().nr_forks, 1);
+    }
+}
+
+#[test]
+fn test_wait_reaps_children() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_next_pid(2);
+
+    let child = vec![Phase::Exec(String::from("cc1"), vec![compute(4)])];
+    rq.insert(parent(vec![Phase::Fork(child.clone()), Phase::Fork(child), Phase::Wait, compute(1)]).spawn());
+
+    let ran = run(&mut rq, &mut sysclock);
+    assert_eq!(ran.len(), 9);
+    assert_eq!(ran.last(), Some(&1));
+
+    let exited = rq.get_exited();
+    assert_eq!(exited.len(), 3);
+    assert_eq!(exited[2].get_id(), 1);
+    for child in &exited[..2] {
+        assert_eq!(child.get_name(), "cc1");
+        assert_eq!(child.get_parent(), Some(1));
+        assert_eq!(child.get_status(), TaskStatus::Dead);
+    }
+}
+
+#[test]
+fn test_zombies() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_slice(100);
+    rq.set_next_pid(2);
+    rq.set_child_runs_first(true);
+    rq.insert(parent(vec![Phase::Fork(vec![compute(1)]), compute(5)]).spawn());
+
+    // the child runs first and exits, but the parent never waits for it
+    for _ in 0..3 {
+        rq.run();
+        rq.idle();
+        sysclock.tick();
+    }
+    assert_eq!(rq.get_nr_children(1), 0);
+    assert_eq!(rq.get_zombies().len(), 1);
+    assert_eq!(rq.get_zombies()[0].get_status(), TaskStatus::Zombie);
+    assert!(rq.get_exited().is_empty());
+
+    // and is reaped once its parent is gone
+    run(&mut rq, &mut sysclock);
+    assert!(rq.get_zombies().is_empty());
+    assert_eq!(rq.get_exited().len(), 2);
+    assert!(rq.get_exited().iter().all(|task| task.get_status() == TaskStatus::Dead));
+}
+
+#[test]
+fn test_orphans() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_next_pid(2);
+    rq.insert(parent(vec![Phase::Fork(vec![compute(6)]), compute(1)]).spawn());
+
+    run(&mut rq, &mut sysclock);
+    let exited = rq.get_exited();
+    assert_eq!(exited[0].get_id(), 1);
+    assert_eq!(exited[1].get_id(), 2);
+    assert_eq!(exited[1].get_status(), TaskStatus::Dead);
+}
@@ -0,0 +1,107 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+mod common;
+
+use cfs::proc::task::{TaskChar, TaskStatus};
+use cfs::trace::gantt::{Gantt, GanttRows};
+use cfs::trace::Trace;
+
+use common::Sim;
+
+fn traced(tasks: Vec<TaskChar>) -> Trace {
+    let mut sim = Sim::new(tasks.iter().map(TaskChar::spawn), |rq| rq.set_tracing(true));
+    sim.run();
+    sim.rq.get_trace().unwrap().clone()
+}
+
+// task 1 alone, then tasks 2 and 3 taking turns
+fn schedule() -> Trace {
+    let mut second = TaskChar::new(2, 3, 3, 0, 1);
+    second.set_arrival(10);
+    let mut third = TaskChar::new(3, 2, 2, 0, 1);
+    third.set_arrival(10);
+    traced(vec![TaskChar::new(1, 5, 5, 0, 1), second, third])
+}
+
+#[test]
+fn test_timeline() {
+    let trace = traced(vec![TaskChar::new(1, 4, 2, 3, 1)]);
+    let chart = Gantt::new(&trace);
+
+    let timeline = chart.timeline(1);
+    assert_eq!(timeline.first(), Some(&(0, Some(TaskStatus::Running))));
+    assert_eq!(timeline[1], (2, Some(TaskStatus::Idle)));
+    assert_eq!(timeline.last(), Some(&(trace.end(), Some(TaskStatus::Terminated))));
+    // the time spent on the cpu is the time running in the chart
+    let running: u128 = timeline.windows(2)
+        .filter(|pair| pair[0].1 == Some(TaskStatus::Running))
+        .map(|pair| pair[1].0 - pair[0].0)
+        .sum();
+    assert_eq!(running, 4);
+}
+
+#[test]
+fn test_ascii() {
+    let trace = schedule();
+    let mut chart = Gantt::new(&trace);
+    assert_eq!(
+        chart.to_ascii(),
+        "\
+tick        0         10
+task-1 (1)  #####x
+task-2 (2)            #-#-#x
+task-3 (3)            -#-#x
+# running  - waiting  . idle (i/o)  s stopped  x terminated
+"
+    );
+
+    // two ticks to a column from tick 10, the exit marked in its column
+    chart.set_zoom(2);
+    chart.set_window(10, Some(16));
+    let lines: Vec<String> = chart.to_ascii().lines().map(String::from).collect();
+    assert_eq!(lines[..4], ["tick        10", "task-1 (1)", "task-2 (2)  ##x", "task-3 (3)  ##x"]);
+
+    chart.set_rows(GanttRows::Cpus);
+    chart.set_zoom(1);
+    chart.set_window(3, Some(16));
+    let lines: Vec<String> = chart.to_ascii().lines().map(String::from).collect();
+    assert_eq!(lines[..2], ["tick   3         13", "CPU 0  11.....23232."]);
+    assert_eq!(lines[2], "1 task-1 (1)  2 task-2 (2)  3 task-3 (3)  . idle");
+}
+
+#[test]
+fn test_svg() {
+    let mut raw = TaskChar::new(1, 4, 2, 3, 1);
+    raw.set_name("<io & co>");
+    let trace = traced(vec![raw, TaskChar::new(2, 3, 3, 0, 1)]);
+    let mut chart = Gantt::new(&trace);
+    let svg = chart.to_svg();
+
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert!(svg.contains("&lt;io &amp; co&gt; (1)"));
+    for state in ["running", "waiting", "idle (i/o)", "terminated"] {
+        assert!(svg.contains(&format!("(1) {} ", state)), "{}", state);
+    }
+    // one bar per slice on the cpu row
+    chart.set_rows(GanttRows::Cpus);
+    let bars = chart.to_svg().matches("<title>").count();
+    let idle = chart.to_svg().matches("<title>idle").count();
+    assert_eq!(bars - idle, trace.slices.len());
+    // in the colour of the task that ran, listed in the legend
+    let svg = chart.to_svg();
+    let fill = |title: &str| svg.split(&format!("\"><title>{}", title)).next().unwrap().rsplit("fill=\"").next().unwrap().to_string();
+    assert_ne!(fill("&lt;io &amp; co&gt; (1) "), fill("task-2 (2) "));
+    let lines: Vec<&str> = svg.lines().collect();
+    let key = format!("fill=\"{}\"/>", fill("task-2 (2) "));
+    assert!(lines.windows(2).any(|pair| pair[0].ends_with(&key) && pair[1].ends_with(">task-2 (2)</text>")));
+}
+
+#[test]
+#[should_panic]
+fn test_zoom() {
+    let trace = Trace::new();
+    Gantt::new(&trace).set_zoom(0);
+}
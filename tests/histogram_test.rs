@@ -0,0 +1,90 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+mod common;
+
+use cfs::proc::task::{SchedPolicy, TaskChar};
+use cfs::stats::histogram::{Histogram, Latencies};
+use cfs::stats::Report;
+
+use common::Sim;
+
+fn run(tasks: Vec<TaskChar>) -> Report {
+    let mut sim = Sim::new(tasks.iter().map(TaskChar::spawn), |_| ());
+    sim.run();
+    let mut report = Report::new(sim.rq.get_exited(), sim.rq.get_fluid());
+    report.latencies = sim.rq.get_latencies().clone();
+    report
+}
+
+#[test]
+fn test_histogram() {
+    let mut histogram = Histogram::default();
+    for value in 1..=1000 {
+        histogram.record(value);
+    }
+    assert_eq!((histogram.count(), histogram.min(), histogram.max()), (1000, Some(1), Some(1000)));
+    assert_eq!(histogram.mean(), 500.5);
+
+    // small values are exact, large ones within 1/16
+    assert_eq!(histogram.value_at_quantile(0.01), Some(10));
+    for q in [0.5, 0.9, 0.99, 0.999] {
+        let exact = (q * 1000.0) as u64;
+        let value = histogram.value_at_quantile(q).unwrap();
+        assert!(value >= exact && value - exact <= exact / 16, "{} {}", q, value);
+    }
+    assert_eq!(histogram.value_at_quantile(1.0), Some(1000));
+    assert_eq!(histogram.buckets().iter().map(|(_, _, count)| count).sum::<u64>(), 1000);
+    assert_eq!(histogram.buckets()[30..32], [(31, 31, 1), (32, 33, 2)]);
+
+    // the whole range fits
+    histogram.record(u64::MAX);
+    assert_eq!(histogram.value_at_quantile(1.0), Some(u64::MAX));
+    assert_eq!(Histogram::default().value_at_quantile(0.5), None);
+}
+
+#[test]
+fn test_merge() {
+    let mut first = Histogram::default();
+    let mut second = Histogram::default();
+    first.record_n(3, 10);
+    second.record(5000);
+    first.merge(&second);
+    assert_eq!((first.count(), first.min(), first.max()), (11, Some(3), Some(5000)));
+    assert_eq!(first.value_at_quantile(0.9), Some(3));
+
+    let mut latencies = run(vec![TaskChar::new(1, 4, 2, 3, 1024), TaskChar::new(2, 3, 3, 0, 15)]).latencies;
+    let again = Latencies::from_json(&latencies.to_json()).unwrap();
+    assert_eq!(again, latencies);
+    latencies.merge(&again);
+    assert_eq!(latencies.wait.global.count(), 2 * again.wait.global.count());
+    assert_eq!(latencies.slice.get_task(2).unwrap().count(), 2 * again.slice.get_task(2).unwrap().count());
+}
+
+#[test]
+#[should_panic]
+fn test_merge_precision() {
+    Histogram::new(3).merge(&Histogram::new(5));
+}
+
+#[test]
+fn test_latencies() {
+    let mut batch = TaskChar::new(3, 6, 6, 0, 1024);
+    batch.set_policy(SchedPolicy::Batch);
+    let report = run(vec![TaskChar::new(1, 4, 2, 3, 1024), TaskChar::new(2, 5, 5, 0, 1024), batch]);
+    let latencies = &report.latencies;
+
+    // the episodes add up to the time spent waiting and running
+    let total = |histogram: &Histogram| (histogram.mean() * histogram.count() as f64).round() as u128;
+    assert_eq!(total(&latencies.wait.global), report.tasks.iter().map(|task| task.wait_time).sum::<u128>());
+    assert_eq!(total(&latencies.slice.global), report.tasks.iter().map(|task| task.cpu_time as u128).sum::<u128>());
+    for task in report.tasks.iter() {
+        assert_eq!(total(latencies.wait.get_task(task.id).unwrap()), task.wait_time);
+    }
+
+    // the new tasks and the one back from io
+    assert_eq!(latencies.wakeup.global.count(), 4);
+    assert_eq!(latencies.slice.get_policy(SchedPolicy::Batch).unwrap().count(), latencies.slice.get_task(3).unwrap().count());
+    assert!(latencies.wait.get_policy(SchedPolicy::Idle).is_none());
+}
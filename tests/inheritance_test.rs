@@ -0,0 +1,132 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::dist::Distribution;
+use cfs::proc::phase::Phase;
+use cfs::proc::queue::TaskQueue;
+use cfs::proc::task::TaskChar;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use cfs::sync::{InversionWindow, SyncKind, SyncObject};
+
+fn compute(ticks: u64) -> Phase {
+    Phase::Compute(Distribution::Constant(ticks))
+}
+
+fn lock(name: &str) -> Phase {
+    Phase::Lock(String::from(name))
+}
+
+fn unlock(name: &str) -> Phase {
+    Phase::Unlock(String::from(name))
+}
+
+fn task(id: u16, arrival: u128, weight: u32, phases: Vec<Phase>) -> TaskChar {
+    let mut raw = TaskChar::new(id, u64::MAX, 1, 0, weight);
+    raw.set_arrival(arrival);
+    raw.set_phases(phases);
+    raw
+}
+
+// a low weight meteorological task holds the bus when the heavy bus manager
+// needs it, while a medium weight communications task keeps the cpu busy
+fn pathfinder(priority_inheritance: bool) -> (Vec<InversionWindow>, u128) {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_sync_object(SyncObject::new("bus", SyncKind::Mutex));
+    rq.set_priority_inheritance(priority_inheritance);
+    rq.set_inversion_bound(30);
+
+    let mut born_queue = TaskQueue::new();
+    born_queue.add(task(1, 0, 1, vec![compute(1), lock("bus"), compute(10), unlock("bus")]).spawn());
+    born_queue.add(task(2, 3, 100, vec![lock("bus"), compute(2), unlock("bus")]).spawn());
+    born_queue.add(task(3, 4, 10, vec![compute(200)]).spawn());
+
+    let mut bus_manager_done = 0;
+    while !rq.is_finished() || !born_queue.is_empty() {
+        rq.push(born_queue.release(sysclock.time()));
+        if rq.run() == Some(2) {
+            bus_manager_done = sysclock.time();
+        }
+        rq.idle();
+        sysclock.tick();
+    }
+
+    let unbounded = rq.get_unbounded_inversions().len();
+    assert_eq!(unbounded, if priority_inheritance { 0 } else { 1 });
+    (rq.get_inversions().to_vec(), bus_manager_done)
+}
+
+#[test]
+fn test_pathfinder() {
+    let (without, late) = pathfinder(false);
+    let (with, early) = pathfinder(true);
+
+    for windows in [&without, &with] {
+        assert_eq!(windows.len(), 1);
+        assert_eq!((windows[0].waiter, windows[0].holder, windows[0].start), (2, 1, 3));
+        assert_eq!(windows[0].lock, "bus");
+    }
+    // comms keeps the holder off the cpu until inheritance lends it the bus manager's weight
+    assert!(without[0].length(0) > 3 * with[0].length(0));
+    assert!(without[0].interference > 30);
+    assert!(with[0].interference <= 30);
+    assert!(late > 3 * early);
+}
+
+#[test]
+fn test_boost_chain() {
+    for priority_inheritance in [false, true] {
+        let mut sysclock = Clock::new();
+        let mut rq = FairAlgorithm::new(&mut sysclock);
+        rq.add_sync_object(SyncObject::new("a", SyncKind::Mutex));
+        rq.add_sync_object(SyncObject::new("b", SyncKind::Mutex));
+        rq.set_priority_inheritance(priority_inheritance);
+
+        rq.insert(task(1, 0, 1, vec![lock("a"), compute(10), unlock("a")]).spawn());
+        rq.insert(task(2, 0, 5, vec![lock("b"), compute(1), lock("a"), unlock("a"), unlock("b")]).spawn());
+        rq.insert(task(3, 0, 50, vec![compute(1), lock("b"), unlock("b")]).spawn());
+
+        // task 3 waits on task 2, which waits on task 1
+        while rq.get_locked().len() < 2 {
+            rq.run();
+            rq.idle();
+            sysclock.tick();
+        }
+        if priority_inheritance {
+            assert_eq!((rq.get_boost(1), rq.get_boost(2)), (Some(50), Some(50)));
+        } else {
+            assert_eq!((rq.get_boost(1), rq.get_boost(2)), (None, None));
+        }
+        assert_eq!(rq.get_boost(3), None);
+
+        while !rq.is_finished() {
+            rq.run();
+            rq.idle();
+            sysclock.tick();
+        }
+        assert_eq!((rq.get_boost(1), rq.get_boost(2)), (None, None));
+        assert_eq!(rq.get_exited().len(), 3);
+    }
+}
+
+#[test]
+fn test_no_inversion_behind_heavier_holder() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_sync_object(SyncObject::new("m", SyncKind::Mutex));
+    rq.set_priority_inheritance(true);
+
+    rq.insert(task(1, 0, 10, vec![lock("m"), compute(3), unlock("m")]).spawn());
+    rq.insert(task(2, 0, 5, vec![lock("m"), unlock("m")]).spawn());
+    assert_eq!(rq.get_locked().len(), 1);
+    // the holder is heavier than its waiter already
+    assert_eq!(rq.get_boost(1), None);
+
+    while !rq.is_finished() {
+        rq.run();
+        rq.idle();
+        sysclock.tick();
+    }
+    assert!(rq.get_inversions().is_empty());
+}
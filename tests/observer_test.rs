@@ -0,0 +1,207 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::queue::TaskQueue;
+use cfs::proc::signal::Signal;
+use cfs::proc::task::{Task, TaskChar};
+use cfs::sched::dynamic::{DynamicWeight, WeightAdjustment};
+use cfs::sched::observer::Observer;
+use cfs::sched::scheduler::Scheduler;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+
+use std::sync::{Arc, Mutex};
+
+// every event as (time, name, task), None for idle ticks
+#[derive(Default)]
+struct Recorder {
+    events: Vec<(u128, &'static str, Option<u16>)>,
+}
+
+impl Recorder {
+    fn count(&self, name: &str) -> usize {
+        self.events.iter().filter(|(_, event, _)| *event == name).count()
+    }
+
+    fn of(&self, id: u16) -> Vec<&'static str> {
+        self.events.iter().filter(|(_, _, task)| *task == Some(id)).map(|(_, event, _)| *event).collect()
+    }
+}
+
+impl Observer for Recorder {
+    fn enqueue(&mut self, now: u128, task: &Task) {
+        self.events.push((now, "enqueue", Some(task.get_id())));
+    }
+
+    fn dequeue(&mut self, now: u128, task: &Task) {
+        self.events.push((now, "dequeue", Some(task.get_id())));
+    }
+
+    fn pick(&mut self, now: u128, task: &Task) {
+        self.events.push((now, "pick", Some(task.get_id())));
+    }
+
+    fn preempt(&mut self, now: u128, task: &Task) {
+        self.events.push((now, "preempt", Some(task.get_id())));
+    }
+
+    fn tick(&mut self, now: u128, curr: Option<&Task>) {
+        self.events.push((now, "tick", curr.map(|task| task.get_id())));
+    }
+
+    fn wakeup(&mut self, now: u128, task: &Task) {
+        self.events.push((now, "wakeup", Some(task.get_id())));
+    }
+
+    fn block(&mut self, now: u128, task: &Task) {
+        self.events.push((now, "block", Some(task.get_id())));
+    }
+
+    fn migrate(&mut self, now: u128, task: &Task, _from: u32, _to: u32) {
+        self.events.push((now, "migrate", Some(task.get_id())));
+    }
+
+    fn exit(&mut self, now: u128, task: &Task) {
+        self.events.push((now, "exit", Some(task.get_id())));
+    }
+
+    fn signal(&mut self, now: u128, id: u16, _signal: Signal) {
+        self.events.push((now, "signal", Some(id)));
+    }
+
+    fn reweight(&mut self, now: u128, task: &Task, _adjustment: &WeightAdjustment) {
+        self.events.push((now, "reweight", Some(task.get_id())));
+    }
+}
+
+// only keeps the order observers are called in
+struct Order {
+    name: &'static str,
+    calls: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Observer for Order {
+    fn pick(&mut self, _now: u128, _task: &Task) {
+        self.calls.lock().unwrap().push(self.name);
+    }
+}
+
+// runs the tasks to completion with the observer, returning the ticks it took
+fn run(tasks: Vec<TaskChar>, observer: Box<dyn Observer>, signals: &[(u128, u16, Signal)]) -> u128 {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_observer(observer);
+    let mut born_queue = TaskQueue::new();
+    for raw in tasks {
+        born_queue.add(raw.spawn());
+    }
+
+    while !rq.is_finished() || !born_queue.is_empty() || signals.iter().any(|(at, _, _)| *at >= sysclock.time()) {
+        rq.push(born_queue.release(sysclock.time()));
+        for (_, id, signal) in signals.iter().filter(|(at, _, _)| *at == sysclock.time()) {
+            assert!(rq.signal(*id, *signal));
+        }
+        rq.run();
+        rq.idle();
+        sysclock.tick();
+    }
+    sysclock.time()
+}
+
+#[test]
+fn test_events() {
+    let recorder = Arc::new(Mutex::new(Recorder::default()));
+    let mut io = TaskChar::new(1, 4, 2, 3, 1024);
+    io.set_arrival(0);
+    let mut cpu = TaskChar::new(2, 3, 3, 0, 1024);
+    cpu.set_arrival(1);
+    let ticks = run(vec![io, cpu], Box::new(recorder.clone()), &[]);
+
+    let recorder = recorder.lock().unwrap();
+    // a tick for every tick of the run, whether a task ran or not
+    assert_eq!(recorder.count("tick") as u128, ticks);
+    assert_eq!(recorder.events.iter().filter(|(_, event, task)| *event == "tick" && task.is_some()).count(), 7);
+    assert_eq!(recorder.count("exit"), 2);
+    // there is only the one cpu to run on
+    assert_eq!(recorder.count("migrate"), 0);
+
+    // woken when new and after the io, blocked once for it
+    let io = recorder.of(1);
+    assert_eq!(io.first(), Some(&"wakeup"));
+    assert_eq!(io.iter().filter(|event| **event == "wakeup").count(), 2);
+    assert_eq!(io.iter().filter(|event| **event == "block").count(), 1);
+    assert_eq!(io.last(), Some(&"exit"));
+
+    // every pick is of a task that was enqueued and not picked since
+    let mut queued = Vec::new();
+    for (_, event, task) in recorder.events.iter() {
+        match *event {
+            "enqueue" => queued.push(task.unwrap()),
+            "pick" => {
+                let idx = queued.iter().position(|id| Some(*id) == *task).unwrap();
+                queued.remove(idx);
+            }
+            _ => ()
+        }
+    }
+    assert!(queued.is_empty());
+
+    // in time order
+    assert!(recorder.events.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+}
+
+#[test]
+fn test_dequeue() {
+    let recorder = Arc::new(Mutex::new(Recorder::default()));
+    let tasks = vec![TaskChar::new(1, 2, 2, 0, 1024), TaskChar::new(2, 2, 2, 0, 1024)];
+    // stopped while waiting for the cpu
+    run(tasks, Box::new(recorder.clone()), &[(1, 2, Signal::Stop), (3, 2, Signal::Cont)]);
+
+    let recorder = recorder.lock().unwrap();
+    assert_eq!(recorder.of(2).iter().filter(|event| **event == "dequeue").count(), 1);
+    let signals: Vec<(u128, Option<u16>)> = recorder.events.iter()
+        .filter(|(_, event, _)| *event == "signal")
+        .map(|(at, _, id)| (*at, *id))
+        .collect();
+    assert_eq!(signals, [(1, Some(2)), (3, Some(2))]);
+    assert_eq!(recorder.of(2).last(), Some(&"exit"));
+}
+
+#[test]
+fn test_observers_on_scheduler() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::new(Mutex::new(Recorder::default()));
+    let mut scheduler = Scheduler::new();
+    scheduler.add_observer(Box::new(Order { name: "first", calls: calls.clone() }));
+    scheduler.add_observer(Box::new(recorder.clone()));
+    scheduler.add_observer(Box::new(Order { name: "second", calls: calls.clone() }));
+
+    scheduler.run(vec![TaskChar::new(1, 2, 2, 0, 1), TaskChar::new(2, 2, 2, 0, 1)]);
+    let picks = recorder.lock().unwrap().count("pick");
+    assert!(picks >= 2);
+    let calls = calls.lock().unwrap().clone();
+    assert_eq!(calls.len(), 2 * picks);
+    assert!(calls.chunks(2).all(|pair| pair == ["first", "second"]));
+
+    // still registered for the next run
+    scheduler.run(vec![TaskChar::new(1, 2, 2, 0, 1)]);
+    assert_eq!(recorder.lock().unwrap().count("exit"), 3);
+}
+
+#[test]
+fn test_reweight() {
+    let recorder = Arc::new(Mutex::new(Recorder::default()));
+    let mut scheduler = Scheduler::new();
+    scheduler.set_logging(false);
+    scheduler.set_dynamic_weight(Some(DynamicWeight::new(4, 16, 50)));
+    scheduler.add_observer(Box::new(recorder.clone()));
+    let report = scheduler.run(vec![TaskChar::new(1, 300, 300, 0, 10), TaskChar::new(2, 30, 1, 20, 10)]);
+
+    // an event for every adjustment the report lists
+    let reweights: Vec<Option<u16>> = recorder.lock().unwrap().events.iter()
+        .filter(|(_, event, _)| *event == "reweight")
+        .map(|(_, _, id)| *id)
+        .collect();
+    assert!(!reweights.is_empty());
+    assert_eq!(reweights, report.weight_adjustments.iter().map(|adj| Some(adj.id)).collect::<Vec<Option<u16>>>());
+}
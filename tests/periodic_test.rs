@@ -0,0 +1,76 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+mod common;
+
+use cfs::proc::periodic::Periodic;
+use cfs::proc::task::{Task, TaskChar};
+
+use common::Sim;
+
+fn run(task: Task) -> Task {
+    let mut sim = Sim::new([task], |_| ());
+    sim.run();
+    sim.rq.get_exited()[0].clone()
+}
+
+#[test]
+fn test_jobs() {
+    let mut raw = TaskChar::new(1, 1000, 1000, 0, 1);
+    raw.set_periodic(Periodic::new(10, 3, 4));
+    let task = run(raw.spawn());
+
+    let jobs = task.get_jobs();
+    assert_eq!(jobs.len(), 4);
+    for (k, job) in jobs.iter().enumerate() {
+        assert_eq!(job.job, k as u64);
+        assert_eq!(job.nominal, 10 * k as u128);
+        assert_eq!(job.release, job.nominal);
+        assert_eq!(job.finish - job.start, 2);
+        assert!(!job.overrun);
+    }
+    // jobs released between ticks start on the next one
+    assert_eq!(jobs[0].response(), 2);
+    assert_eq!(jobs[1].response(), 3);
+    assert_eq!(task.get_runtime(), 3);
+}
+
+#[test]
+fn test_overrun() {
+    let mut raw = TaskChar::new(1, 1000, 1000, 0, 1);
+    raw.set_periodic(Periodic::new(5, 8, 3));
+    let task = run(raw.spawn());
+
+    let jobs = task.get_jobs();
+    assert!(jobs.iter().all(|job| job.overrun));
+    // a late job is followed right away
+    assert_eq!(jobs[1].release, jobs[0].finish);
+    assert_eq!(jobs[2].release, jobs[1].finish);
+    assert!(jobs[2].response() > jobs[1].response());
+}
+
+#[test]
+fn test_jitter() {
+    let mut periodic = Periodic::new(20, 2, 30);
+    periodic.offset = 7;
+    periodic.jitter = 4;
+    let mut raw = TaskChar::new(1, 1000, 1000, 0, 1);
+    raw.set_periodic(periodic);
+    raw.set_arrival(3);
+
+    let seeded = |seed| {
+        let mut task = raw.spawn();
+        task.seed(seed);
+        run(task)
+    };
+    let task = seeded(1);
+
+    let jobs = task.get_jobs();
+    for (k, job) in jobs.iter().enumerate() {
+        assert_eq!(job.nominal, 10 + 20 * k as u128);
+        assert!(job.release >= job.nominal && job.release <= job.nominal + 4);
+    }
+    assert!(jobs.iter().any(|job| job.release != job.nominal));
+    assert_eq!(seeded(1).get_jobs(), jobs);
+}
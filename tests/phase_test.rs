@@ -0,0 +1,106 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::dist::{Distribution, Rng};
+use cfs::proc::phase::{Phase, Script, Step};
+use cfs::proc::task::{SchedPolicy, TaskChar};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+
+fn compute(ticks: u64) -> Phase {
+    Phase::Compute(Distribution::Constant(ticks))
+}
+
+fn sleep(ticks: u64) -> Phase {
+    Phase::Sleep(Distribution::Constant(ticks))
+}
+
+fn server() -> Vec<Phase> {
+    vec![
+        compute(5),
+        Phase::Repeat(Some(3), vec![sleep(4), compute(2)]),
+        Phase::SetNice(10),
+        Phase::SetPolicy(SchedPolicy::Batch),
+        compute(10),
+    ]
+}
+
+#[test]
+fn test_script_order() {
+    let mut script = Script::new(&server());
+    let mut rng = Rng::new(0);
+
+    let mut steps = Vec::new();
+    while let Some(step) = script.next(&mut rng) {
+        steps.push(step);
+    }
+
+    assert_eq!(steps, vec![
+        Step::Compute(5),
+        Step::Sleep(4), Step::Compute(2),
+        Step::Sleep(4), Step::Compute(2),
+        Step::Sleep(4), Step::Compute(2),
+        Step::SetWeight(110),
+        Step::SetPolicy(SchedPolicy::Batch),
+        Step::Compute(10),
+    ]);
+
+    script.reset();
+    assert_eq!(script.next(&mut rng), Some(Step::Compute(5)));
+}
+
+#[test]
+fn test_nested_repeat() {
+    let phases = vec![Phase::Repeat(Some(2), vec![compute(1), Phase::Repeat(Some(3), vec![sleep(1)])])];
+    let mut script = Script::new(&phases);
+    let mut rng = Rng::new(0);
+
+    let mut steps = Vec::new();
+    while let Some(step) = script.next(&mut rng) {
+        steps.push(step);
+    }
+    assert_eq!(steps.len(), 8);
+    assert_eq!(steps.iter().filter(|step| **step == Step::Sleep(1)).count(), 6);
+}
+
+#[test]
+fn test_validate() {
+    assert!(Phase::validate(&[Phase::Repeat(None, vec![Phase::SetNice(1)])]).is_err());
+    assert!(Phase::validate(&[Phase::Repeat(Some(2), vec![])]).is_err());
+    assert!(Phase::validate(&[Phase::SetNice(20)]).is_err());
+    assert!(Phase::validate(&[Phase::SetWeight(0)]).is_err());
+    assert!(Phase::validate(&server()).is_ok());
+
+    assert!(Phase::is_endless(&[compute(1), Phase::Repeat(Some(2), vec![Phase::Repeat(None, vec![compute(1)])])]));
+    assert!(!Phase::is_endless(&server()));
+}
+
+#[test]
+fn test_phased_task() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    let mut raw = TaskChar::new(1, u64::MAX, 1, 0, 1024);
+    raw.set_phases(server());
+    rq.insert(raw.spawn());
+
+    let mut ran = Vec::new();
+    while !rq.is_finished() {
+        ran.push(rq.run().is_some());
+        rq.idle();
+        sysclock.tick();
+    }
+
+    // 21 ticks of compute with three 4 tick sleeps in between
+    assert_eq!(ran.iter().filter(|ran| **ran).count(), 21);
+    assert_eq!(&ran[5..9], &[false; 4]);
+    assert_eq!(ran.len(), 21 + 12);
+
+    let exited = &rq.get_exited()[0];
+    assert_eq!(exited.weight(), 110);
+    assert_eq!(exited.get_policy(), SchedPolicy::Batch);
+    // three sleeps and the exit
+    assert_eq!(exited.get_stats().nr_voluntary_switches, 4);
+    assert_eq!(rq.get_policy_runtime(SchedPolicy::Normal), 11);
+    assert_eq!(rq.get_policy_runtime(SchedPolicy::Batch), 10);
+}
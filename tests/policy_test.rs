@@ -0,0 +1,96 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::task::{SchedPolicy, Task, TaskStatus};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+
+fn task_with_policy(id: u16, start_time: u128, policy: SchedPolicy) -> Task {
+    let mut task = Task::new(id, 100, 100, 0, start_time, 1);
+    task.set_policy(policy);
+    task
+}
+
+#[test]
+fn test_policy_parsing() {
+    assert_eq!("batch".parse::<SchedPolicy>().unwrap(), SchedPolicy::Batch);
+    assert_eq!("SCHED_IDLE".parse::<SchedPolicy>().unwrap(), SchedPolicy::Idle);
+    assert_eq!("other".parse::<SchedPolicy>().unwrap(), SchedPolicy::Normal);
+    assert!("fifo".parse::<SchedPolicy>().is_err());
+}
+
+#[test]
+fn test_idle_weight() {
+    let mut task = Task::new(1, 100, 100, 0, 0, 20);
+    assert_eq!(task.weight(), 20);
+    task.set_policy(SchedPolicy::Idle);
+    assert_eq!(task.weight(), 3);
+}
+
+#[test]
+fn test_idle_runs_last() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    let normal = Task::new(1, 50, 50, 0, 0, 1);
+    rq.push(vec![task_with_policy(2, 0, SchedPolicy::Idle), normal]);
+
+    let mut order = Vec::new();
+    while let Some(id) = rq.run() {
+        order.push(id);
+        sysclock.tick();
+    }
+
+    assert_eq!(order.len(), 150);
+    assert!(order[..50].iter().all(|id| *id == 1));
+    assert!(order[50..].iter().all(|id| *id == 2));
+    assert_eq!(rq.get_policy_runtime(SchedPolicy::Normal), 50);
+    assert_eq!(rq.get_policy_runtime(SchedPolicy::Idle), 100);
+    assert_eq!(rq.get_policy_runtime(SchedPolicy::Batch), 0);
+}
+
+fn wakeup_preempts(curr_policy: SchedPolicy, wakee_policy: SchedPolicy) -> bool {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_slice(10);
+
+    for _ in 0..10 {
+        sysclock.tick();
+    }
+    rq.insert(task_with_policy(1, 0, curr_policy));
+    assert_eq!(rq.run(), Some(1));
+    assert_eq!(rq.get_curr().unwrap().get_status(), TaskStatus::Running);
+
+    rq.insert(task_with_policy(2, sysclock.time(), wakee_policy));
+    sysclock.tick();
+
+    rq.run() == Some(2)
+}
+
+#[test]
+fn test_wakeup_preemption() {
+    assert!(wakeup_preempts(SchedPolicy::Normal, SchedPolicy::Normal));
+    assert!(wakeup_preempts(SchedPolicy::Batch, SchedPolicy::Normal));
+    assert!(wakeup_preempts(SchedPolicy::Idle, SchedPolicy::Normal));
+    assert!(!wakeup_preempts(SchedPolicy::Normal, SchedPolicy::Batch));
+    assert!(!wakeup_preempts(SchedPolicy::Normal, SchedPolicy::Idle));
+}
+
+#[test]
+fn test_slice() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_slice(4);
+
+    rq.push(vec![
+        task_with_policy(1, 0, SchedPolicy::Batch),
+        task_with_policy(2, 0, SchedPolicy::Batch)
+    ]);
+
+    let mut order = Vec::new();
+    for _ in 0..8 {
+        order.push(rq.run().unwrap());
+        sysclock.tick();
+    }
+    assert_eq!(order, vec![1, 1, 1, 1, 2, 2, 2, 2]);
+}
@@ -0,0 +1,124 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+mod common;
+
+use cfs::proc::task::{SchedPolicy, TaskChar, TaskStatus};
+use cfs::sched::{fair::FairAlgorithm, procfs};
+
+use common::Sim;
+
+// runs the tasks for the given number of ticks and returns the views then
+fn views(tasks: Vec<TaskChar>, ticks: u128, view: impl Fn(&FairAlgorithm) -> Vec<String>) -> Vec<String> {
+    let mut sim = Sim::new(tasks.iter().map(TaskChar::spawn), |_| ());
+    sim.run_until(ticks);
+    view(&sim.rq)
+}
+
+fn tasks() -> Vec<TaskChar> {
+    let mut batch = TaskChar::new(2, 6, 6, 0, 1024);
+    batch.set_policy(SchedPolicy::Batch);
+    batch.set_name("batch");
+    vec![TaskChar::new(1, 4, 2, 3, 1024), batch]
+}
+
+fn value<'a>(view: &'a str, name: &str) -> &'a str {
+    let line = view.lines().find(|line| line.split(':').next().unwrap().trim() == name).unwrap();
+    line.split(':').nth(1).unwrap().trim()
+}
+
+#[test]
+fn test_task_sched() {
+    let out = views(tasks(), 6, |rq| vec![procfs::task_sched(rq, 1).unwrap(), procfs::task_sched(rq, 2).unwrap()]);
+
+    assert!(out[0].starts_with("task-1 (1, #threads: 1)\n-------------------------------------------------------------------\n"));
+    assert_eq!(out[0].lines().nth(2).unwrap(), "se.vruntime                                  :                    0");
+    // task 1 ran for two ticks before its io, and is back in the runqueue
+    assert_eq!(value(&out[0], "se.sum_exec_runtime"), "2.000000");
+    assert_eq!(value(&out[0], "iowait_sum"), "2.000000");
+    assert_eq!(value(&out[0], "nr_voluntary_switches"), "1");
+    assert_eq!(value(&out[0], "nr_wakeups"), "1");
+    assert_eq!(value(&out[0], "prio"), "120");
+
+    assert_eq!(value(&out[1], "policy"), "3");
+    assert_eq!(value(&out[1], "se.load.weight"), "1024");
+    let wait_sum: f64 = value(&out[1], "wait_sum").parse().unwrap();
+    let exec: f64 = value(&out[1], "se.sum_exec_runtime").parse().unwrap();
+    assert_eq!(wait_sum + exec, 6.0);
+}
+
+#[test]
+fn test_schedstat() {
+    let out = views(tasks(), 6, |rq| vec![
+        procfs::task_schedstat(rq, 1).unwrap(),
+        procfs::task_schedstat(rq, 2).unwrap(),
+        procfs::schedstat(rq)
+    ]);
+
+    let task: Vec<u128> = out[0].split_whitespace().map(|value| value.parse().unwrap()).collect();
+    assert_eq!(task[..2], [2_000_000, 2_000_000]);
+    let batch: Vec<u128> = out[1].split_whitespace().map(|value| value.parse().unwrap()).collect();
+    assert_eq!(batch[0] + batch[1], 6_000_000);
+
+    let lines: Vec<&str> = out[2].lines().collect();
+    assert_eq!(lines[..2], ["version 15", "timestamp 6"]);
+    let cpu: Vec<u128> = lines[2].split_whitespace().skip(1).map(|value| value.parse().unwrap()).collect();
+    // no idle ticks, and every tick ran a task
+    assert_eq!(cpu[3], 0);
+    assert_eq!(cpu[6], 6_000_000);
+    // the run delay of waits that ended, not task 1's in the runqueue now
+    assert_eq!(cpu[7], task[1] + batch[1] - 1_000_000);
+    assert_eq!(cpu[8], task[2] + batch[2]);
+}
+
+#[test]
+fn test_sched_debug() {
+    let out = views(tasks(), 3, |rq| vec![procfs::sched_debug(rq)]);
+    let out = &out[0];
+
+    assert!(out.starts_with("Sched Debug Version: v0.11, rust-cfs\n"));
+    assert_eq!(value(out, ".nr_uninterruptible"), "1");
+    assert_eq!(value(out, ".load"), "1024");
+    let rows: Vec<&str> = out.lines().skip_while(|line| !line.starts_with("----")).skip(1).collect();
+    assert_eq!(rows.len(), 2);
+    assert!(rows[0].starts_with(" D          task-1     1 "));
+    assert!(rows[1].starts_with(" R           batch     2 "));
+    assert!(rows[0].ends_with("1.000000             2.000000         0.000000"));
+}
+
+// sched_debug and the time series show the same min_vruntime at the same
+// tick, and the same vruntimes for the tasks queued throughout it
+#[test]
+fn test_sched_debug_matches_series() {
+    let tasks = [TaskChar::new(1, 9, 3, 2, 1), TaskChar::new(2, 12, 4, 0, 2), TaskChar::new(3, 6, 6, 0, 3)];
+    for ticks in 1..20 {
+        let mut sim = Sim::new(tasks.iter().map(TaskChar::spawn), |rq| rq.set_sampling(Some(1)).unwrap());
+        sim.run_until(ticks);
+        sim.step();
+        let rq = &sim.rq;
+
+        let out = procfs::sched_debug(rq);
+        let series = rq.get_series().unwrap();
+        let sample = series.get_runqueue().last().unwrap();
+        assert_eq!(value(&out, ".min_vruntime"), sample.min_vruntime.to_string());
+        let rows = out.lines().skip_while(|line| !line.starts_with("----")).skip(1);
+        for row in rows {
+            let columns: Vec<&str> = row.split_whitespace().collect();
+            let (id, key) = (columns[columns.len() - 7], columns[columns.len() - 6]);
+            let task = series.get_tasks().iter().rev().find(|task| task.id.to_string() == id).unwrap();
+            // tasks that blocked or woke up later in the tick are not comparable
+            let queued = columns[0].ends_with('R');
+            if queued && matches!(task.state, TaskStatus::Running | TaskStatus::Waiting) {
+                assert_eq!(task.vruntime.to_string(), key);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_gone() {
+    let out = views(tasks(), 20, |rq| vec![format!("{:?}", procfs::task_sched(rq, 1)), format!("{:?}", procfs::task_schedstat(rq, 7))]);
+    assert_eq!(out, ["None", "None"]);
+}
+
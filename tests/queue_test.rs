@@ -2,7 +2,7 @@
 
 extern crate rust_cfs as cfs;
 
-use cfs::proc::task::{Task, TaskStatus};
+use cfs::proc::task::{Task, TaskChar, TaskStatus};
 use cfs::proc::queue::TaskQueue;
 
 #[test]
@@ -76,3 +76,43 @@ fn test_remove() {
     }
     assert_eq!(idx, 0);
 }
+
+#[test]
+fn test_release() {
+    let mut early = TaskChar::new(1, 10, 5, 2, 1);
+    early.set_arrival(0);
+    let mut late = TaskChar::new(2, 10, 5, 2, 1);
+    late.set_arrival(7);
+    let mut later = TaskChar::new(3, 10, 5, 2, 1);
+    later.set_arrival(7);
+    let mut last = TaskChar::new(4, 10, 5, 2, 1);
+    last.set_arrival(12);
+
+    let mut task_queue = TaskQueue::new();
+    for raw in [last, later, late, early].iter() {
+        task_queue.add(raw.spawn());
+    }
+    assert_eq!(task_queue.next_time(), Some(0));
+
+    let mut released = Vec::new();
+    for now in 0..20 {
+        for task in task_queue.release(now) {
+            assert_eq!(task.get_start_time(), now);
+            released.push((task.get_id(), now));
+        }
+    }
+
+    assert_eq!(released, vec![(1, 0), (3, 7), (2, 7), (4, 12)]);
+    assert!(task_queue.is_empty());
+    assert_eq!(task_queue.next_time(), None);
+}
+
+#[test]
+fn test_pop_from_zero() {
+    let mut task_queue = TaskQueue::new();
+    task_queue.add(Task::new(1, 10, 5, 2, 4, 1));
+    task_queue.add(Task::new(2, 10, 5, 2, 0, 1));
+
+    assert_eq!(task_queue.pop()[0].get_id(), 2);
+    assert_eq!(task_queue.pop()[0].get_id(), 1);
+}
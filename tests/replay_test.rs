@@ -0,0 +1,104 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+mod common;
+
+use cfs::proc::dist::Distribution;
+use cfs::proc::phase::Phase;
+use cfs::proc::task::TaskChar;
+use cfs::trace::{ftrace, Trace};
+use cfs::workload::replay;
+
+use common::Sim;
+
+const TRACE: &str = "\
+# tracer: nop
+#
+#           TASK-PID     CPU#  ||||   TIMESTAMP  FUNCTION
+#              | |         |   ||||      |         |
+          <idle>-0       [000] d... 5000.000000: sched_wakeup_new: comm=make pid=4242 prio=120 target_cpu=000
+          <idle>-0       [000] d... 5000.000100: sched_switch: prev_comm=swapper/0 prev_pid=0 prev_prio=120 prev_state=R ==> next_comm=make next_pid=4242 next_prio=120
+            make-4242    [000] d... 5000.003000: sched_wakeup: comm=Web Content pid=77 prio=125 target_cpu=000
+            make-4242    [000] d... 5000.004100: sched_switch: prev_comm=make prev_pid=4242 prev_prio=120 prev_state=R+ ==> next_comm=Web Content next_pid=77 next_prio=125
+     Web Content-77      [000] d... 5000.006000: sched_switch: prev_comm=Web Content prev_pid=77 prev_prio=125 prev_state=S ==> next_comm=make next_pid=4242 next_prio=120
+            make-4242    [000] d... 5000.007000: sched_switch: prev_comm=make prev_pid=4242 prev_prio=120 prev_state=D ==> next_comm=swapper/0 next_pid=0 next_prio=120
+          <idle>-0       [000] d... 5000.010000: sched_wakeup: comm=make pid=4242 prio=120 target_cpu=000
+          <idle>-0       [000] d... 5000.010000: sched_switch: prev_comm=swapper/0 prev_pid=0 prev_prio=120 prev_state=R ==> next_comm=make next_pid=4242 next_prio=120
+            make-4242    [000] d... 5000.012000: sched_process_exit: comm=make pid=4242 prio=120
+            make-4242    [000] d... 5000.012000: sched_switch: prev_comm=make prev_pid=4242 prev_prio=120 prev_state=X ==> next_comm=swapper/0 next_pid=0 next_prio=120
+";
+
+fn compute(ticks: u64) -> Phase {
+    Phase::Compute(Distribution::Constant(ticks))
+}
+
+fn sleep(ticks: u64) -> Phase {
+    Phase::Sleep(Distribution::Constant(ticks))
+}
+
+fn simulate(tasks: Vec<TaskChar>, tracing: bool) -> (Vec<(u128, u16)>, Option<Trace>) {
+    let mut sim = Sim::new(tasks.iter().map(TaskChar::spawn), |rq| rq.set_tracing(tracing));
+    let ran = sim.run();
+    (ran, sim.rq.get_trace().cloned())
+}
+
+#[test]
+fn test_parse() {
+    let tasks = replay::parse(TRACE).unwrap();
+    assert_eq!(tasks.len(), 2);
+
+    // ids in order of appearance, times from the first event in milliseconds
+    let make = &tasks[0];
+    assert_eq!((make.get_id(), make.get_name(), make.get_arrival(), make.get_weight()), (1, "make", 0, 1024));
+    // the preemption does not end the first burst, the uninterruptible sleep does
+    assert_eq!(make.get_phases().unwrap(), [compute(5), sleep(3), compute(2)]);
+
+    let web = &tasks[1];
+    assert_eq!((web.get_id(), web.get_name(), web.get_arrival(), web.get_weight()), (2, "Web Content", 3, 335));
+    assert_eq!(web.get_phases().unwrap(), [compute(2)]);
+
+    // perf sched script puts the subsystem in front of the event
+    let perf = TRACE.replace(": sched_", ": sched:sched_");
+    assert_eq!(replay::parse(&perf).unwrap().len(), 2);
+    assert!(replay::is_trace(&perf));
+    assert!(!replay::is_trace("5 5 0 1 0 normal 0\n"));
+
+    // a coarser tick
+    let tasks = replay::parse_with_tick(TRACE, 2000).unwrap();
+    assert_eq!(tasks[0].get_phases().unwrap(), [compute(3), sleep(2), compute(1)]);
+}
+
+#[test]
+fn test_round_trip() {
+    let original = vec![TaskChar::new(1, 4, 2, 3, 1024)];
+    let (ran, trace) = simulate(original, true);
+    let tasks = replay::parse(&ftrace::to_text(&trace.unwrap())).unwrap();
+
+    assert_eq!(tasks[0].get_phases().unwrap(), [compute(2), sleep(2), compute(2)]);
+    assert_eq!(simulate(tasks, false).0, ran);
+}
+
+#[test]
+fn test_arrivals() {
+    let mut late = TaskChar::new(2, 3, 3, 0, 1024);
+    late.set_arrival(5);
+    let (_, trace) = simulate(vec![TaskChar::new(1, 2, 2, 0, 15), late], true);
+    let tasks = replay::parse(&ftrace::to_text(&trace.unwrap())).unwrap();
+
+    assert_eq!(tasks.iter().map(|task| task.get_arrival()).collect::<Vec<u128>>(), [0, 5]);
+    assert_eq!(tasks.iter().map(|task| task.get_weight()).collect::<Vec<u32>>(), [15, 1024]);
+}
+
+#[test]
+fn test_errors() {
+    let err = replay::parse("# tracer: nop\n").err().unwrap();
+    assert_eq!((err.get_line(), err.get_message()), (0, "no sched_switch events found"));
+
+    let err = replay::parse(&TRACE.replace("next_pid=77", "next_pid=web")).err().unwrap();
+    assert_eq!((err.get_line(), err.get_message()), (8, "malformed sched_switch event"));
+
+    let err = replay::parse(&TRACE.replace("5000.010000", "4999.000000")).err().unwrap();
+    assert_eq!(err.get_line(), 11);
+    assert!(replay::parse_with_tick(TRACE, 0).is_err());
+}
@@ -0,0 +1,146 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::io::device::{Device, IoPolicy};
+use cfs::proc::dist::Distribution;
+use cfs::proc::phase::Phase;
+use cfs::proc::signal::Signal;
+use cfs::proc::task::{TaskChar, TaskStatus};
+use cfs::sched::dynamic::DynamicWeight;
+use cfs::sched::scheduler::Scheduler;
+use cfs::stats::{Report, Summary, Unfinished};
+use cfs::sync::{SyncKind, SyncObject};
+
+fn task(id: u16, cpu_time: u64, cpu_burst: u64, io_burst: u64, arrival: u128) -> TaskChar {
+    let mut raw = TaskChar::new(id, cpu_time, cpu_burst, io_burst, 1);
+    raw.set_arrival(arrival);
+    raw
+}
+
+#[test]
+fn test_summary() {
+    let samples: Vec<f64> = (1..=100).map(|n| n as f64).collect();
+    let summary = Summary::of(&samples);
+    assert_eq!(summary.count, 100);
+    assert_eq!(summary.mean, 50.5);
+    assert_eq!((summary.p50, summary.p95, summary.p99, summary.max), (50.0, 95.0, 99.0, 100.0));
+
+    // nearest rank, not interpolated
+    let summary = Summary::of(&[4.0, 1.0, 3.0]);
+    assert_eq!((summary.p50, summary.p95, summary.max), (3.0, 4.0, 4.0));
+    assert_eq!(Summary::of(&[]), Summary::default());
+}
+
+#[test]
+fn test_report() {
+    let tasks = vec![task(1, 5, 5, 0, 0), task(2, 3, 3, 0, 10), task(3, 2, 2, 0, 10)];
+    let report = Scheduler::new().run(tasks);
+
+    assert_eq!(report.tasks.iter().map(|task| task.id).collect::<Vec<u16>>(), [1, 2, 3]);
+    let first = report.get_task(1).unwrap();
+    assert_eq!((first.first_run, first.completion, first.wait_time, first.slowdown), (Some(0), 5, 0, 1.0));
+
+    // 2 and 3 take turns from 10 on, 2 running first
+    let second = report.get_task(2).unwrap();
+    assert_eq!((second.arrival, second.first_run, second.completion), (10, Some(10), 15));
+    assert_eq!((second.turnaround, second.wait_time, second.response), (5, 2, 0));
+    let third = report.get_task(3).unwrap();
+    assert_eq!((third.first_run, third.completion, third.response), (Some(11), 14, 1));
+    assert_eq!(third.slowdown, 2.0);
+
+    assert_eq!(report.turnaround.max, 5.0);
+    assert_eq!(report.response.mean, 1.0 / 3.0);
+}
+
+#[test]
+fn test_time_accounted() {
+    let tasks = vec![task(1, 6, 2, 3, 0), task(2, 5, 1, 2, 0), task(3, 4, 4, 0, 2)];
+    let report = Scheduler::new().run(tasks);
+
+    // every tick between arrival and completion is spent running, waiting or on i/o
+    for task in report.tasks.iter() {
+        assert_eq!(task.turnaround, task.cpu_time as u128 + task.wait_time + task.io_time);
+    }
+    // two io bursts of three ticks, with two ticks queued behind task 2
+    assert_eq!(report.get_task(1).unwrap().io_time, 8);
+    assert_eq!(report.get_task(3).unwrap().io_time, 0);
+}
+
+#[test]
+fn test_device_time() {
+    let mut raw = task(1, 6, 2, 1, 0);
+    raw.set_io_device("disk");
+    let mut scheduler = Scheduler::new();
+    scheduler.set_devices(vec![Device::new("disk", Distribution::Constant(5), IoPolicy::Fifo)]);
+    let report: Report = scheduler.run(vec![raw]);
+
+    // two requests of five ticks each, served without queueing
+    let task = &report.tasks[0];
+    assert_eq!((task.completion, task.cpu_time, task.io_time, task.wait_time), (16, 6, 10, 0));
+}
+
+#[test]
+fn test_weight_adjustments() {
+    let mut scheduler = Scheduler::new();
+    scheduler.set_dynamic_weight(Some(DynamicWeight::new(4, 16, 50)));
+    let report = scheduler.run(vec![TaskChar::new(1, 300, 300, 0, 10), TaskChar::new(2, 30, 1, 20, 10)]);
+
+    // the hog loses weight, the io task gains it
+    let adjustments = &report.weight_adjustments;
+    assert!(adjustments.iter().any(|adj| adj.id == 1 && adj.new_weight < adj.old_weight));
+    assert!(adjustments.iter().any(|adj| adj.id == 2 && adj.new_weight > adj.old_weight));
+    assert!(adjustments.windows(2).all(|pair| pair[0].time <= pair[1].time));
+
+    scheduler.set_dynamic_weight(None);
+    let report = scheduler.run(vec![TaskChar::new(1, 300, 300, 0, 10)]);
+    assert!(report.weight_adjustments.is_empty());
+}
+
+#[test]
+fn test_unfinished() {
+    // stopped and never continued
+    let mut scheduler = Scheduler::new();
+    scheduler.send_signal(2, 1, Signal::Stop);
+    let report = scheduler.run(vec![task(1, 5, 5, 0, 0), task(2, 5, 5, 0, 0)]);
+    assert_eq!(report.tasks.iter().map(|task| task.id).collect::<Vec<u16>>(), [2]);
+    assert_eq!(report.get_task(2).unwrap().state, TaskStatus::Dead);
+    let stopped = &report.unfinished[0];
+    assert_eq!((stopped.id, stopped.state, stopped.reason.clone()), (1, TaskStatus::Stopped, Unfinished::Stopped));
+    assert!(stopped.cpu_time > 0);
+    assert!(!report.is_complete() && !report.is_deadlocked());
+
+    // each holds the lock the other one wants
+    let lock = |name: &str| Phase::Lock(String::from(name));
+    let unlock = |name: &str| Phase::Unlock(String::from(name));
+    let compute = Phase::Compute(Distribution::Constant(2));
+    let mut first = TaskChar::new(1, u64::MAX, 1, 0, 1);
+    first.set_phases(vec![lock("a"), compute.clone(), lock("b"), unlock("b"), unlock("a")]);
+    let mut second = TaskChar::new(2, u64::MAX, 1, 0, 1);
+    second.set_phases(vec![lock("b"), compute, lock("a"), unlock("a"), unlock("b")]);
+
+    let mut scheduler = Scheduler::new();
+    scheduler.set_sync_objects(vec![SyncObject::new("a", SyncKind::Mutex), SyncObject::new("b", SyncKind::Mutex)]);
+    let report = scheduler.run(vec![first, second]);
+    assert!(report.tasks.is_empty());
+    let reasons: Vec<(u16, Unfinished)> = report.unfinished.iter().map(|task| (task.id, task.reason.clone())).collect();
+    assert_eq!(reasons, [(1, Unfinished::Blocked(String::from("b"))), (2, Unfinished::Blocked(String::from("a")))]);
+    assert!(report.is_deadlocked());
+
+    let report = scheduler.run(vec![task(1, 5, 5, 0, 0)]);
+    assert!(report.is_complete());
+}
+
+#[test]
+fn test_display() {
+    let mut scheduler = Scheduler::new();
+    scheduler.send_signal(2, 1, Signal::Stop);
+    let report = scheduler.run(vec![task(1, 5, 5, 0, 0), task(2, 3, 3, 0, 0)]);
+    let text = report.to_string();
+
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "Task id 2: 0 yields, 1 voluntary and 1 involuntary switches");
+    assert!(lines[1].starts_with("Task id 2: arrival 0, first run "));
+    assert!(lines.contains(&"SCHED_NORMAL tasks: 2, cpu time: 4"));
+    assert_eq!(lines.last(), Some(&"Task id 1 is still stopped"));
+}
@@ -0,0 +1,95 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+mod common;
+
+use cfs::proc::task::{TaskChar, TaskStatus};
+use cfs::stats::series::{TimeSeries, Value};
+
+use common::Sim;
+
+fn sampled(tasks: Vec<TaskChar>, interval: u64) -> (TimeSeries, u128) {
+    let mut sim = Sim::new(tasks.iter().map(TaskChar::spawn), |rq| rq.set_sampling(Some(interval)).unwrap());
+    sim.run();
+    (sim.rq.get_series().unwrap().clone(), sim.time())
+}
+
+#[test]
+fn test_samples() {
+    let mut late = TaskChar::new(2, 2, 2, 0, 2048);
+    late.set_arrival(3);
+    let (series, end) = sampled(vec![TaskChar::new(1, 4, 2, 3, 1024), late], 1);
+
+    // a sample for every tick, idle ones included
+    assert_eq!(series.get_runqueue().len() as u128, end);
+    let first = series.get_runqueue()[0];
+    assert_eq!((first.time, first.nr_running, first.load, first.curr), (0, 1, 1024, Some(1)));
+    // task 1 is on io when task 2 arrives
+    let arrived = series.get_runqueue()[3];
+    assert_eq!((arrived.nr_running, arrived.load, arrived.curr), (1, 2048, Some(2)));
+    let states: Vec<(u16, TaskStatus)> = series.get_tasks().iter()
+        .filter(|sample| sample.time == 3)
+        .map(|sample| (sample.id, sample.state))
+        .collect();
+    assert_eq!(states, [(1, TaskStatus::Idle), (2, TaskStatus::Running)]);
+
+    // runtime only grows while the task runs
+    let runtimes: Vec<u64> = series.get_tasks().iter().filter(|sample| sample.id == 1).map(|sample| sample.runtime).collect();
+    assert!(runtimes.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(series.get_runqueue().windows(2).all(|pair| pair[0].min_vruntime <= pair[1].min_vruntime));
+}
+
+#[test]
+fn test_interval() {
+    let (series, _) = sampled(vec![TaskChar::new(1, 10, 10, 0, 1024)], 3);
+    let times: Vec<u128> = series.get_runqueue().iter().map(|sample| sample.time).collect();
+    assert_eq!(times, [0, 3, 6, 9]);
+    assert_eq!(series.get_tasks().len(), 4);
+}
+
+#[test]
+fn test_csv() {
+    let (series, _) = sampled(vec![TaskChar::new(1, 2, 1, 2, 1024)], 1);
+    let table = series.runqueue_table();
+    assert_eq!(table.names(), ["time", "cpu", "nr_running", "load", "min_vruntime", "curr"]);
+
+    // nothing runs while the task is on io
+    let csv = table.select(&["time", "curr", "nr_running"]).unwrap().to_csv();
+    assert_eq!(csv, "time,curr,nr_running\n0,1,1\n1,,0\n2,1,1\n");
+
+    let tasks = series.task_table();
+    assert_eq!(tasks.rows[0][4], Value::Text(String::from("running")));
+    assert_eq!(tasks.select(&["state"]).unwrap().to_csv().lines().nth(2), Some("idle"));
+}
+
+#[test]
+fn test_unknown_column() {
+    let (series, _) = sampled(vec![TaskChar::new(1, 1, 1, 0, 1024)], 1);
+    assert!(series.task_table().select(&["weight"]).is_err());
+    assert!(series.task_table().select(&["id", "state"]).is_ok());
+}
+
+#[test]
+fn test_zero_interval() {
+    assert!(TimeSeries::new(0).is_err());
+    let mut sim = Sim::new(Vec::new(), |_| ());
+    assert!(sim.rq.set_sampling(Some(0)).is_err());
+    assert!(sim.rq.get_series().is_none());
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn test_parquet() {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let (series, _) = sampled(vec![TaskChar::new(1, 2, 1, 2, 1024)], 1);
+    let path = std::env::temp_dir().join(format!("series_test_{}.parquet", std::process::id()));
+    series.runqueue_table().write_parquet(std::fs::File::create(&path).unwrap()).unwrap();
+
+    let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+    let rows: Vec<String> = reader.get_row_iter(None).unwrap().map(|row| row.unwrap().to_string()).collect();
+    assert_eq!(rows[1], "{time: 1, cpu: 0, nr_running: 0, load: 0, min_vruntime: 0, curr: null}");
+    std::fs::remove_file(&path).unwrap();
+}
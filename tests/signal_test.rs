@@ -0,0 +1,123 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::dist::Distribution;
+use cfs::proc::phase::Phase;
+use cfs::proc::signal::Signal;
+use cfs::proc::task::{TaskChar, TaskStatus};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use cfs::sync::{SyncKind, SyncObject};
+
+fn compute(ticks: u64) -> Phase {
+    Phase::Compute(Distribution::Constant(ticks))
+}
+
+fn task(id: u16, phases: Vec<Phase>) -> TaskChar {
+    let mut raw = TaskChar::new(id, u64::MAX, 1, 0, 1);
+    raw.set_phases(phases);
+    raw
+}
+
+// runs until done, delivering each signal right before its tick
+fn run(rq: &mut FairAlgorithm, sysclock: &mut Clock, signals: &[(u128, u16, Signal)]) -> Vec<Option<u16>> {
+    let mut ran = Vec::new();
+    while !rq.is_finished() || signals.iter().any(|(at, _, _)| *at >= sysclock.time()) {
+        for (_, id, signal) in signals.iter().filter(|(at, _, _)| *at == sysclock.time()) {
+            assert!(rq.signal(*id, *signal));
+        }
+        ran.push(rq.run());
+        rq.idle();
+        sysclock.tick();
+    }
+    ran
+}
+
+#[test]
+fn test_stop_and_continue() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.insert(task(1, vec![compute(4)]).spawn());
+    rq.insert(task(2, vec![compute(4)]).spawn());
+
+    let ran = run(&mut rq, &mut sysclock, &[(2, 1, Signal::Stop), (5, 1, Signal::Cont)]);
+    let ran: Vec<u16> = ran.into_iter().flatten().collect();
+    assert_eq!(ran, [1, 2, 2, 2, 2, 1, 1, 1]);
+
+    let stats = rq.get_exited()[1].get_stats();
+    assert_eq!(rq.get_exited()[1].get_id(), 1);
+    assert_eq!(stats.nr_stops, 1);
+    assert!(!stats.killed);
+}
+
+#[test]
+fn test_stop_while_asleep() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.insert(task(1, vec![compute(1), Phase::Sleep(Distribution::Constant(2)), compute(1)]).spawn());
+
+    // the sleep runs out while stopped, but the task only runs once continued
+    let ran = run(&mut rq, &mut sysclock, &[(1, 1, Signal::Stop), (6, 1, Signal::Cont)]);
+    assert_eq!(ran, [Some(1), None, None, None, None, None, Some(1)]);
+    assert!(!rq.is_stopped(1));
+}
+
+#[test]
+fn test_stopped_task_is_left_behind() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.insert(task(1, vec![compute(3)]).spawn());
+    rq.insert(task(2, vec![compute(3)]).spawn());
+
+    run(&mut rq, &mut sysclock, &[(1, 2, Signal::Stop)]);
+    assert_eq!(rq.get_stopped().len(), 1);
+    assert_eq!(rq.get_stopped()[0].get_status(), TaskStatus::Stopped);
+    assert_eq!(rq.get_exited().len(), 1);
+}
+
+#[test]
+fn test_kill() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_sync_object(SyncObject::new("m", SyncKind::Mutex));
+
+    let locked = vec![Phase::Lock(String::from("m")), compute(5), Phase::Unlock(String::from("m"))];
+    rq.insert(task(1, locked.clone()).spawn());
+    rq.insert(task(2, locked).spawn());
+    rq.insert(task(3, vec![compute(20)]).spawn());
+
+    // killing the holder hands the mutex on, killing a runnable task takes it out of the tree
+    let ran = run(&mut rq, &mut sysclock, &[(2, 1, Signal::Kill), (3, 3, Signal::Kill)]);
+    let ran: Vec<u16> = ran.into_iter().flatten().collect();
+    assert_eq!(ran, [1, 3, 3, 2, 2, 2, 2, 2]);
+
+    let exited: Vec<(u16, bool)> = rq.get_exited().iter().map(|task| (task.get_id(), task.get_stats().killed)).collect();
+    assert_eq!(exited, [(1, true), (3, true), (2, false)]);
+    assert_eq!(rq.get_exited()[0].get_status(), TaskStatus::Dead);
+    assert_eq!(rq.get_sync_objects()[0].get_stats().nr_handoffs, 1);
+
+    assert!(!rq.signal(1, Signal::Kill));
+    assert!(!rq.signal(9, Signal::Stop));
+}
+
+#[test]
+fn test_kill_stopped_and_blocked() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.insert(task(1, vec![compute(1), Phase::Sleep(Distribution::Constant(10)), compute(1)]).spawn());
+    rq.insert(task(2, vec![compute(10)]).spawn());
+
+    let ran = run(&mut rq, &mut sysclock, &[(2, 1, Signal::Kill), (3, 2, Signal::Stop), (4, 2, Signal::Kill)]);
+    assert_eq!(ran, [Some(1), Some(2), Some(2), None, None]);
+    assert!(rq.get_stopped().is_empty());
+    assert!(rq.get_exited().iter().all(|task| task.get_stats().killed));
+}
+
+#[test]
+fn test_parse_signal() {
+    assert_eq!("SIGSTOP".parse::<Signal>(), Ok(Signal::Stop));
+    assert_eq!("cont".parse::<Signal>(), Ok(Signal::Cont));
+    assert_eq!("sigkill".parse::<Signal>(), Ok(Signal::Kill));
+    assert!("SIGHUP".parse::<Signal>().is_err());
+    assert_eq!(Signal::Kill.to_string(), "SIGKILL");
+}
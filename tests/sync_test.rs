@@ -0,0 +1,257 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::dist::Distribution;
+use cfs::proc::phase::Phase;
+use cfs::proc::task::TaskChar;
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+use cfs::sync::{SyncKind, SyncObject};
+use cfs::workload::spec;
+
+fn compute(ticks: u64) -> Phase {
+    Phase::Compute(Distribution::Constant(ticks))
+}
+
+fn lock(name: &str) -> Phase {
+    Phase::Lock(String::from(name))
+}
+
+fn unlock(name: &str) -> Phase {
+    Phase::Unlock(String::from(name))
+}
+
+fn task(id: u16, phases: Vec<Phase>) -> TaskChar {
+    let mut raw = TaskChar::new(id, u64::MAX, 1, 0, 1);
+    raw.set_phases(phases);
+    raw
+}
+
+fn run(rq: &mut FairAlgorithm, sysclock: &mut Clock) -> Vec<u16> {
+    let mut ran = Vec::new();
+    while !rq.is_finished() {
+        if let Some(id) = rq.run() {
+            ran.push(id);
+        }
+        rq.idle();
+        sysclock.tick();
+    }
+    ran
+}
+
+#[test]
+fn test_mutex_handoff() {
+    let mut mutex = SyncObject::new("m", SyncKind::Mutex);
+    assert_eq!(mutex.try_acquire(1, 0), Ok(true));
+    assert_eq!(mutex.try_acquire(2, 1), Ok(false));
+    mutex.enqueue(2, 1);
+    mutex.enqueue(3, 2);
+
+    // the lock never goes free while the waiters queue up behind each other
+    assert_eq!(mutex.release(1, 5), Ok(Some(2)));
+    assert_eq!(mutex.get_holders(), [2]);
+    assert_eq!(mutex.release(2, 6), Ok(Some(3)));
+    assert_eq!(mutex.release(3, 10), Ok(None));
+    assert_eq!(mutex.get_count(), 1);
+
+    let stats = mutex.get_stats();
+    assert_eq!((stats.nr_acquires, stats.nr_contended, stats.nr_handoffs), (3, 2, 2));
+    assert_eq!(stats.max_convoy, 2);
+    assert_eq!((stats.hold_sum, stats.hold_max), (10, 5));
+    assert_eq!((stats.wait_sum, stats.wait_max), (8, 4));
+    assert_eq!(stats.max_waiters, 2);
+}
+
+#[test]
+fn test_unlock_by_other_task() {
+    let mut mutex = SyncObject::new("m", SyncKind::Mutex);
+    assert_eq!(mutex.try_acquire(1, 0), Ok(true));
+    assert!(mutex.release(2, 1).is_err());
+    assert!(mutex.try_acquire(1, 2).is_err());
+    assert_eq!(mutex.get_holders(), [1]);
+
+    let mut condvar = SyncObject::new("c", SyncKind::CondVar);
+    assert!(condvar.try_acquire(1, 0).is_err());
+    assert!(mutex.wake(None, 3).is_err());
+}
+
+#[test]
+fn test_lock_blocks_task() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_sync_object(SyncObject::new("m", SyncKind::Mutex));
+    rq.add_sync_object(SyncObject::new("sem", SyncKind::Semaphore(2)));
+
+    let phases = vec![compute(1), lock("m"), compute(3), unlock("m")];
+    rq.insert(task(1, phases.clone()).spawn());
+    rq.insert(task(2, phases).spawn());
+
+    // task 2 leaves the tree while task 1 holds the lock
+    assert_eq!(run(&mut rq, &mut sysclock), [1, 2, 1, 1, 1, 2, 2, 2]);
+    assert_eq!(rq.get_sync_objects()[0].get_stats().nr_handoffs, 1);
+    assert!(rq.get_locked().is_empty());
+
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_sync_object(SyncObject::new("sem", SyncKind::Semaphore(2)));
+    for id in 1..=3 {
+        rq.insert(task(id, vec![lock("sem"), compute(2), unlock("sem")]).spawn());
+    }
+
+    // two tasks fit, the third waits for one of them
+    assert_eq!(run(&mut rq, &mut sysclock), [1, 2, 1, 2, 3, 3]);
+    let stats = rq.get_sync_objects()[0].get_stats();
+    assert_eq!((stats.nr_acquires, stats.nr_contended), (3, 1));
+}
+
+#[test]
+fn test_condvar() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_sync_object(SyncObject::new("m", SyncKind::Mutex));
+    rq.add_sync_object(SyncObject::new("ready", SyncKind::CondVar));
+
+    let waiter = vec![
+        lock("m"),
+        Phase::WaitOn(String::from("ready"), Some(String::from("m"))),
+        compute(1),
+        unlock("m")
+    ];
+    rq.insert(task(1, waiter.clone()).spawn());
+    rq.insert(task(2, waiter).spawn());
+    rq.insert(task(3, vec![compute(2), lock("m"), Phase::Broadcast(String::from("ready")), compute(1), unlock("m")]).spawn());
+
+    // both waiters wake up on the broadcast but take the mutex one after the other
+    assert_eq!(run(&mut rq, &mut sysclock), [3, 3, 3, 1, 2]);
+    let ready = rq.get_sync_objects()[1].get_stats();
+    assert_eq!((ready.nr_contended, ready.nr_wakeups), (2, 2));
+    let mutex = rq.get_sync_objects()[0].get_stats();
+    assert_eq!(mutex.nr_handoffs, 2);
+}
+
+#[test]
+fn test_wait_queue() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_sync_object(SyncObject::new("futex", SyncKind::WaitQueue));
+
+    for id in 1..=2 {
+        rq.insert(task(id, vec![Phase::WaitOn(String::from("futex"), None), compute(1)]).spawn());
+    }
+    rq.insert(task(3, vec![compute(2), Phase::Signal(String::from("futex")), compute(2)]).spawn());
+
+    // only one waiter is woken, the other one is left behind
+    let ran = run(&mut rq, &mut sysclock);
+    assert_eq!(ran.iter().filter(|id| **id == 1).count(), 1);
+    assert!(!ran.contains(&2));
+    let deadlocked: Vec<u16> = rq.get_deadlocked().iter().map(|task| task.get_id()).collect();
+    assert_eq!(deadlocked, [2]);
+}
+
+#[test]
+fn test_deadlock() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_sync_object(SyncObject::new("a", SyncKind::Mutex));
+    rq.add_sync_object(SyncObject::new("b", SyncKind::Mutex));
+
+    rq.insert(task(1, vec![lock("a"), compute(2), lock("b"), unlock("b"), unlock("a")]).spawn());
+    rq.insert(task(2, vec![lock("b"), compute(2), lock("a"), unlock("a"), unlock("b")]).spawn());
+
+    run(&mut rq, &mut sysclock);
+    let deadlocked: Vec<(u16, String)> = rq.get_deadlocked()
+        .iter()
+        .map(|task| (task.get_id(), task.get_sync().unwrap().1.to_string()))
+        .collect();
+    assert_eq!(deadlocked, [(1, String::from("b")), (2, String::from("a"))]);
+}
+
+#[test]
+fn test_refused_steps() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.add_sync_object(SyncObject::new("m", SyncKind::Mutex));
+    rq.add_sync_object(SyncObject::new("c", SyncKind::CondVar));
+
+    // steps the objects refuse leave the tasks blocked instead of the runner dead
+    rq.insert(task(1, vec![compute(1), unlock("m"), compute(1)]).spawn());
+    rq.insert(task(2, vec![lock("m"), compute(1), lock("m"), unlock("m")]).spawn());
+    rq.insert(task(3, vec![Phase::WaitOn(String::from("c"), Some(String::from("m"))), compute(1)]).spawn());
+    rq.insert(task(4, vec![compute(3), Phase::Signal(String::from("m")), compute(1)]).spawn());
+
+    run(&mut rq, &mut sysclock);
+    let mut deadlocked: Vec<u16> = rq.get_deadlocked().iter().map(|task| task.get_id()).collect();
+    deadlocked.sort();
+    assert_eq!(deadlocked, [1, 2, 3, 4]);
+    assert_eq!(rq.get_sync_objects()[0].get_holders(), [2]);
+    assert!(rq.get_sync_objects()[0].get_waiters().is_empty());
+}
+
+#[test]
+fn test_workload_sync() {
+    let src = r#"
+[[sync]]
+name = "buf"
+kind = "mutex"
+
+[[sync]]
+name = "items"
+kind = "condvar"
+
+[[sync]]
+name = "slots"
+kind = "semaphore"
+count = 4
+
+[[sync]]
+name = "futex"
+kind = "waitqueue"
+
+[[task]]
+id = 1
+phases = [{ lock = "buf" }, { wait_on = "items", mutex = "buf" }, { unlock = "buf" }, { wait_on = "futex" }]
+
+[[task]]
+id = 2
+phases = [{ lock = "slots" }, { signal = "items" }, { broadcast = "futex" }, { unlock = "slots" }, { compute = 1 }]
+"#;
+    let workload = spec::parse_workload(src).unwrap();
+    let kinds: Vec<SyncKind> = workload.sync_objects.iter().map(|object| object.get_kind()).collect();
+    assert_eq!(kinds, [SyncKind::Mutex, SyncKind::CondVar, SyncKind::Semaphore(4), SyncKind::WaitQueue]);
+    assert_eq!(
+        workload.tasks[0].get_phases().unwrap()[1],
+        Phase::WaitOn(String::from("items"), Some(String::from("buf")))
+    );
+
+    let again = spec::parse(&format!("{}\n{}", &src[..src.find("[[task]]").unwrap()], spec::to_toml(&workload.tasks))).unwrap();
+    assert_eq!(again[0].get_phases(), workload.tasks[0].get_phases());
+    assert_eq!(again[1].get_phases(), workload.tasks[1].get_phases());
+
+    let objects = &src[..src.find("[[task]]").unwrap()];
+    let error_line = |phases: &str| {
+        let src = format!("{}[[task]]\nid = 1\nphases = {}\n", objects, phases);
+        spec::parse(&src).err().map(|err| err.get_line())
+    };
+    let line = objects.matches('\n').count() + 3;
+    assert_eq!(error_line("[{ lock = \"items\" }]"), Some(line));
+    assert_eq!(error_line("[{ lock = \"nope\" }]"), Some(line));
+    assert_eq!(error_line("[{ wait_on = \"items\" }]"), Some(line));
+    assert_eq!(error_line("[{ wait_on = \"futex\", mutex = \"buf\" }]"), Some(line));
+    assert_eq!(error_line("[{ wait_on = \"items\", mutex = \"slots\" }]"), Some(line));
+    assert_eq!(error_line("[{ signal = \"buf\" }]"), Some(line));
+    assert_eq!(error_line("[{ compute = 1, mutex = \"buf\" }]"), Some(line));
+    assert_eq!(error_line("[{ signal = \"futex\" }]"), None);
+
+    // unbalanced mutexes are caught at the phase that trips over them
+    assert_eq!(error_line("[{ unlock = \"buf\" }]"), Some(line));
+    assert_eq!(error_line("[{ wait_on = \"items\", mutex = \"buf\" }]"), Some(line));
+    assert_eq!(error_line("[\n{ lock = \"buf\" },\n{ lock = \"buf\" }]"), Some(line + 2));
+    assert_eq!(error_line("[{ repeat = 2, phases = [{ lock = \"buf\" }] }]"), Some(line));
+    assert_eq!(error_line("[{ lock = \"buf\" }, { fork = [{ unlock = \"buf\" }] }]"), Some(line));
+    assert_eq!(error_line("[{ repeat = 1, phases = [{ lock = \"buf\" }] }, { unlock = \"buf\" }]"), None);
+    assert_eq!(error_line("[{ lock = \"slots\" }, { lock = \"slots\" }, { unlock = \"slots\" }]"), None);
+    assert_eq!(error_line("[{ lock = \"buf\" }, { exec = \"x\", phases = [{ unlock = \"buf\" }] }]"), None);
+
+    assert_eq!(spec::parse("[[sync]]\nname = \"m\"\nkind = \"mutex\"\ncount = 2\n").err().unwrap().get_line(), 4);
+    assert_eq!(spec::parse("[[sync]]\nname = \"m\"\nkind = \"spinlock\"\n").err().unwrap().get_line(), 3);
+}
@@ -18,17 +18,16 @@ fn test_execute() {
     tasks.push(Pointer::new(&mut my_task));
     tasks.push(Pointer::new(&mut other_task));
 
-    while tasks.len() > 0 {
-        let y = tasks.len();
+    while !tasks.is_empty() {
         let mut to_remove: Vec<usize> = Vec::new();
-        for i in 0..y {
-            let mut task = tasks[i];
+        for (i, task) in tasks.iter().enumerate() {
+            let mut task = *task;
             println!("Performing sequence for task {:?}", task.get_id());
             match task.get_status() {
                 TaskStatus::Running => {
                     match task.get_id() {
                         1 => {
-                            x = x + 1;
+                            x += 1;
                             println!("Executing task 1");
                         },
                         id => println!("Executing task {:?}", id)
@@ -41,10 +40,10 @@ fn test_execute() {
                 TaskStatus::Idle => {
                     task.io_cycle();
                 },
-                TaskStatus::New => {
+                TaskStatus::New | TaskStatus::Stopped => {
                     task.schedule();
                 },
-                TaskStatus::Terminated => {
+                TaskStatus::Terminated | TaskStatus::Zombie | TaskStatus::Dead => {
                     to_remove.push(i);
                 }
             };
@@ -75,7 +74,7 @@ fn test_preempt() {
     tasks.push(Pointer::new(&mut my_task));
     tasks.push(Pointer::new(&mut other_task));
 
-    while tasks.len() > 0 {
+    while !tasks.is_empty() {
         let y = tasks.len();
         let mut to_remove: Vec<usize> = Vec::new();
         
@@ -101,15 +100,14 @@ fn test_preempt() {
         if task.get_status() == TaskStatus::Running {
             match task.get_id() {
                 1 => {
-                    x = x + 1;
+                    x += 1;
                 },
                 id => println!("Executing task {:?}", id)
             };
             task.cpu_cycle();
         }
 
-        for i in 0..y {
-            let curr = tasks[i];
+        for (i, curr) in tasks.iter().enumerate() {
             if curr.get_status() == TaskStatus::Terminated {
                 to_remove.push(i);
             }
@@ -0,0 +1,135 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+mod common;
+
+use cfs::proc::task::TaskChar;
+use cfs::trace::{chrome, ftrace, EventKind, Slice, SwitchState, Trace, TICK_US};
+
+use common::Sim;
+
+fn traced(tasks: Vec<TaskChar>) -> Trace {
+    let mut sim = Sim::new(tasks.iter().map(TaskChar::spawn), |rq| rq.set_tracing(true));
+    sim.run();
+    sim.rq.get_trace().unwrap().clone()
+}
+
+fn slices(trace: &Trace) -> Vec<(u16, u128, u128, Option<SwitchState>)> {
+    trace.slices.iter().map(|slice| (slice.id, slice.start, slice.end, slice.state)).collect()
+}
+
+#[test]
+fn test_merge_slices() {
+    let mut trace = Trace::new();
+    trace.run(0, 1, 0);
+    trace.switch_out(1, SwitchState::Preempted);
+    trace.run(0, 1, 1);
+    trace.switch_out(1, SwitchState::Preempted);
+    trace.run(0, 2, 2);
+
+    // picking the same task again is not a switch
+    assert_eq!(trace.slices[0], Slice { cpu: 0, id: 1, start: 0, end: 2, state: Some(SwitchState::Preempted) });
+    assert_eq!(trace.slices[1].state, None);
+    let events: Vec<(u16, u128, EventKind)> = trace.events.iter().map(|event| (event.id, event.time, event.kind)).collect();
+    assert_eq!(events, [
+        (1, 0, EventKind::Switch { prev: None, prev_state: SwitchState::Preempted, next: Some(1) }),
+        (1, 2, EventKind::Preempt),
+        (2, 2, EventKind::Switch { prev: Some(1), prev_state: SwitchState::Preempted, next: Some(2) })
+    ]);
+
+    trace.switch_out(2, SwitchState::Sleeping);
+    trace.idle(0, 3);
+    trace.idle(0, 4);
+    let switch = EventKind::Switch { prev: Some(2), prev_state: SwitchState::Sleeping, next: None };
+    assert_eq!(trace.events[3..].iter().map(|event| event.kind).collect::<Vec<_>>(), [switch]);
+}
+
+#[test]
+fn test_schedule() {
+    let mut second = TaskChar::new(2, 3, 3, 0, 1);
+    second.set_arrival(10);
+    let mut third = TaskChar::new(3, 2, 2, 0, 1);
+    third.set_arrival(10);
+    let trace = traced(vec![TaskChar::new(1, 5, 5, 0, 1), second, third]);
+
+    let dead = Some(SwitchState::Dead);
+    let preempted = Some(SwitchState::Preempted);
+    assert_eq!(
+        slices(&trace),
+        [(1, 0, 5, dead), (2, 10, 11, preempted), (3, 11, 12, preempted), (2, 12, 13, preempted), (3, 13, 14, dead), (2, 14, 15, dead)]
+    );
+
+    let events: Vec<(u128, u16, EventKind)> = trace.events.iter()
+        .filter(|event| !matches!(event.kind, EventKind::Switch { .. }))
+        .map(|event| (event.time, event.id, event.kind))
+        .collect();
+    assert_eq!(&events[..4], [(0, 1, EventKind::WakeupNew), (5, 1, EventKind::Exit), (10, 2, EventKind::WakeupNew), (10, 3, EventKind::WakeupNew)]);
+    assert_eq!(events.iter().filter(|(_, _, kind)| *kind == EventKind::Preempt).count(), 3);
+    assert_eq!(trace.name(2), "task-2");
+}
+
+#[test]
+fn test_blocked() {
+    let trace = traced(vec![TaskChar::new(1, 4, 2, 3, 1)]);
+
+    // the io burst is not a preemption, and waking from it is not a new task
+    assert_eq!(trace.slices[0].state, Some(SwitchState::Blocked));
+    assert_eq!(trace.slices.last().unwrap().state, Some(SwitchState::Dead));
+    assert_eq!(trace.events.iter().filter(|event| event.kind == EventKind::Wakeup).count(), 1);
+    assert!(trace.events.iter().all(|event| event.kind != EventKind::Preempt));
+}
+
+#[test]
+fn test_chrome() {
+    let trace = traced(vec![TaskChar::new(1, 4, 2, 3, 1), TaskChar::new(2, 3, 1, 0, 1)]);
+    let value = chrome::to_value(&trace);
+    let events = value["traceEvents"].as_array().unwrap();
+
+    let complete: Vec<_> = events.iter().filter(|event| event["ph"] == "X").collect();
+    assert_eq!(complete.len(), trace.slices.len());
+    for (event, slice) in complete.iter().zip(trace.slices.iter()) {
+        assert_eq!(event["tid"], 0);
+        assert_eq!(event["ts"].as_u64().unwrap() as u128, slice.start * TICK_US);
+        assert_eq!(event["args"]["pid"], slice.id);
+    }
+    let instants: Vec<_> = events.iter().filter(|event| event["ph"] == "i").collect();
+    let switches = trace.events.iter().filter(|event| matches!(event.kind, EventKind::Switch { .. })).count();
+    assert_eq!(instants.len(), trace.events.len() - switches);
+    assert!(instants.iter().any(|event| event["name"] == "sched_wakeup_new"));
+    assert!(events.iter().any(|event| event["ph"] == "M" && event["args"]["name"] == "CPU 0"));
+
+    // the output parses back
+    let parsed: serde_json::Value = serde_json::from_str(&chrome::to_json(&trace)).unwrap();
+    assert_eq!(parsed, value);
+}
+
+#[test]
+fn test_ftrace() {
+    let mut raw = TaskChar::new(1, 2, 1, 2, 1024);
+    raw.set_name("kworker one");
+    let mut late = TaskChar::new(2, 1, 1, 0, 15);
+    late.set_arrival(1);
+    let text = ftrace::to_text(&traced(vec![raw, late]));
+
+    let lines: Vec<&str> = text.lines().filter(|line| !line.starts_with('#')).collect();
+    assert!(text.starts_with("# tracer: nop\n"));
+    assert!(text.contains(&format!("# entries-in-buffer/entries-written: {}/{}   #P:1\n", lines.len(), lines.len())));
+    assert_eq!(
+        lines[..2],
+        [
+            "          <idle>-0       [000] d...      0.000000: sched_wakeup_new: comm=kworker_one pid=1 prio=120 target_cpu=000",
+            "          <idle>-0       [000] d...      0.000000: sched_switch: prev_comm=swapper/0 prev_pid=0 prev_prio=120 prev_state=R ==> next_comm=kworker_one next_pid=1 next_prio=120"
+        ]
+    );
+    // task 1 blocks on io after its first tick and task 2 runs next
+    assert!(lines[2].contains("sched_wakeup_new: comm=task-2 pid=2 prio=139"));
+    assert!(lines[3].ends_with("prev_comm=kworker_one prev_pid=1 prev_prio=120 prev_state=D ==> next_comm=task-2 next_pid=2 next_prio=139"));
+    assert!(lines[3].starts_with("     kworker_one-1       [000] d...      0.001000: sched_switch:"));
+
+    // the cpu goes idle once the last task is gone
+    assert!(lines.last().unwrap().ends_with("prev_state=X ==> next_comm=swapper/0 next_pid=0 next_prio=120"));
+    for event in ["sched_wakeup:", "sched_process_exit:"] {
+        assert!(lines.iter().any(|line| line.contains(event)));
+    }
+}
@@ -0,0 +1,244 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::dist::Distribution;
+use cfs::proc::task::{Action, SchedPolicy};
+use cfs::workload::{legacy, spec};
+
+const WORKLOAD: &str = r#"
+[[task]]
+id = 1
+name = "make"
+cpu_time = 500
+cpu_burst = 20
+io_burst = 5
+nice = 5
+group = 1
+
+[[task]]
+id = 2
+name = "shell"
+arrival = 40
+cpu_time = 30
+cpu_burst = 2
+weight = 7
+policy = "batch"
+affinity = [0]
+actions = [{ at = 3, action = "yield" }, { at = 10, action = "sleep", ticks = 4 }]
+"#;
+
+#[test]
+fn test_parse() {
+    let tasks = spec::parse(WORKLOAD).unwrap();
+    assert_eq!(tasks.len(), 2);
+
+    let make = &tasks[0];
+    assert_eq!(make.get_id(), 1);
+    assert_eq!(make.get_name(), "make");
+    assert_eq!(make.get_arrival(), 0);
+    assert_eq!(make.get_cpu_time(), 500);
+    assert_eq!(make.get_cpu_burst_length(), 20);
+    assert_eq!(make.get_io_burst_length(), 5);
+    assert_eq!(make.get_weight(), 335);
+    assert_eq!(make.get_session(), 1);
+    assert_eq!(make.get_policy(), SchedPolicy::Normal);
+
+    let shell = &tasks[1];
+    assert_eq!(shell.get_arrival(), 40);
+    assert_eq!(shell.get_weight(), 7);
+    assert_eq!(shell.get_policy(), SchedPolicy::Batch);
+    assert_eq!(shell.get_affinity(), 1);
+    assert_eq!(shell.get_actions(), &[(3, Action::Yield), (10, Action::Sleep(4))]);
+}
+
+fn error_line(src: &str) -> usize {
+    spec::parse(src).err().unwrap().get_line()
+}
+
+#[test]
+fn test_validation_errors() {
+    let duplicate = "[[task]]\nid = 1\ncpu_time = 5\ncpu_burst = 5\n\n[[task]]\nid = 1\ncpu_time = 5\ncpu_burst = 5\n";
+    assert_eq!(error_line(duplicate), 7);
+
+    let both = "[[task]]\nid = 1\ncpu_time = 5\ncpu_burst = 5\nweight = 2\nnice = 3\n";
+    assert_eq!(error_line(both), 6);
+
+    let unknown = "[[task]]\nid = 1\ncpu_time = 5\ncpu_burts = 5\n";
+    assert_eq!(error_line(unknown), 4);
+
+    let policy = "[[task]]\nid = 1\ncpu_time = 5\ncpu_burst = 5\npolicy = \"fifo\"\n";
+    assert_eq!(error_line(policy), 5);
+
+    let affinity = "[[task]]\nid = 1\ncpu_time = 5\ncpu_burst = 5\naffinity = [3]\n";
+    assert_eq!(error_line(affinity), 5);
+
+    // a cpu beyond the simulated ones is refused rather than dropped
+    let beyond = "[[task]]\nid = 1\ncpu_time = 5\ncpu_burst = 5\naffinity = [0, 1]\n";
+    assert_eq!(error_line(beyond), 5);
+    let none = "[[task]]\nid = 1\ncpu_time = 5\ncpu_burst = 5\naffinity = []\n";
+    assert_eq!(error_line(none), 5);
+
+    let sleep = "[[task]]\nid = 1\ncpu_time = 5\ncpu_burst = 5\nactions = [\n  { at = 1, action = \"sleep\" },\n]\n";
+    assert_eq!(error_line(sleep), 6);
+
+    let syntax = "[[task]]\nid = 1\ncpu_time = = 5\n";
+    assert_eq!(error_line(syntax), 3);
+
+    let err = spec::parse(both).err().unwrap();
+    assert_eq!(err.to_string(), "line 6: a task has either a weight or a nice value");
+}
+
+#[test]
+fn test_json_and_yaml() {
+    let json = r#"{
+  "task": [
+    { "id": 1, "name": "make", "cpu_time": 500, "cpu_burst": 20, "io_burst": 5, "nice": 5, "group": 1 },
+    { "id": 2, "name": "shell", "arrival": 40, "cpu_time": 30, "cpu_burst": 2, "weight": 7, "policy": "batch",
+      "affinity": [0], "actions": [{ "at": 3, "action": "yield" }, { "at": 10, "action": "sleep", "ticks": 4 }] }
+  ]
+}"#;
+    let yaml = r#"
+task:
+  - { id: 1, name: make, cpu_time: 500, cpu_burst: 20, io_burst: 5, nice: 5, group: 1 }
+  - id: 2
+    name: shell
+    arrival: 40
+    cpu_time: 30
+    cpu_burst: 2
+    weight: 7
+    policy: batch
+    affinity: [0]
+    actions: [{ at: 3, action: yield }, { at: 10, action: sleep, ticks: 4 }]
+"#;
+    let toml = spec::to_toml(&spec::parse(WORKLOAD).unwrap());
+    for workload in [spec::parse_json_workload(json).unwrap(), spec::parse_yaml_workload(yaml).unwrap()] {
+        assert_eq!(spec::to_toml(&workload.tasks), toml);
+    }
+
+    // syntax errors are on the line they are found on, the rest name the table
+    let err = spec::parse_json_workload("{\n  \"task\": [,]\n}").err().unwrap();
+    assert_eq!(err.get_line(), 2);
+    let err = spec::parse_yaml_workload("task:\n  - id: 1\n    cpu_time: 5\n    cpu_burst: 5\n  - id: 2\n    cpu_time: 5\n    cpu_burst: 5\n    nice: 40\n").err().unwrap();
+    assert_eq!(err.to_string(), "task 2: nice must be within [-20, 19]");
+}
+
+#[test]
+fn test_legacy() {
+    let tasks = legacy::parse("100 10 5 4\n\n50 5 0 2 3 idle 12\n").unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].get_id(), 1);
+    assert_eq!(tasks[1].get_id(), 3);
+    assert_eq!(tasks[1].get_session(), 3);
+    assert_eq!(tasks[1].get_policy(), SchedPolicy::Idle);
+    assert_eq!(tasks[1].get_arrival(), 12);
+
+    // named columns can come in any order and leave the others at their defaults
+    let tasks = legacy::parse("100 10 5 4 arrival=12\n50 5 0 2 3 arrival=7 policy=batch\n").unwrap();
+    assert_eq!(tasks[0].get_session(), 0);
+    assert_eq!(tasks[0].get_policy(), SchedPolicy::Normal);
+    assert_eq!(tasks[0].get_arrival(), 12);
+    assert_eq!(tasks[1].get_session(), 3);
+    assert_eq!(tasks[1].get_policy(), SchedPolicy::Batch);
+    assert_eq!(tasks[1].get_arrival(), 7);
+
+    assert_eq!(legacy::parse("100 10 5 4 arrival=1 3\n").err().unwrap().get_line(), 1);
+    assert_eq!(legacy::parse("100 10 5 4 3 session=2\n").err().unwrap().get_line(), 1);
+    assert_eq!(legacy::parse("100 10 5 4 start=2\n").err().unwrap().get_line(), 1);
+    assert_eq!(legacy::parse("100 10 5 4\n100 ten 5 4\n").err().unwrap().get_line(), 2);
+    assert_eq!(legacy::parse("100 10\n").err().unwrap().get_line(), 1);
+    assert_eq!(legacy::parse("100 0 5 4\n").err().unwrap().get_line(), 1);
+}
+
+#[test]
+fn test_migration() {
+    let old = legacy::parse("100 10 5 4\n50 5 0 2 3 batch 12\n").unwrap();
+    let new = spec::parse(&spec::to_toml(&old)).unwrap();
+
+    assert_eq!(old.len(), new.len());
+    for (before, after) in old.iter().zip(new.iter()) {
+        assert_eq!(before.get_id(), after.get_id());
+        assert_eq!(before.get_name(), after.get_name());
+        assert_eq!(before.get_cpu_time(), after.get_cpu_time());
+        assert_eq!(before.get_cpu_burst_length(), after.get_cpu_burst_length());
+        assert_eq!(before.get_io_burst_length(), after.get_io_burst_length());
+        assert_eq!(before.get_weight(), after.get_weight());
+        assert_eq!(before.get_session(), after.get_session());
+        assert_eq!(before.get_policy(), after.get_policy());
+        assert_eq!(before.get_arrival(), after.get_arrival());
+    }
+}
+
+#[test]
+fn test_distributions() {
+    let src = "[[task]]\nid = 1\ncpu_time = 50\ncpu_burst = { dist = \"exponential\", mean = 8 }\nio_burst = { dist = \"empirical\", bins = [[1, 9], [40, 1]] }\n";
+    let tasks = spec::parse(src).unwrap();
+    assert_eq!(tasks[0].get_cpu_burst(), &Distribution::Exponential { mean: 8.0 });
+    assert_eq!(tasks[0].get_io_burst(), &Distribution::Empirical(vec![(1, 9), (40, 1)]));
+    assert_eq!(tasks[0].get_cpu_burst_length(), 8);
+
+    let again = spec::parse(&spec::to_toml(&tasks)).unwrap();
+    assert_eq!(again[0].get_cpu_burst(), tasks[0].get_cpu_burst());
+    assert_eq!(again[0].get_io_burst(), tasks[0].get_io_burst());
+
+    let missing = "[[task]]\nid = 1\ncpu_time = 50\n\ncpu_burst = { dist = \"pareto\", scale = 2 }\n";
+    assert_eq!(error_line(missing), 5);
+
+    let invalid = "[[task]]\nid = 1\ncpu_time = 50\ncpu_burst = { dist = \"uniform\", min = 9, max = 2 }\n";
+    assert_eq!(error_line(invalid), 4);
+}
+
+#[test]
+fn test_phases() {
+    let src = "[[task]]\nid = 1\nphases = [\n  { compute = 5 },\n  { repeat = 3, phases = [{ sleep = 4 }, { compute = 2 }] },\n  { policy = \"batch\" },\n]\n";
+    let tasks = spec::parse(src).unwrap();
+    assert_eq!(tasks[0].get_cpu_time(), u64::MAX);
+    assert_eq!(tasks[0].get_phases().unwrap().len(), 3);
+
+    let again = spec::parse(&spec::to_toml(&tasks)).unwrap();
+    assert_eq!(again[0].get_phases(), tasks[0].get_phases());
+
+    let two_kinds = "[[task]]\nid = 1\nphases = [\n  { compute = 5 },\n  { compute = 5, nice = 3 },\n]\n";
+    assert_eq!(error_line(two_kinds), 5);
+
+    let endless = "[[task]]\nid = 1\nphases = [{ repeat = \"forever\", phases = [{ compute = 5 }] }]\n";
+    assert_eq!(error_line(endless), 2);
+
+    let both = "[[task]]\nid = 1\ncpu_time = 5\ncpu_burst = 5\nphases = [{ compute = 5 }]\n";
+    assert_eq!(error_line(both), 4);
+}
+
+#[test]
+fn test_fork_phases() {
+    let src = "[[task]]\nid = 1\nphases = [\n  { fork = [{ exec = \"cc1\", phases = [{ compute = 3 }] }] },\n  { wait = true },\n  { exit = true },\n]\n";
+    let tasks = spec::parse(src).unwrap();
+    let again = spec::parse(&spec::to_toml(&tasks)).unwrap();
+    assert_eq!(again[0].get_phases(), tasks[0].get_phases());
+
+    let exec_alone = "[[task]]\nid = 1\nphases = [\n  { exec = \"cc1\" },\n]\n";
+    assert_eq!(error_line(exec_alone), 4);
+
+    let endless_child = "[[task]]\nid = 1\ncpu_time = 10\nphases = [\n  { fork = [{ repeat = \"forever\", phases = [{ compute = 1 }] }] },\n]\n";
+    assert_eq!(error_line(endless_child), 5);
+}
+
+#[test]
+fn test_periodic() {
+    let src = "[[task]]\nid = 1\nperiod = 10\nwcet = 3\njitter = 2\njobs = 5\n";
+    let tasks = spec::parse(src).unwrap();
+    let periodic = tasks[0].get_periodic().unwrap();
+    assert_eq!((periodic.period, periodic.offset, periodic.wcet, periodic.jitter, periodic.jobs), (10, 0, 3, 2, 5));
+    assert_eq!(tasks[0].get_cpu_burst_length(), 3);
+
+    let again = spec::parse(&spec::to_toml(&tasks)).unwrap();
+    assert_eq!(again[0].get_periodic(), tasks[0].get_periodic());
+
+    let no_jobs = "[[task]]\nid = 1\ncpu_burst = 3\n\nperiod = 10\nwcet = 3\n";
+    assert_eq!(error_line(no_jobs), 5);
+
+    let cpu_time = "[[task]]\nid = 1\ncpu_time = 9\ncpu_burst = 3\nperiod = 10\nwcet = 3\njobs = 2\n";
+    assert_eq!(error_line(cpu_time), 3);
+
+    let stray = "[[task]]\nid = 1\ncpu_time = 9\ncpu_burst = 3\njitter = 2\n";
+    assert_eq!(error_line(stray), 5);
+}
@@ -0,0 +1,115 @@
+#![cfg(test)]
+
+extern crate rust_cfs as cfs;
+
+use cfs::proc::task::{Action, SchedPolicy, Task};
+use cfs::sched::{clock::Clock, fair::FairAlgorithm};
+
+fn tick(rq: &mut FairAlgorithm, sysclock: &mut Clock) -> Option<u16> {
+    let id = rq.run();
+    rq.idle();
+    sysclock.tick();
+    id
+}
+
+#[test]
+fn test_yield_skips_task() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_slice(100);
+
+    let mut yielder = Task::new(1, 20, 20, 0, 0, 1000);
+    yielder.add_action(2, Action::Yield);
+    rq.insert(yielder);
+
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(1));
+    rq.insert(Task::new(2, 3, 3, 0, 0, 1));
+
+    // the yielding task is still leftmost, but the other task is picked
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(1));
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(2));
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(2));
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(2));
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(1));
+
+    while tick(&mut rq, &mut sysclock).is_some() {}
+
+    let exited = rq.get_exited();
+    assert_eq!(exited[0].get_id(), 2);
+    assert_eq!(exited[1].get_id(), 1);
+
+    let stats = exited[1].get_stats();
+    assert_eq!(stats.nr_yields, 1);
+    assert_eq!(stats.nr_involuntary_switches, 1);
+    assert_eq!(stats.nr_voluntary_switches, 1);
+}
+
+#[test]
+fn test_yield_alone() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_slice(100);
+
+    let mut task = Task::new(1, 10, 10, 0, 0, 1);
+    for offset in 1..5 {
+        task.add_action(offset, Action::Yield);
+    }
+    rq.insert(task);
+
+    let mut ran = 0;
+    while tick(&mut rq, &mut sysclock).is_some() {
+        ran += 1;
+    }
+    assert_eq!(ran, 10);
+
+    let stats = rq.get_exited()[0].get_stats();
+    assert_eq!(stats.nr_yields, 4);
+    assert_eq!(stats.nr_involuntary_switches, 0);
+}
+
+#[test]
+fn test_sleep() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+
+    let mut task = Task::new(1, 6, 100, 0, 0, 1);
+    task.add_action(3, Action::Sleep(5));
+    rq.insert(task);
+
+    let mut trace = Vec::new();
+    while !rq.is_finished() {
+        trace.push(tick(&mut rq, &mut sysclock));
+    }
+
+    let expected = vec![
+        Some(1), Some(1), Some(1),
+        None, None, None, None, None,
+        Some(1), Some(1), Some(1)
+    ];
+    assert_eq!(trace, expected);
+    assert_eq!(rq.get_exited()[0].get_stats().nr_voluntary_switches, 2);
+}
+
+#[test]
+fn test_cond_resched() {
+    let mut sysclock = Clock::new();
+    let mut rq = FairAlgorithm::new(&mut sysclock);
+    rq.set_slice(100);
+
+    let mut task = Task::new(1, 10, 10, 0, 0, 1);
+    task.add_action(1, Action::CondResched);
+    task.add_action(4, Action::CondResched);
+    rq.insert(task);
+
+    // nothing else wants the cpu at the first resched point
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(1));
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(1));
+
+    let mut batch = Task::new(2, 5, 5, 0, sysclock.time(), 1);
+    batch.set_policy(SchedPolicy::Batch);
+    rq.insert(batch);
+
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(1));
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(1));
+    assert_eq!(tick(&mut rq, &mut sysclock), Some(2));
+}